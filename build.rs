@@ -1,7 +1,7 @@
 // spell-checker:ignore (vars) krate
 
 use std::env;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Write;
 use std::path::Path;
 
@@ -15,6 +15,10 @@ pub fn main() {
     const ENV_FEATURE_PREFIX: &str = "CARGO_FEATURE_";
     const OVERRIDE_PREFIX: &str = "oe_";
 
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let manifest = fs::read_to_string(Path::new(&manifest_dir).join("Cargo.toml")).unwrap();
+    check_utilities_reachable(&manifest);
+
     let out_dir = env::var("OUT_DIR").unwrap();
     println!("cargo:warning=out_dir={}", out_dir);
 
@@ -68,3 +72,86 @@ pub fn main() {
 
     mf.flush().unwrap();
 }
+
+/// Fails the build when a utility of the workspace can never make it into the
+/// multicall binary: every `src/oe/*` member needs a path dependency here, and
+/// that dependency has to be listed by one of the features.
+fn check_utilities_reachable(manifest: &str) {
+    let entries = manifest_entries(manifest);
+    let quoted = |value: &str| -> Vec<String> {
+        value
+            .split('"')
+            .skip(1)
+            .step_by(2)
+            .map(String::from)
+            .collect()
+    };
+
+    let members = entries
+        .iter()
+        .filter(|(section, key, _)| section == "workspace" && key == "members")
+        .flat_map(|(_, _, value)| quoted(value))
+        .filter(|member| member.starts_with("src/oe/"));
+    let features: Vec<Vec<String>> = entries
+        .iter()
+        .filter(|(section, _, _)| section == "features")
+        .map(|(_, _, value)| quoted(value))
+        .collect();
+
+    for member in members {
+        let krate = entries
+            .iter()
+            .filter(|(section, _, _)| section == "dependencies")
+            .find(|(_, _, value)| {
+                value
+                    .trim_matches(|c| c == '{' || c == '}')
+                    .split(',')
+                    .filter_map(|field| field.split_once('='))
+                    .any(|(key, path)| {
+                        key.trim() == "path" && path.trim().trim_matches('"') == member
+                    })
+            })
+            .map(|(_, krate, _)| krate)
+            .unwrap_or_else(|| {
+                panic!(
+                    "workspace utility {} is not a dependency of easybox; add it to [dependencies]",
+                    member
+                )
+            });
+        if !features.iter().any(|list| list.contains(krate)) {
+            panic!(
+                "workspace utility {} is not enabled by any feature; add \"{}\" to the `linux` feature",
+                member, krate
+            );
+        }
+    }
+}
+
+/// Splits Cargo.toml into `(section, key, value)` entries, just far enough for
+/// [check_utilities_reachable]: arrays may span lines, inline tables may not.
+fn manifest_entries(manifest: &str) -> Vec<(String, String, String)> {
+    let mut entries = Vec::new();
+    let mut section = String::new();
+    let mut pending: Option<(String, String)> = None;
+    for line in manifest.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if let Some((key, mut value)) = pending.take() {
+            value.push_str(line);
+            if line.contains(']') {
+                entries.push((section.clone(), key, value));
+            } else {
+                pending = Some((key, value));
+            }
+        } else if line.starts_with('[') {
+            section = line.trim_matches(|c| c == '[' || c == ']').to_string();
+        } else if let Some((key, value)) = line.split_once('=') {
+            let (key, value) = (key.trim().to_string(), value.trim().to_string());
+            if value.starts_with('[') && !value.contains(']') {
+                pending = Some((key, value));
+            } else {
+                entries.push((section.clone(), key, value));
+            }
+        }
+    }
+    entries
+}
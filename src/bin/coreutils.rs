@@ -10,10 +10,13 @@ use clap_complete::Shell;
 use std::cmp;
 use std::ffi::OsStr;
 use std::ffi::OsString;
-use std::io::{self, Write};
+use std::fs;
+use std::io::{self, ErrorKind, Write};
+use std::os::unix::fs::symlink;
 use std::path::{Path, PathBuf};
 use std::process;
 use uucore::display::Quotable;
+use uucore::error::strip_errno;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -21,12 +24,11 @@ include!(concat!(env!("OUT_DIR"), "/openeuler_map.rs"));
 
 fn usage<T>(utils: &UtilityMap<T>, name: &str) {
     println!("{} {} (multi-call binary)\n", name, VERSION);
-    println!("Usage: {} [function [arguments...]]\n", name);
+    println!("Usage: {} [function [arguments...]]", name);
+    println!("       {} --install [-s] [DIR]", name);
+    println!("       {} --list[-full]\n", name);
     println!("Currently defined functions:\n");
-    #[allow(clippy::map_clone)]
-    let mut utils: Vec<&str> = utils.keys().map(|&s| s).collect();
-    utils.sort_unstable();
-    let display_list = utils.join(", ");
+    let display_list = sorted_utils(utils).join(", ");
     let width = cmp::min(textwrap::termwidth(), 100) - 4 * 2; // (opinion/heuristic) max 100 chars wide with 4 character side indentions
     println!(
         "{}",
@@ -34,6 +36,104 @@ fn usage<T>(utils: &UtilityMap<T>, name: &str) {
     );
 }
 
+/// Where an applet lives in a root filesystem, following BusyBox's layout:
+/// boot-critical tools in `/bin` and `/sbin`, everything else in `/usr/bin`.
+fn install_path(util: &str) -> PathBuf {
+    let dir = match util {
+        "base64" | "mount" | "pidof" | "usleep" => "/bin",
+        "hwclock" => "/sbin",
+        _ => "/usr/bin",
+    };
+    Path::new(dir).join(util)
+}
+
+fn sorted_utils<T>(utils: &UtilityMap<T>) -> Vec<&'static str> {
+    let mut utils: Vec<&str> = utils.keys().copied().collect();
+    utils.sort_unstable();
+    utils
+}
+
+/// Prints every applet, one per line; with `full` at its [install_path].
+fn list<T>(
+    utils: &UtilityMap<T>,
+    name: &str,
+    full: bool,
+    args: impl Iterator<Item = OsString>,
+) -> ! {
+    no_extra_operand(name, args);
+    for util in sorted_utils(utils) {
+        if full {
+            println!("{}", install_path(util).display());
+        } else {
+            println!("{}", util);
+        }
+    }
+    process::exit(0);
+}
+
+/// Links every applet to this binary, as hard links unless `-s` asks for
+/// symbolic ones, either all in DIR or each at its [install_path]. Like
+/// BusyBox, applets that already exist are left alone.
+fn install<T>(utils: &UtilityMap<T>, name: &str, mut args: impl Iterator<Item = OsString>) -> ! {
+    let mut symbolic = false;
+    let mut dir = args.next();
+    if dir.as_deref() == Some(OsStr::new("-s")) {
+        symbolic = true;
+        dir = args.next();
+    }
+    no_extra_operand(name, args);
+
+    let fail = |path: &Path, e: io::Error| {
+        eprintln!("{}: {}: {}", name, path.maybe_quote(), strip_errno(&e));
+    };
+    let dir = dir.map(PathBuf::from);
+    if let Some(dir) = &dir {
+        if let Err(e) = fs::read_dir(dir) {
+            fail(dir, e);
+            process::exit(1);
+        }
+    }
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            eprintln!(
+                "{}: cannot locate own executable: {}",
+                name,
+                strip_errno(&e)
+            );
+            process::exit(1);
+        }
+    };
+
+    let mut code = 0;
+    for util in sorted_utils(utils) {
+        let link = match &dir {
+            Some(dir) => dir.join(util),
+            None => install_path(util),
+        };
+        let result = if symbolic {
+            symlink(&exe, &link)
+        } else {
+            fs::hard_link(&exe, &link)
+        };
+        match result {
+            Err(e) if e.kind() != ErrorKind::AlreadyExists => {
+                fail(&link, e);
+                code = 1;
+            }
+            _ => {}
+        }
+    }
+    process::exit(code);
+}
+
+fn no_extra_operand(name: &str, mut args: impl Iterator<Item = OsString>) {
+    if let Some(arg) = args.next() {
+        eprintln!("{}: extra operand {}", name, arg.quote());
+        process::exit(1);
+    }
+}
+
 fn binary_path(args: &mut impl Iterator<Item = OsString>) -> PathBuf {
     match args.next() {
         Some(ref s) if !s.is_empty() => PathBuf::from(s),
@@ -117,6 +217,10 @@ fn main() {
                     }
                     usage(&utils, binary_as_util);
                     process::exit(0);
+                } else if util == "--install" {
+                    install(&utils, binary_as_util, args);
+                } else if util == "--list" || util == "--list-full" {
+                    list(&utils, binary_as_util, util == "--list-full", args);
                 } else {
                     not_found(&util_os);
                 }
@@ -14,7 +14,11 @@ use uucore::{
 
 /// attr common functions
 pub mod attr_common;
+/// POSIX ACL codec
+pub mod lib_acl;
 pub mod lib_attr;
+/// getfattr dump format
+pub mod lib_dump;
 
 const ABOUT: &str = help_section!("about", "attr.md");
 const USAGE: &str = help_usage!("attr.md");
@@ -6,20 +6,25 @@
 // that was distributed with this source code.
 
 use crate::lib_attr;
+use std::ffi::OsStr;
 use std::io::Read;
 use std::io::Write;
-use std::os::unix::ffi::OsStrExt;
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
 
+use uucore::display::Quotable;
 use uucore::error::{UResult, UUsageError};
 use uucore::format_usage;
+use uucore::quoting_style::{escape_name, Quotes, QuotingStyle};
 
 use clap::{crate_version, Arg, ArgMatches, Command};
-use lib_attr::{attr_get, attr_list, attr_remove, attr_set};
+use lib_attr::{attr_get, attr_list, attr_remove, attr_set, Namespace};
 use libc::EXIT_FAILURE;
 
 const BAD_USAGE_MESSAGE: &str = "Only one of -s, -g, -r, or -l allowed";
 const BAD_USAGE_MESSAGE_VALUE: &str = "-V only allowed with -s";
 const BAD_USAGE_MESSAGE_V_MUST_WITH_S: &str = "-V only allowed with -s";
+const BAD_USAGE_MESSAGE_LIST_ONLY: &str = "-v and --namespace only allowed with -l";
+const BAD_USAGE_MESSAGE_NAMESPACE: &str = "Only one of -R, -S or --namespace allowed";
 
 /// Config
 pub struct Config {
@@ -39,6 +44,13 @@ pub struct Config {
     pub attrvalue: Option<String>,
     /// The file to operate
     pub filename: String,
+    /// Namespace listed by -l, or None for the user, trusted and security
+    /// namespaces
+    pub namespace: Option<Namespace>,
+    /// List every namespace, names prefixed with it, for --namespace=all
+    pub all_namespaces: bool,
+    /// List values as well as names
+    pub values: bool,
 }
 
 /// Command Options
@@ -61,6 +73,10 @@ pub mod options {
     pub const SECURE_FLAG: &str = "S";
     ///
     pub const VERBOSE: &str = "q";
+    /// Option -v
+    pub const VALUES: &str = "v";
+    /// Option --namespace
+    pub const NAMESPACE: &str = "namespace";
     ///
     pub const FILE_NAME: &str = "pathname";
 }
@@ -127,6 +143,28 @@ impl Config {
                 ));
             }
         }
+
+        let values = args_matches.get_flag(options::VALUES);
+        let rootflag = args_matches.get_flag(options::ROOT_FLAG);
+        let secureflag = args_matches.get_flag(options::SECURE_FLAG);
+        let namespace_arg = args_matches.get_one::<String>(options::NAMESPACE);
+        if (values || namespace_arg.is_some()) && opflag != Operation::LISTOP {
+            return Err(UUsageError::new(EXIT_FAILURE, BAD_USAGE_MESSAGE_LIST_ONLY));
+        }
+        let namespace = match namespace_arg {
+            Some(_) if rootflag || secureflag => {
+                return Err(UUsageError::new(EXIT_FAILURE, BAD_USAGE_MESSAGE_NAMESPACE));
+            }
+            Some(name) if name == "all" => None,
+            Some(name) => Some(Namespace::from_name(name).ok_or_else(|| {
+                UUsageError::new(EXIT_FAILURE, format!("invalid namespace {}", name.quote()))
+            })?),
+            None if rootflag => Some(Namespace::Trusted),
+            None if secureflag => Some(Namespace::Security),
+            None => None,
+        };
+        let all_namespaces = matches!(namespace_arg, Some(name) if name == "all");
+
         Ok(Self {
             opflag,
             attrname: attrname.unwrap_or_default(),
@@ -136,9 +174,12 @@ impl Config {
                 .unwrap()
                 .to_string(),
             follow: args_matches.get_flag(options::FOLLOW_LINK),
-            rootflag: args_matches.get_flag(options::ROOT_FLAG),
-            secureflag: args_matches.get_flag(options::SECURE_FLAG),
+            rootflag,
+            secureflag,
             verbose: args_matches.get_flag(options::VERBOSE),
+            namespace,
+            all_namespaces,
+            values,
         })
     }
 }
@@ -221,6 +262,19 @@ pub fn attr_app<'a>(about: &'a str, usage: &'a str) -> Command<'a> {
                 .action(clap::ArgAction::SetFalse)
                 .help("be quiet, output error messages (to stderr) but will not print status messages"),
         )
+        .arg(
+            Arg::new(options::VALUES)
+                .short('v')
+                .action(clap::ArgAction::SetTrue)
+                .help("with -l, print the namespace, name and value of each attribute"),
+        )
+        .arg(
+            Arg::new(options::NAMESPACE)
+                .long(options::NAMESPACE)
+                .takes_value(true)
+                .value_name("ns")
+                .help("with -l, list the user, trusted, security or system namespace, or all"),
+        )
         .arg(Arg::new(options::FILE_NAME).index(1).hide(true).required(true))
 }
 
@@ -271,18 +325,79 @@ pub fn handle_removeop(config: &Config) -> UResult<()> {
     Ok(())
 }
 
+/// Escape bytes in the C quoting style, with double quotes if `quotes` is
+/// set; bytes which are not UTF-8 are written as octal escapes
+fn escape_bytes(bytes: &[u8], quotes: bool) -> String {
+    let style = QuotingStyle::C {
+        quotes: if quotes { Quotes::Double } else { Quotes::None },
+    };
+    let escape = |s: &str| {
+        let escaped = escape_name(OsStr::new(s), &style);
+        if quotes {
+            escaped[1..escaped.len() - 1].to_string()
+        } else {
+            escaped
+        }
+    };
+    let mut out = String::new();
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        match std::str::from_utf8(rest) {
+            Ok(s) => {
+                out.push_str(&escape(s));
+                break;
+            }
+            Err(e) => {
+                let (valid, invalid) = rest.split_at(e.valid_up_to());
+                out.push_str(&escape(std::str::from_utf8(valid).unwrap_or_default()));
+                let bad = e.error_len().unwrap_or(invalid.len());
+                for b in &invalid[..bad] {
+                    out.push_str(&format!("\\{:03o}", b));
+                }
+                rest = &invalid[bad..];
+            }
+        }
+    }
+    if quotes {
+        format!("\"{}\"", out)
+    } else {
+        out
+    }
+}
+
 /// List attributes from file and output them
+///
+/// With `--namespace=all` names are printed with their namespace prefix,
+/// so that attributes of different namespaces can be told apart.
 pub fn handle_listop(config: &Config) -> UResult<()> {
     let alist = attr_list(config)?;
-    for i in alist {
+    let mut stdout = std::io::stdout().lock();
+    for (namespace, name, value) in alist {
+        if config.values {
+            writeln!(
+                stdout,
+                "{} {} {}",
+                namespace.name(),
+                escape_bytes(name.as_bytes(), false),
+                escape_bytes(&value, true)
+            )?;
+            continue;
+        }
+        let mut name = name.into_vec();
+        if config.all_namespaces {
+            name.splice(0..0, format!("{}.", namespace.name()).into_bytes());
+        }
         if config.verbose {
-            println!(
+            writeln!(
+                stdout,
                 "Attribute {:?} has a {} byte value for {}",
-                i.0, i.1, config.filename
-            );
+                OsStr::from_bytes(&name),
+                value.len(),
+                config.filename
+            )?;
         } else {
-            std::io::stdout().write_all(i.0.as_os_str().as_bytes())?;
-            print!("\n");
+            stdout.write_all(&name)?;
+            stdout.write_all(b"\n")?;
         }
     }
     Ok(())
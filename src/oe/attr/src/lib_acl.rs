@@ -0,0 +1,443 @@
+//! This file is part of the easybox package.
+//
+// (c) openeuler developers
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+//! POSIX ACLs stored in the `system.posix_acl_*` extended attributes
+
+use std::path::Path;
+
+use errno::Errno;
+use libc::ENODATA;
+use uucore::entries::{gid2grp, grp2gid, uid2usr, usr2uid};
+
+use crate::lib_attr::{xattr_delete, xattr_store, xattr_value};
+
+/// Extended attribute holding the access ACL
+pub const ACL_ACCESS_XATTR: &str = "system.posix_acl_access";
+/// Extended attribute holding the default ACL of a directory
+pub const ACL_DEFAULT_XATTR: &str = "system.posix_acl_default";
+
+/// Based on posix_acl_xattr.h: a little endian u32 version header
+/// followed by entries of u16 tag, u16 perm and u32 id
+const ACL_XATTR_VERSION: u32 = 2;
+const ACL_HEADER_LEN: usize = 4;
+const ACL_ENTRY_LEN: usize = 8;
+const ACL_UNDEFINED_ID: u32 = u32::MAX;
+
+const ACL_USER_OBJ: u16 = 0x01;
+const ACL_USER: u16 = 0x02;
+const ACL_GROUP_OBJ: u16 = 0x04;
+const ACL_GROUP: u16 = 0x08;
+const ACL_MASK: u16 = 0x10;
+const ACL_OTHER: u16 = 0x20;
+
+/// Read permission
+pub const ACL_READ: u16 = 0x04;
+/// Write permission
+pub const ACL_WRITE: u16 = 0x02;
+/// Execute permission
+pub const ACL_EXECUTE: u16 = 0x01;
+/// Execute only for directories or files with some execute bit set, as
+/// given by `X`; never stored
+pub const ACL_EXECUTE_IF: u16 = 0x08;
+/// Permission bits which may be stored
+const ACL_PERM_BITS: u16 = ACL_READ | ACL_WRITE | ACL_EXECUTE;
+
+/// ACL entry tags; the derived order is the canonical order the kernel expects
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum AclTag {
+    /// Owner of the file
+    UserObj,
+    /// Named user
+    User(u32),
+    /// Owning group of the file
+    GroupObj,
+    /// Named group
+    Group(u32),
+    /// Upper bound for named entries and the owning group
+    Mask,
+    /// Everybody else
+    Other,
+}
+
+impl AclTag {
+    fn code(self) -> (u16, u32) {
+        match self {
+            Self::UserObj => (ACL_USER_OBJ, ACL_UNDEFINED_ID),
+            Self::User(id) => (ACL_USER, id),
+            Self::GroupObj => (ACL_GROUP_OBJ, ACL_UNDEFINED_ID),
+            Self::Group(id) => (ACL_GROUP, id),
+            Self::Mask => (ACL_MASK, ACL_UNDEFINED_ID),
+            Self::Other => (ACL_OTHER, ACL_UNDEFINED_ID),
+        }
+    }
+
+    fn from_code(tag: u16, id: u32) -> Option<Self> {
+        match tag {
+            ACL_USER_OBJ => Some(Self::UserObj),
+            ACL_USER => Some(Self::User(id)),
+            ACL_GROUP_OBJ => Some(Self::GroupObj),
+            ACL_GROUP => Some(Self::Group(id)),
+            ACL_MASK => Some(Self::Mask),
+            ACL_OTHER => Some(Self::Other),
+            _ => None,
+        }
+    }
+
+    /// Whether the mask limits this entry
+    pub fn is_masked(self) -> bool {
+        matches!(self, Self::User(_) | Self::GroupObj | Self::Group(_))
+    }
+
+    /// Text form of the tag and qualifier, such as `user:bin:`
+    pub fn to_text(self, numeric: bool) -> String {
+        match self {
+            Self::UserObj => "user::".to_string(),
+            Self::User(id) => format!("user:{}:", user_name(id, numeric)),
+            Self::GroupObj => "group::".to_string(),
+            Self::Group(id) => format!("group:{}:", group_name(id, numeric)),
+            Self::Mask => "mask::".to_string(),
+            Self::Other => "other::".to_string(),
+        }
+    }
+}
+
+/// Name of a user, or its number if it has none or `numeric` is set
+pub fn user_name(id: u32, numeric: bool) -> String {
+    if numeric {
+        id.to_string()
+    } else {
+        uid2usr(id).unwrap_or_else(|_| id.to_string())
+    }
+}
+
+/// Name of a group, or its number if it has none or `numeric` is set
+pub fn group_name(id: u32, numeric: bool) -> String {
+    if numeric {
+        id.to_string()
+    } else {
+        gid2grp(id).unwrap_or_else(|_| id.to_string())
+    }
+}
+
+/// One entry of an ACL
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AclEntry {
+    /// Tag and qualifier
+    pub tag: AclTag,
+    /// Permission bits
+    pub perm: u16,
+}
+
+/// An access or default ACL, kept in canonical order
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Acl {
+    /// Entries sorted by tag
+    pub entries: Vec<AclEntry>,
+}
+
+impl Acl {
+    /// The minimal ACL equivalent to the permission bits of `mode`
+    pub fn from_mode(mode: u32) -> Self {
+        let perm = |shift: u32| ((mode >> shift) as u16) & ACL_PERM_BITS;
+        Self {
+            entries: vec![
+                AclEntry {
+                    tag: AclTag::UserObj,
+                    perm: perm(6),
+                },
+                AclEntry {
+                    tag: AclTag::GroupObj,
+                    perm: perm(3),
+                },
+                AclEntry {
+                    tag: AclTag::Other,
+                    perm: perm(0),
+                },
+            ],
+        }
+    }
+
+    /// Decode the xattr format, or None if it is malformed
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < ACL_HEADER_LEN {
+            return None;
+        }
+        let (header, body) = bytes.split_at(ACL_HEADER_LEN);
+        if u32::from_le_bytes([header[0], header[1], header[2], header[3]]) != ACL_XATTR_VERSION {
+            return None;
+        }
+        let entries = body.chunks_exact(ACL_ENTRY_LEN);
+        if !entries.remainder().is_empty() {
+            return None;
+        }
+        let mut acl = Self::default();
+        for e in entries {
+            let id = u32::from_le_bytes([e[4], e[5], e[6], e[7]]);
+            let tag = AclTag::from_code(u16::from_le_bytes([e[0], e[1]]), id)?;
+            acl.set(tag, u16::from_le_bytes([e[2], e[3]]) & ACL_PERM_BITS);
+        }
+        Some(acl)
+    }
+
+    /// Encode in the xattr format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(ACL_HEADER_LEN + self.entries.len() * ACL_ENTRY_LEN);
+        bytes.extend_from_slice(&ACL_XATTR_VERSION.to_le_bytes());
+        for entry in &self.entries {
+            let (tag, id) = entry.tag.code();
+            bytes.extend_from_slice(&tag.to_le_bytes());
+            bytes.extend_from_slice(&entry.perm.to_le_bytes());
+            bytes.extend_from_slice(&id.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Whether the ACL has no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether the ACL only has the entries equivalent to permission bits
+    pub fn is_minimal(&self) -> bool {
+        self.entries
+            .iter()
+            .all(|e| matches!(e.tag, AclTag::UserObj | AclTag::GroupObj | AclTag::Other))
+    }
+
+    /// Permissions of the entry with this tag
+    pub fn get(&self, tag: AclTag) -> Option<u16> {
+        self.entries.iter().find(|e| e.tag == tag).map(|e| e.perm)
+    }
+
+    /// Add or replace an entry, keeping the canonical order
+    pub fn set(&mut self, tag: AclTag, perm: u16) {
+        match self.entries.binary_search_by(|e| e.tag.cmp(&tag)) {
+            Ok(i) => self.entries[i].perm = perm,
+            Err(i) => self.entries.insert(i, AclEntry { tag, perm }),
+        }
+    }
+
+    /// Remove the entry with this tag, returning whether there was one
+    pub fn remove(&mut self, tag: AclTag) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|e| e.tag != tag);
+        self.entries.len() != len
+    }
+
+    /// Drop all but the entries equivalent to permission bits
+    pub fn strip(&mut self) {
+        self.entries
+            .retain(|e| matches!(e.tag, AclTag::UserObj | AclTag::GroupObj | AclTag::Other));
+    }
+
+    /// Based on remove_extended_entries() in setfacl: like [`Acl::strip`], but
+    /// the owning group first loses the permissions the mask denied it, so
+    /// that removing the ACL never grants more than it did
+    pub fn remove_extended_entries(&mut self) {
+        if let Some(mask) = self.get(AclTag::Mask) {
+            if let Some(group) = self.get(AclTag::GroupObj) {
+                self.set(AclTag::GroupObj, group & mask);
+            }
+        }
+        self.strip();
+    }
+
+    /// Based on acl_calc_mask(): set the mask to the union of the entries it
+    /// limits, if the ACL has named entries or already has a mask
+    pub fn calc_mask(&mut self) {
+        let named = self
+            .entries
+            .iter()
+            .any(|e| matches!(e.tag, AclTag::User(_) | AclTag::Group(_)));
+        if !named && self.get(AclTag::Mask).is_none() {
+            return;
+        }
+        let perm = self
+            .entries
+            .iter()
+            .filter(|e| e.tag.is_masked())
+            .fold(0, |perm, e| perm | e.perm);
+        self.set(AclTag::Mask, perm);
+    }
+
+    /// Permissions granted by an entry once the mask is applied
+    pub fn effective(&self, entry: &AclEntry) -> u16 {
+        match self.get(AclTag::Mask) {
+            Some(mask) if entry.tag.is_masked() => entry.perm & mask,
+            _ => entry.perm,
+        }
+    }
+
+    /// Based on acl_check(): the base entries must be present and a mask is
+    /// required once there are named entries
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.entries.windows(2).any(|w| w[0].tag == w[1].tag) {
+            return Err("Duplicate entries");
+        }
+        let named = self
+            .entries
+            .iter()
+            .any(|e| matches!(e.tag, AclTag::User(_) | AclTag::Group(_)));
+        let has = |tag| self.get(tag).is_some();
+        if !has(AclTag::UserObj)
+            || !has(AclTag::GroupObj)
+            || !has(AclTag::Other)
+            || (named && !has(AclTag::Mask))
+        {
+            return Err("Missing or wrong entry");
+        }
+        Ok(())
+    }
+
+    /// Comma separated text form, as used in error messages
+    pub fn to_short_text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|e| format!("{}{}", e.tag.to_text(false), perm_to_text(e.perm)))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Read an ACL; None if the file has none of that kind
+pub fn acl_read(path: &Path, name: &str, follow: bool) -> Result<Option<Acl>, Errno> {
+    match xattr_value(path, name.as_ref(), follow) {
+        Ok(bytes) => Acl::from_bytes(&bytes).map(Some).ok_or(Errno(libc::EINVAL)),
+        Err(Errno(ENODATA)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Write an ACL, removing the attribute for an empty ACL
+pub fn acl_write(path: &Path, name: &str, acl: &Acl, follow: bool) -> Result<(), Errno> {
+    if acl.is_empty() {
+        return match xattr_delete(path, name.as_ref(), follow) {
+            Err(Errno(ENODATA)) => Ok(()),
+            res => res,
+        };
+    }
+    xattr_store(path, name.as_ref(), &acl.to_bytes(), follow)
+}
+
+/// Text form of permission bits, such as `r-x`
+pub fn perm_to_text(perm: u16) -> String {
+    [(ACL_READ, 'r'), (ACL_WRITE, 'w'), (ACL_EXECUTE, 'x')]
+        .iter()
+        .map(|&(bit, c)| if perm & bit != 0 { c } else { '-' })
+        .collect()
+}
+
+/// Parse `rwxX-` letters or a single octal digit
+pub fn parse_perm(text: &str) -> Option<u16> {
+    if text.len() == 1 {
+        if let Some(d) = text.chars().next().and_then(|c| c.to_digit(8)) {
+            return Some(d as u16);
+        }
+    }
+    let mut perm = 0;
+    for c in text.chars() {
+        perm |= match c {
+            'r' => ACL_READ,
+            'w' => ACL_WRITE,
+            'x' => ACL_EXECUTE,
+            'X' => ACL_EXECUTE_IF,
+            '-' => 0,
+            _ => return None,
+        };
+    }
+    Some(perm)
+}
+
+/// One entry of an ACL specification, as given to setfacl
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AclSpecEntry {
+    /// Whether the entry is for the default ACL
+    pub default: bool,
+    /// Tag and qualifier
+    pub tag: AclTag,
+    /// Permissions, absent for entries to remove
+    pub perm: Option<u16>,
+}
+
+fn parse_qualifier(qualifier: &str, user: bool) -> Option<u32> {
+    if let Ok(id) = qualifier.parse::<u32>() {
+        return Some(id);
+    }
+    if user {
+        usr2uid(qualifier).ok()
+    } else {
+        grp2gid(qualifier).ok()
+    }
+}
+
+fn parse_spec_entry(text: &str, with_perms: bool) -> Option<AclSpecEntry> {
+    let (default, text) = match text.split_once(':') {
+        Some(("d" | "default", rest)) => (true, rest),
+        _ => (false, text),
+    };
+    let mut fields = text.splitn(3, ':');
+    let tag = fields.next()?;
+    let (tag, rest): (AclTag, Vec<&str>) = match tag {
+        "u" | "user" | "g" | "group" => {
+            let user = tag.starts_with('u');
+            let qualifier = fields.next()?;
+            let tag = match (qualifier.is_empty(), user) {
+                (true, true) => AclTag::UserObj,
+                (true, false) => AclTag::GroupObj,
+                (false, true) => AclTag::User(parse_qualifier(qualifier, true)?),
+                (false, false) => AclTag::Group(parse_qualifier(qualifier, false)?),
+            };
+            (tag, fields.collect())
+        }
+        "m" | "mask" | "o" | "other" => {
+            let tag = if tag.starts_with('m') {
+                AclTag::Mask
+            } else {
+                AclTag::Other
+            };
+            // The empty qualifier is optional: both "o::r" and "o:r" work.
+            let rest: Vec<&str> = fields.collect();
+            match rest.as_slice() {
+                ["", perm] => (tag, vec![*perm]),
+                [""] if !with_perms => (tag, vec![]),
+                _ => (tag, rest),
+            }
+        }
+        _ => return None,
+    };
+    let perm = match (with_perms, rest.as_slice()) {
+        (true, [perm]) => Some(parse_perm(perm.trim())?),
+        // Entries to remove may keep the trailing colon, as in "g::".
+        (false, [] | [""]) => None,
+        _ => return None,
+    };
+    Some(AclSpecEntry { default, tag, perm })
+}
+
+/// Parse comma or newline separated ACL entries, such as `u:bin:rw,d:g::r`.
+/// Text after `#` on a line is a comment. Entries to remove take no
+/// permissions.
+pub fn parse_acl_spec(spec: &str, with_perms: bool) -> Result<Vec<AclSpecEntry>, String> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    for line in spec.split_inclusive('\n') {
+        let content = line.split('#').next().unwrap_or_default();
+        let mut pos = offset;
+        for text in content.split(&[',', '\n'][..]) {
+            let trimmed = text.trim();
+            if !trimmed.is_empty() {
+                let start = pos + (text.len() - text.trim_start().len());
+                let entry = parse_spec_entry(trimmed, with_perms)
+                    .ok_or_else(|| format!("Invalid argument near character {}", start + 1))?;
+                entries.push(entry);
+            }
+            pos += text.len() + 1;
+        }
+        offset += line.len();
+    }
+    Ok(entries)
+}
@@ -8,7 +8,7 @@
 
 use std::{
     ffi::{OsStr, OsString},
-    os::unix::ffi::{OsStrExt, OsStringExt},
+    os::unix::ffi::OsStrExt,
     path::Path,
 };
 
@@ -17,7 +17,7 @@ use extattr::{
     getxattr, lgetxattr, listxattr, llistxattr, lremovexattr, lsetxattr, removexattr, setxattr,
     Flags,
 };
-use libc::{ENOATTR, ENOTSUP, EXIT_FAILURE};
+use libc::{ENOATTR, ENOTSUP, ERANGE, EXIT_FAILURE};
 use uucore::error::{UResult, USimpleError};
 
 use crate::attr_common::Config;
@@ -27,6 +27,10 @@ const USER_NAME: &str = "user.";
 const SECURE_NAME: &str = "security.";
 const TRUSTED_NAME: &str = "trusted.";
 const XFSROOT_NAME: &str = "xfsroot.";
+const SYSTEM_NAME: &str = "system.";
+/// How often a read is retried when the attribute grows between probing its
+/// size and reading it
+const ERANGE_RETRIES: usize = 8;
 
 /// Based on attr_set() in libattr.c
 pub fn attr_set(config: &Config, attrvalue: &Vec<u8>) -> UResult<()> {
@@ -74,11 +78,11 @@ pub fn attr_get(config: &Config) -> UResult<Vec<u8>> {
     let mut res: Result<Vec<u8>, Errno> = Err(Errno(0));
     for compat in 0..2 {
         let name = api_convert(config, compat)?;
-        if config.follow {
-            res = getxattr(<String as AsRef<Path>>::as_ref(&config.filename), name);
-        } else {
-            res = lgetxattr(<String as AsRef<Path>>::as_ref(&config.filename), name);
-        }
+        res = xattr_value(
+            <String as AsRef<Path>>::as_ref(&config.filename),
+            name.as_ref(),
+            config.follow,
+        );
         match res {
             Err(Errno(ENOATTR)) => continue,
             Err(Errno(ENOTSUP)) => continue,
@@ -129,36 +133,27 @@ pub fn attr_remove(config: &Config) -> UResult<()> {
     }
 }
 
-/// Based on attr_list() in libattr.c
-pub fn attr_list(config: &Config) -> UResult<Vec<(OsString, usize)>> {
-    let res: Result<Vec<OsString>, Errno>;
-    if config.follow {
-        res = listxattr(<String as AsRef<Path>>::as_ref(&config.filename));
-    } else {
-        res = llistxattr(<String as AsRef<Path>>::as_ref(&config.filename));
-    }
-    if let Err(err) = res {
-        eprintln!(
-            "attr_list: {}\nCould not list {}",
-            err.to_string(),
-            config.filename
-        );
-        return Err(USimpleError::new(EXIT_FAILURE, ""));
-    }
-    let mut alist: Vec<(OsString, usize)> = Vec::new();
-    for attrname in res.unwrap() {
-        if let Ok(name) = api_unconvert(config, attrname.as_os_str()) {
-            let res_get: Result<Vec<u8>, Errno>;
-            if config.follow {
-                res_get = getxattr(<String as AsRef<Path>>::as_ref(&config.filename), attrname);
-            } else {
-                res_get = lgetxattr(<String as AsRef<Path>>::as_ref(&config.filename), attrname);
-            }
-            if let Ok(val) = res_get {
-                alist.push((name, val.len()));
+/// Based on attr_list() in libattr.c: the attributes of the namespaces
+/// selected by `config`, with their values, sorted by namespace and name
+pub fn attr_list(config: &Config) -> UResult<Vec<(Namespace, OsString, Vec<u8>)>> {
+    let path = <String as AsRef<Path>>::as_ref(&config.filename);
+    let names = match xattr_names(path, config.follow) {
+        Ok(names) => names,
+        Err(err) => {
+            eprintln!("attr_list: {}\nCould not list {}", err, config.filename);
+            return Err(USimpleError::new(EXIT_FAILURE, ""));
+        }
+    };
+    let mut alist = Vec::new();
+    for attrname in names {
+        if let Some((namespace, name)) = api_unconvert(config, attrname.as_os_str()) {
+            // An attribute removed since listing is left out.
+            if let Ok(val) = xattr_value(path, &attrname, config.follow) {
+                alist.push((namespace, name, val));
             }
         }
     }
+    alist.sort();
     Ok(alist)
 }
 
@@ -186,41 +181,132 @@ fn api_convert(config: &Config, compat: i8) -> UResult<String> {
     Ok(name)
 }
 
-fn api_unconvert(config: &Config, linuxname: &OsStr) -> Result<OsString, ()> {
-    #[allow(non_camel_case_types)]
-    #[derive(PartialEq)]
-    enum ATTRTYPE {
-        ATTR_USER,
-        ATTR_SECURE,
-        ATTR_ROOT,
-    }
-    let bytes_name = linuxname.as_bytes();
-    let mut find_iter = bytes_name.splitn(2, |n| *n == b'.');
-    if let Some(prefix) = find_iter.next() {
-        let str_prefix = std::str::from_utf8(prefix).unwrap_or_default();
-        let len_add_one = str_prefix.len() + 1; // Add the last '.'
-        let attr_type: ATTRTYPE;
-        if len_add_one == USER_NAME.len() && USER_NAME.starts_with(str_prefix) {
-            attr_type = ATTRTYPE::ATTR_USER;
-        } else if len_add_one == SECURE_NAME.len() && SECURE_NAME.starts_with(str_prefix) {
-            attr_type = ATTRTYPE::ATTR_SECURE;
-        } else if len_add_one == TRUSTED_NAME.len() && TRUSTED_NAME.starts_with(str_prefix) {
-            attr_type = ATTRTYPE::ATTR_ROOT;
-        } else if len_add_one == XFSROOT_NAME.len() && XFSROOT_NAME.starts_with(str_prefix) {
-            attr_type = ATTRTYPE::ATTR_ROOT;
-        } else {
-            return Err(());
+fn api_unconvert(config: &Config, linuxname: &OsStr) -> Option<(Namespace, OsString)> {
+    let (namespace, name) = Namespace::split(linuxname)?;
+    let listed = match config.namespace {
+        Some(wanted) => wanted == namespace,
+        // Without a filter, libattr lists all but the system namespace.
+        None => config.all_namespaces || namespace != Namespace::System,
+    };
+    if listed {
+        Some((namespace, name.to_os_string()))
+    } else {
+        None
+    }
+}
+
+/// Attribute namespaces, in the order attr lists them
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Namespace {
+    /// `user.`
+    User,
+    /// `trusted.`, or `xfsroot.` on old XFS filesystems
+    Trusted,
+    /// `security.`
+    Security,
+    /// `system.`, such as POSIX ACLs
+    System,
+}
+
+impl Namespace {
+    /// Get namespace from its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "user" => Some(Self::User),
+            "trusted" => Some(Self::Trusted),
+            "security" => Some(Self::Security),
+            "system" => Some(Self::System),
+            _ => None,
         }
-        // Found:
-        if config.secureflag && attr_type != ATTRTYPE::ATTR_SECURE {
-            return Err(());
+    }
+
+    /// Name of the namespace, without the trailing '.'
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Trusted => "trusted",
+            Self::Security => "security",
+            Self::System => "system",
         }
-        if config.rootflag && attr_type != ATTRTYPE::ATTR_ROOT {
-            return Err(());
+    }
+
+    /// Split a full attribute name into its namespace and the name within it
+    pub fn split(linuxname: &OsStr) -> Option<(Self, &OsStr)> {
+        let bytes = linuxname.as_bytes();
+        [
+            (USER_NAME, Self::User),
+            (TRUSTED_NAME, Self::Trusted),
+            (XFSROOT_NAME, Self::Trusted),
+            (SECURE_NAME, Self::Security),
+            (SYSTEM_NAME, Self::System),
+        ]
+        .iter()
+        .find_map(|&(prefix, namespace)| {
+            bytes
+                .strip_prefix(prefix.as_bytes())
+                .map(|name| (namespace, OsStr::from_bytes(name)))
+        })
+    }
+}
+
+/// Retry a read which failed with ERANGE because the attribute grew after
+/// its size was probed
+fn retry_erange<T>(read: impl Fn() -> Result<T, Errno>) -> Result<T, Errno> {
+    let mut res = read();
+    for _ in 0..ERANGE_RETRIES {
+        match res {
+            Err(Errno(ERANGE)) => res = read(),
+            _ => break,
         }
-        return Ok(OsString::from_vec(
-            find_iter.next().unwrap_or_default().to_vec(),
-        ));
-    };
-    Err(())
+    }
+    res
+}
+
+/// List the full names of all attributes of `path`, following a final
+/// symbolic link if `follow` is set
+pub fn xattr_names(path: &Path, follow: bool) -> Result<Vec<OsString>, Errno> {
+    retry_erange(|| {
+        if follow {
+            listxattr(path)
+        } else {
+            llistxattr(path)
+        }
+    })
+}
+
+/// Get the value of the attribute with the full name `name`
+pub fn xattr_value(path: &Path, name: &OsStr, follow: bool) -> Result<Vec<u8>, Errno> {
+    retry_erange(|| {
+        if follow {
+            getxattr(path, name)
+        } else {
+            lgetxattr(path, name)
+        }
+    })
+}
+
+/// Create or replace the attribute with the full name `name`
+pub fn xattr_store(path: &Path, name: &OsStr, value: &[u8], follow: bool) -> Result<(), Errno> {
+    if follow {
+        setxattr(path, name, value, Flags::empty())
+    } else {
+        lsetxattr(path, name, value, Flags::empty())
+    }
+}
+
+/// Remove the attribute with the full name `name`
+pub fn xattr_delete(path: &Path, name: &OsStr, follow: bool) -> Result<(), Errno> {
+    if follow {
+        removexattr(path, name)
+    } else {
+        lremovexattr(path, name)
+    }
+}
+
+/// Describe an xattr error the way getfattr and setfattr do
+pub fn xattr_strerror(err: Errno) -> String {
+    match err {
+        Errno(ENOATTR) => "No such attribute".to_string(),
+        err => err.to_string(),
+    }
 }
@@ -0,0 +1,275 @@
+//! This file is part of the easybox package.
+//
+// (c) Jiale Xiao <xiao-xjle@qq.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+//! Value encodings and the dump format shared by getfattr and setfattr, and
+//! the `# file:` headers and error texts the ACL tools share with them
+
+use std::{
+    ffi::OsString,
+    io::BufRead,
+    os::unix::ffi::{OsStrExt, OsStringExt},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use uucore::encoding::{decode, encode, Format};
+
+/// Prefix of hex encoded values
+const HEX_PREFIX: &str = "0x";
+/// Prefix of base64 encoded values
+const BASE64_PREFIX: &str = "0s";
+/// Header line introducing the attributes of one file
+pub const FILE_HEADER: &str = "# file: ";
+
+/// Set once the leading '/' warning has been printed
+static WARNED_ABSOLUTE: AtomicBool = AtomicBool::new(false);
+
+/// Encodings of attribute values, as selected by getfattr -e
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ValueEncoding {
+    /// Double quoted text with octal escapes
+    Text,
+    /// Lower case hex digits after `0x`
+    Hex,
+    /// Base64 after `0s`
+    Base64,
+}
+
+impl ValueEncoding {
+    /// Get encoding from its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(Self::Text),
+            "hex" => Some(Self::Hex),
+            "base64" => Some(Self::Base64),
+            _ => None,
+        }
+    }
+}
+
+fn is_print(c: u8) -> bool {
+    (0x20..0x7f).contains(&c)
+}
+
+/// Based on well_enough_printable() in getfattr.c: text is used when at most
+/// one byte in eight is not printable
+fn well_enough_printable(value: &[u8]) -> bool {
+    let nonprint = value.iter().filter(|&&c| !is_print(c)).count();
+    value.len() >= nonprint * 8
+}
+
+fn push_octal(out: &mut Vec<u8>, c: u8) {
+    out.extend_from_slice(&[
+        b'\\',
+        b'0' + (c >> 6),
+        b'0' + ((c >> 3) & 7),
+        b'0' + (c & 7),
+    ]);
+}
+
+/// Encode an attribute value; without an explicit encoding, text is used for
+/// mostly printable values and base64 otherwise
+pub fn encode_value(value: &[u8], encoding: Option<ValueEncoding>) -> Vec<u8> {
+    let encoding = encoding.unwrap_or(if well_enough_printable(value) {
+        ValueEncoding::Text
+    } else {
+        ValueEncoding::Base64
+    });
+    match encoding {
+        ValueEncoding::Text => {
+            let mut out = Vec::with_capacity(value.len() + 2);
+            out.push(b'"');
+            for (i, &c) in value.iter().enumerate() {
+                match c {
+                    // A single trailing NUL is implied by the C string conventions.
+                    0 if i == value.len() - 1 => break,
+                    0 | b'\n' | b'\r' => push_octal(&mut out, c),
+                    b'\\' | b'"' => out.extend_from_slice(&[b'\\', c]),
+                    _ => out.push(c),
+                }
+            }
+            out.push(b'"');
+            out
+        }
+        ValueEncoding::Hex => {
+            let mut out = String::from(HEX_PREFIX);
+            for c in value {
+                out.push_str(&format!("{:02x}", c));
+            }
+            out.into_bytes()
+        }
+        ValueEncoding::Base64 => match encode(Format::Base64, value) {
+            Ok(s) => format!("{}{}", BASE64_PREFIX, s).into_bytes(),
+            Err(_) => unreachable!("base64 accepts any input length"),
+        },
+    }
+}
+
+fn hex_digit(c: u8) -> Option<u8> {
+    (c as char).to_digit(16).map(|d| d as u8)
+}
+
+/// Based on decode() in setfattr.c: accept `0x` hex, `0s` base64 and plain or
+/// double quoted text with backslash escapes
+pub fn decode_value(value: &[u8]) -> Option<Vec<u8>> {
+    if value.len() >= 2 && value[0] == b'0' {
+        match value[1] {
+            b'x' | b'X' => {
+                let digits: Vec<u8> = value[2..]
+                    .iter()
+                    .copied()
+                    .filter(|c| !c.is_ascii_whitespace())
+                    .collect();
+                let pairs = digits.chunks_exact(2);
+                if !pairs.remainder().is_empty() {
+                    return None;
+                }
+                return pairs
+                    .map(|pair| Some(hex_digit(pair[0])? << 4 | hex_digit(pair[1])?))
+                    .collect();
+            }
+            b's' | b'S' => {
+                let digits: Vec<u8> = value[2..]
+                    .iter()
+                    .copied()
+                    .filter(|c| !c.is_ascii_whitespace())
+                    .collect();
+                return decode(Format::Base64, &digits).ok();
+            }
+            _ => {}
+        }
+    }
+
+    let mut text = value;
+    if text.len() >= 2 && text[0] == b'"' && text[text.len() - 1] == b'"' {
+        text = &text[1..text.len() - 1];
+    }
+    Some(unescape(text))
+}
+
+fn unescape(text: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    let mut i = 0;
+    while i < text.len() {
+        if text[i] == b'\\' && i + 1 < text.len() {
+            let octal = text[i + 1..]
+                .iter()
+                .take(3)
+                .take_while(|c| (b'0'..=b'7').contains(c))
+                .count();
+            if octal == 3 {
+                let n = text[i + 1..i + 4]
+                    .iter()
+                    .fold(0u32, |n, c| n * 8 + u32::from(c - b'0'));
+                out.push(n as u8);
+                i += 4;
+            } else {
+                out.push(text[i + 1]);
+                i += 2;
+            }
+        } else {
+            out.push(text[i]);
+            i += 1;
+        }
+    }
+    out
+}
+
+/// Based on quote() in libmisc: escape backslashes, line breaks and any of
+/// `extra` as octal, so names and paths fit on one dump line
+pub fn quote(s: &[u8], extra: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(s.len());
+    for &c in s {
+        if c == b'\\' || c == b'\n' || c == b'\r' || extra.contains(&c) {
+            push_octal(&mut out, c);
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Reverse [`quote`]
+pub fn unquote(s: &[u8]) -> Vec<u8> {
+    unescape(s)
+}
+
+/// The file name printed in the `# file:` header
+///
+/// Unless `absolute_names` is set, leading slashes are stripped with a
+/// warning on the first such path, as getfattr and getfacl do.
+pub fn header_name(path: &Path, absolute_names: bool) -> Vec<u8> {
+    let bytes = path.as_os_str().as_bytes();
+    if absolute_names || !bytes.starts_with(b"/") {
+        return quote(bytes, b"");
+    }
+    if !WARNED_ABSOLUTE.swap(true, Ordering::Relaxed) {
+        eprintln!(
+            "{}: Removing leading '/' from absolute path names",
+            uucore::util_name()
+        );
+    }
+    let stripped = &bytes[bytes.iter().take_while(|&&c| c == b'/').count()..];
+    if stripped.is_empty() {
+        quote(b".", b"")
+    } else {
+        quote(stripped, b"")
+    }
+}
+
+/// Drop the " (os error N)" suffix of io::Error messages
+pub fn strip_os_error(msg: &str) -> &str {
+    match msg.find(" (os error ") {
+        Some(pos) => &msg[..pos],
+        None => msg,
+    }
+}
+
+/// The attributes of one file in a dump
+pub struct DumpFile {
+    /// Path from the `# file:` header
+    pub path: PathBuf,
+    /// Full attribute names and their decoded values
+    pub attrs: Vec<(OsString, Vec<u8>)>,
+}
+
+/// Parse the output of `getfattr --dump`
+///
+/// Errors name the offending line, and nothing is returned for a malformed
+/// dump so that a restore never applies half of it.
+pub fn parse_dump<R: BufRead>(input: R) -> Result<Vec<DumpFile>, String> {
+    let mut files: Vec<DumpFile> = Vec::new();
+    for (n, line) in input.split(b'\n').enumerate() {
+        let lineno = n + 1;
+        let mut line = line.map_err(|e| e.to_string())?;
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        if let Some(path) = line.strip_prefix(FILE_HEADER.as_bytes()) {
+            files.push(DumpFile {
+                path: PathBuf::from(OsString::from_vec(unquote(path))),
+                attrs: Vec::new(),
+            });
+            continue;
+        }
+        if line.iter().all(u8::is_ascii_whitespace) || line[0] == b'#' {
+            continue;
+        }
+        let file = match files.last_mut() {
+            Some(file) => file,
+            None => return Err(format!("No filename found in line {}, aborting", lineno)),
+        };
+        let (name, value) = match line.iter().position(|&c| c == b'=') {
+            Some(pos) => (&line[..pos], &line[pos + 1..]),
+            None => (&line[..], &b""[..]),
+        };
+        let value =
+            decode_value(value).ok_or_else(|| format!("bad input encoding in line {}", lineno))?;
+        file.attrs.push((OsString::from_vec(unquote(name)), value));
+    }
+    Ok(files)
+}
@@ -7,10 +7,10 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
-use std::io::{stdout, Read, Write};
+use std::io::{stdout, BufWriter, Read};
 
 use uucore::display::Quotable;
-use uucore::encoding::{wrap_print, Data, Format};
+use uucore::encoding::{Data, EncodeError, Format};
 use uucore::error::{FromIo, UResult, USimpleError, UUsageError};
 use uucore::format_usage;
 
@@ -169,29 +169,19 @@ pub fn handle_input<R: Read>(
         data = data.line_wrap(wrap);
     }
 
+    let stdout = stdout();
+    let output = BufWriter::new(stdout.lock());
     if !decode {
-        match data.encode() {
-            Ok(s) => {
-                wrap_print(&data, &s);
-                Ok(())
-            }
-            Err(_) => Err(USimpleError::new(
+        match data.encode_to(output) {
+            Ok(()) => Ok(()),
+            Err(EncodeError::Z85InputLenNotMultipleOf4) => Err(USimpleError::new(
                 1,
                 "error: invalid input (length must be multiple of 4 characters)",
             )),
+            Err(EncodeError::Io(e)) => Err(USimpleError::new(1, format!("error: {}", e))),
         }
     } else {
-        match data.decode() {
-            Ok(s) => {
-                // Silent the warning as we want to the error message
-                #[allow(clippy::question_mark)]
-                if stdout().write_all(&s).is_err() {
-                    // on windows console, writing invalid utf8 returns an error
-                    return Err(USimpleError::new(1, "error: cannot write non-utf8 data"));
-                }
-                Ok(())
-            }
-            Err(_) => Err(USimpleError::new(1, "error: invalid input")),
-        }
+        data.decode_to(output)
+            .map_err(|e| USimpleError::new(1, format!("error: {}", e)))
     }
 }
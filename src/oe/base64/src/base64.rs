@@ -0,0 +1,44 @@
+//! This file is part of the uutils coreutils package.
+//
+// (c) Jordy Dickinson <jordy.dickinson@gmail.com>
+// (c) Jian Zeng <anonymousknight96@gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::io::{stdin, Read};
+
+use clap::Command;
+use oe_base32::base_common;
+use uucore::{encoding::Format, error::UResult, help_section, help_usage};
+
+const ABOUT: &str = help_section!("about", "base64.md");
+const USAGE: &str = help_usage!("base64.md");
+
+#[uucore::main]
+/// This the main of base64
+///
+pub fn oemain(args: impl uucore::Args) -> UResult<()> {
+    let format = Format::Base64;
+
+    let config: base_common::Config = base_common::parse_base_cmd_args(args, ABOUT, USAGE)?;
+
+    // Create a reference to stdin so we can return a locked stdin from
+    // parse_base_cmd_args
+    let stdin_raw = stdin();
+    let mut input: Box<dyn Read> = base_common::get_input(&config, &stdin_raw)?;
+
+    base_common::handle_input(
+        &mut input,
+        format,
+        config.wrap_cols,
+        config.ignore_garbage,
+        config.decode,
+    )
+}
+
+/// This the oe_app of base64
+///
+pub fn oe_app<'a>() -> Command<'a> {
+    base_common::base_app(ABOUT, USAGE)
+}
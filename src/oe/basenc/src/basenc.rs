@@ -0,0 +1,117 @@
+//! This file is part of the uutils coreutils package.
+//
+// (c) Jian Zeng <anonymousknight96@gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+// spell-checker:ignore (encodings) lsbf msbf
+
+use std::io::{stdin, Read};
+
+use clap::{Arg, Command};
+use oe_base32::base_common::{self, Config, BASE_CMD_PARSE_ERROR};
+use uucore::{
+    encoding::Format,
+    error::{UResult, UUsageError},
+    help_section, help_usage,
+};
+
+const ABOUT: &str = help_section!("about", "basenc.md");
+const USAGE: &str = help_usage!("basenc.md");
+
+/// Encoding options, in the order they are listed in the help.
+const ENCODINGS: &[(&str, Format, &str)] = &[
+    (
+        "base64",
+        Format::Base64,
+        "same as 'base64' program (RFC4648 section 4)",
+    ),
+    (
+        "base64url",
+        Format::Base64Url,
+        "file- and url-safe base64 (RFC4648 section 5)",
+    ),
+    (
+        "base32",
+        Format::Base32,
+        "same as 'base32' program (RFC4648 section 6)",
+    ),
+    (
+        "base32hex",
+        Format::Base32Hex,
+        "extended hex alphabet base32 (RFC4648 section 7)",
+    ),
+    ("base16", Format::Base16, "hex encoding (RFC4648 section 8)"),
+    (
+        "base2msbf",
+        Format::Base2Msbf,
+        "bit string with most significant bit (msb) first",
+    ),
+    (
+        "base2lsbf",
+        Format::Base2Lsbf,
+        "bit string with least significant bit (lsb) first",
+    ),
+    (
+        "z85",
+        Format::Z85,
+        "ascii85-like encoding (ZeroMQ spec:32/Z85);\n\
+         when encoding, input length must be a multiple of 4;\n\
+         when decoding, input length must be a multiple of 5",
+    ),
+];
+
+/// This the oe_app of basenc
+///
+pub fn oe_app<'a>() -> Command<'a> {
+    let mut command = base_common::base_app(ABOUT, USAGE);
+    for (name, _, help) in ENCODINGS {
+        command = command.arg(
+            Arg::new(*name)
+                .long(name)
+                .help(*help)
+                .multiple_occurrences(true),
+        );
+    }
+    command
+}
+
+/// Parse the command line, returning the config and the last encoding given.
+fn parse_cmd_args(args: impl uucore::Args) -> UResult<(Config, Format)> {
+    let matches = oe_app().try_get_matches_from(args.collect_lossy())?;
+    // Like GNU basenc, a later encoding option overrides an earlier one.
+    let format = ENCODINGS
+        .iter()
+        .filter_map(|(name, format, _)| {
+            matches
+                .indices_of(name)
+                .and_then(|indices| indices.max())
+                .map(|index| (index, *format))
+        })
+        .max_by_key(|(index, _)| *index)
+        .map(|(_, format)| format)
+        .ok_or_else(|| UUsageError::new(BASE_CMD_PARSE_ERROR, "missing encoding type"))?;
+    let config = Config::from(&matches)?;
+    Ok((config, format))
+}
+
+#[uucore::main]
+/// This the main of basenc
+///
+pub fn oemain(args: impl uucore::Args) -> UResult<()> {
+    let (config, format) = parse_cmd_args(args)?;
+
+    // Create a reference to stdin so we can return a locked stdin from
+    // parse_base_cmd_args
+    let stdin_raw = stdin();
+    let mut input: Box<dyn Read> = base_common::get_input(&config, &stdin_raw)?;
+
+    base_common::handle_input(
+        &mut input,
+        format,
+        config.wrap_cols,
+        config.ignore_garbage,
+        config.decode,
+    )
+}
@@ -0,0 +1,51 @@
+//! This file is part of the easybox package.
+//
+// (c) openeuler developers
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use clap::Command;
+use oe_taskset::taskset_common::run_program;
+use uucore::{error::UResult, help_section, help_usage};
+
+/// Chrt common functions
+pub mod chrt_common;
+
+const ABOUT: &str = help_section!("about", "chrt.md");
+const USAGE: &str = help_usage!("chrt.md");
+
+#[uucore::main]
+/// This the main of chrt
+///
+pub fn oemain(args: impl uucore::Args) -> UResult<()> {
+    let config = chrt_common::parse_chrt_cmd_args(args, ABOUT, USAGE)?;
+    if config.max {
+        chrt_common::show_min_max();
+        return Ok(());
+    }
+    if config.policy.is_none() {
+        return chrt_common::show_sched_info(&config, "current");
+    }
+
+    let show_status = config.verbose && config.command.is_none();
+    if show_status {
+        chrt_common::show_sched_info(&config, "current")?;
+    }
+    chrt_common::set_sched(&config)?;
+    if show_status {
+        chrt_common::show_sched_info(&config, "new")?;
+    }
+
+    if let Some(command) = config.command {
+        run_program(command)?;
+    }
+
+    Ok(())
+}
+
+/// This the oe_app of chrt
+///
+pub fn oe_app<'a>() -> Command<'a> {
+    chrt_common::chrt_app(ABOUT, USAGE)
+}
@@ -0,0 +1,524 @@
+//! This file is part of the easybox package.
+//
+// (c) openeuler developers
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::mem::size_of;
+
+use clap::{crate_version, Arg, ArgMatches, Command};
+use libc::EXIT_FAILURE;
+use nix::errno::Errno;
+use nix::unistd::{getpid, Pid};
+use uucore::error::{UResult, USimpleError, UUsageError};
+use uucore::format_usage;
+
+const SCHED_DEADLINE: i32 = 6;
+const SCHED_FLAG_RESET_ON_FORK: u64 = 0x01;
+
+/// Scheduling policies with their names, in the order `--max` prints them
+const POLICIES: [(i32, &str); 6] = [
+    (libc::SCHED_OTHER, "SCHED_OTHER"),
+    (libc::SCHED_FIFO, "SCHED_FIFO"),
+    (libc::SCHED_RR, "SCHED_RR"),
+    (libc::SCHED_BATCH, "SCHED_BATCH"),
+    (libc::SCHED_IDLE, "SCHED_IDLE"),
+    (SCHED_DEADLINE, "SCHED_DEADLINE"),
+];
+
+const ADDITIONAL_HELP: &str = "Run a command with SCHED_FIFO priority 10:
+    chrt -f 10 sshd -b 1024
+Retrieve the scheduling attributes of an existing task:
+    chrt -p 700
+Or set them:
+    chrt -r -p 5 700
+SCHED_DEADLINE takes its parameters in nanoseconds and priority 0:
+    chrt -d -T 1000000 -P 10000000 0 cmd";
+
+/// Argument of the sched_setattr(2) and sched_getattr(2) system calls
+#[repr(C)]
+#[derive(Default)]
+struct SchedAttr {
+    size: u32,
+    sched_policy: u32,
+    sched_flags: u64,
+    sched_nice: i32,
+    sched_priority: u32,
+    sched_runtime: u64,
+    sched_deadline: u64,
+    sched_period: u64,
+}
+
+/// Config
+pub struct Config {
+    /// The process PID to operate, 0 for ourself
+    pub pid: Pid,
+    /// Some: The policy we want to apply
+    /// None: Just retrieve scheduling attributes
+    pub policy: Option<i32>,
+    /// Static priority for the new policy
+    pub priority: i32,
+    /// Set SCHED_FLAG_RESET_ON_FORK
+    pub reset_on_fork: bool,
+    /// SCHED_DEADLINE runtime in nanoseconds
+    pub runtime: u64,
+    /// SCHED_DEADLINE deadline in nanoseconds
+    pub deadline: u64,
+    /// SCHED_DEADLINE period in nanoseconds
+    pub period: u64,
+    /// The command we want to launch
+    pub command: Option<Vec<String>>,
+    /// Is --all-tasks option set
+    pub all_tasks: bool,
+    /// Print the attributes before and after changing them
+    pub verbose: bool,
+    /// Only print the valid priority ranges
+    pub max: bool,
+}
+
+/// Command Options
+pub mod options {
+    /// Option --all-tasks
+    pub static ALLTASKS: &str = "all-tasks";
+    /// Option --batch
+    pub static BATCH: &str = "batch";
+    /// Option --deadline
+    pub static DEADLINE: &str = "deadline";
+    /// Option --fifo
+    pub static FIFO: &str = "fifo";
+    /// Option --idle
+    pub static IDLE: &str = "idle";
+    /// Option --other
+    pub static OTHER: &str = "other";
+    /// Option --rr
+    pub static RR: &str = "rr";
+    /// Option --reset-on-fork
+    pub static RESET_ON_FORK: &str = "reset-on-fork";
+    /// Option --sched-runtime
+    pub static SCHED_RUNTIME: &str = "sched-runtime";
+    /// Option --sched-period
+    pub static SCHED_PERIOD: &str = "sched-period";
+    /// Option --sched-deadline
+    pub static SCHED_DEADLINE: &str = "sched-deadline";
+    /// Option --max
+    pub static MAX: &str = "max";
+    /// Option --pid
+    pub static PID: &str = "pid";
+    /// Option --verbose
+    pub static VERBOSE: &str = "verbose";
+    /// Positional priority argument
+    pub static PRIORITY: &str = "priority";
+    /// Positional pid or command arguments
+    pub static PID_CMD: &str = "pidcmd";
+}
+
+impl Config {
+    /// Generate chrt general Config
+    pub fn from(args_matches: &ArgMatches) -> UResult<Self> {
+        let max = args_matches.contains_id(options::MAX);
+        let pid_set = args_matches.contains_id(options::PID);
+        let priority_option = args_matches.get_one::<String>(options::PRIORITY);
+        let pid_cmd_option: Option<Vec<String>> = args_matches
+            .get_many::<String>(options::PID_CMD)
+            .map(|v| v.map(String::from).collect());
+
+        let mut policy = libc::SCHED_RR;
+        for (id, value) in [
+            (options::OTHER, libc::SCHED_OTHER),
+            (options::FIFO, libc::SCHED_FIFO),
+            (options::RR, libc::SCHED_RR),
+            (options::BATCH, libc::SCHED_BATCH),
+            (options::IDLE, libc::SCHED_IDLE),
+            (options::DEADLINE, SCHED_DEADLINE),
+        ] {
+            if args_matches.contains_id(id) {
+                policy = value;
+            }
+        }
+        let mut runtime = parse_nanoseconds(args_matches, options::SCHED_RUNTIME, "runtime")?;
+        let mut deadline = parse_nanoseconds(args_matches, options::SCHED_DEADLINE, "deadline")?;
+        let period = parse_nanoseconds(args_matches, options::SCHED_PERIOD, "period")?;
+
+        let mut config = Self {
+            pid: Pid::from_raw(0),
+            policy: None,
+            priority: 0,
+            reset_on_fork: args_matches.contains_id(options::RESET_ON_FORK),
+            runtime: 0,
+            deadline: 0,
+            period: 0,
+            command: None,
+            all_tasks: args_matches.contains_id(options::ALLTASKS),
+            verbose: args_matches.contains_id(options::VERBOSE),
+            max,
+        };
+        if max {
+            return Ok(config);
+        }
+
+        match (priority_option, pid_cmd_option) {
+            (Some(pid), None) if pid_set => {
+                config.pid = parse_pid(pid)?;
+                return Ok(config);
+            }
+            (Some(priority), Some(mut pid_cmd)) => {
+                config.priority = match priority.parse::<i32>() {
+                    Ok(v) => v,
+                    Err(_) => {
+                        return Err(USimpleError::new(
+                            EXIT_FAILURE,
+                            format!("invalid priority argument: '{}'", priority),
+                        ))
+                    }
+                };
+                if pid_set {
+                    if pid_cmd.len() > 1 {
+                        return Err(UUsageError::new(
+                            EXIT_FAILURE,
+                            "bad usage: PID option set with too many positional argument.",
+                        ));
+                    }
+                    config.pid = parse_pid(&pid_cmd.remove(0))?;
+                } else {
+                    config.command = Some(pid_cmd);
+                }
+            }
+            _ => return Err(UUsageError::new(EXIT_FAILURE, "bad usage")),
+        }
+
+        if policy != SCHED_DEADLINE && (runtime != 0 || deadline != 0 || period != 0) {
+            return Err(USimpleError::new(
+                EXIT_FAILURE,
+                "--sched-{runtime,deadline,period} options are supported for SCHED_DEADLINE only",
+            ));
+        }
+        if policy == SCHED_DEADLINE {
+            // The basic rule is runtime <= deadline <= period, so deadline and
+            // runtime are optional. Checking the values is up to the kernel.
+            if deadline == 0 {
+                deadline = period;
+            }
+            if runtime == 0 {
+                runtime = deadline;
+            }
+        }
+        let (min, max) = priority_range(policy);
+        if config.priority < min || config.priority > max {
+            return Err(USimpleError::new(
+                EXIT_FAILURE,
+                format!(
+                    "unsupported priority value for the policy: {}: see --max for valid range",
+                    config.priority
+                ),
+            ));
+        }
+
+        config.policy = Some(policy);
+        config.runtime = runtime;
+        config.deadline = deadline;
+        config.period = period;
+        Ok(config)
+    }
+}
+
+fn parse_pid(pid: &str) -> UResult<Pid> {
+    match pid.parse::<i32>() {
+        Ok(v) if v >= 0 => Ok(Pid::from_raw(v)),
+        _ => Err(USimpleError::new(
+            EXIT_FAILURE,
+            format!("invalid PID argument: '{}'", pid),
+        )),
+    }
+}
+
+fn parse_nanoseconds(args_matches: &ArgMatches, id: &str, name: &str) -> UResult<u64> {
+    match args_matches.get_one::<String>(id) {
+        None => Ok(0),
+        Some(v) => v.parse::<u64>().map_err(|_| {
+            USimpleError::new(EXIT_FAILURE, format!("invalid {} argument: '{}'", name, v))
+        }),
+    }
+}
+
+/// Valid static priority range of policy
+fn priority_range(policy: i32) -> (i32, i32) {
+    let min = unsafe { libc::sched_get_priority_min(policy) };
+    let max = unsafe { libc::sched_get_priority_max(policy) };
+    (min, max)
+}
+
+fn policy_name(policy: i32) -> &'static str {
+    match POLICIES.iter().find(|(p, _)| *p == policy) {
+        Some((_, name)) => name,
+        None => "unknown",
+    }
+}
+
+/// Print the priority range of every policy the kernel supports
+pub fn show_min_max() {
+    for (policy, name) in POLICIES {
+        let (min, max) = priority_range(policy);
+        if min >= 0 && max >= 0 {
+            println!("{} min/max priority\t: {}/{}", name, min, max);
+        } else {
+            println!("{} not supported?", name);
+        }
+    }
+}
+
+fn sched_getattr(pid: Pid) -> Result<SchedAttr, Errno> {
+    let mut attr = SchedAttr {
+        size: size_of::<SchedAttr>() as u32,
+        ..Default::default()
+    };
+    let res = unsafe {
+        libc::syscall(
+            libc::SYS_sched_getattr,
+            pid.as_raw(),
+            &mut attr as *mut SchedAttr,
+            size_of::<SchedAttr>() as u32,
+            0u32,
+        )
+    };
+    if res == 0 {
+        return Ok(attr);
+    }
+    if Errno::last() != Errno::ENOSYS {
+        return Err(Errno::last());
+    }
+    // Kernels before 3.14 only know sched_getscheduler and sched_getparam
+    let policy = Errno::result(unsafe { libc::sched_getscheduler(pid.as_raw()) })?;
+    let mut param = libc::sched_param { sched_priority: 0 };
+    Errno::result(unsafe { libc::sched_getparam(pid.as_raw(), &mut param) })?;
+    attr.sched_policy = (policy & !libc::SCHED_RESET_ON_FORK) as u32;
+    if policy & libc::SCHED_RESET_ON_FORK != 0 {
+        attr.sched_flags = SCHED_FLAG_RESET_ON_FORK;
+    }
+    attr.sched_priority = param.sched_priority as u32;
+    Ok(attr)
+}
+
+fn sched_setattr(pid: Pid, attr: &SchedAttr) -> Result<(), Errno> {
+    let res = unsafe {
+        libc::syscall(
+            libc::SYS_sched_setattr,
+            pid.as_raw(),
+            attr as *const SchedAttr,
+            0u32,
+        )
+    };
+    Errno::result(res).map(drop)
+}
+
+/// Print the scheduling attributes of pid
+fn show_sched_pid(pid: Pid, state: &str) -> UResult<()> {
+    let pid = match pid.as_raw() {
+        0 => getpid(),
+        _ => pid,
+    };
+    let attr = match sched_getattr(pid) {
+        Ok(v) => v,
+        Err(e) => {
+            return Err(USimpleError::new(
+                EXIT_FAILURE,
+                format!("failed to get pid {}'s policy: {}", pid, e.desc()),
+            ))
+        }
+    };
+    let policy = attr.sched_policy as i32;
+    let reset = match attr.sched_flags & SCHED_FLAG_RESET_ON_FORK {
+        0 => "",
+        _ => "|SCHED_RESET_ON_FORK",
+    };
+    println!(
+        "pid {}'s {} scheduling policy: {}{}",
+        pid,
+        state,
+        policy_name(policy),
+        reset
+    );
+    println!(
+        "pid {}'s {} scheduling priority: {}",
+        pid, state, attr.sched_priority
+    );
+    if policy == SCHED_DEADLINE {
+        println!(
+            "pid {}'s {} runtime/deadline/period parameters: {}/{}/{}",
+            pid, state, attr.sched_runtime, attr.sched_deadline, attr.sched_period
+        );
+    }
+    Ok(())
+}
+
+/// Show the scheduling attributes of pid, or of all its tasks
+pub fn show_sched_info(config: &Config, state: &str) -> UResult<()> {
+    if config.all_tasks && config.pid.as_raw() != 0 {
+        for task in oe_taskset::taskset_common::process_tasks(config.pid) {
+            show_sched_pid(task, state)?;
+        }
+        return Ok(());
+    }
+    show_sched_pid(config.pid, state)
+}
+
+fn set_sched_one(config: &Config, pid: Pid) -> Result<(), Errno> {
+    // The caller only gets here with a policy to set
+    let policy = config.policy.unwrap();
+    if policy == SCHED_DEADLINE {
+        let attr = SchedAttr {
+            size: size_of::<SchedAttr>() as u32,
+            sched_policy: policy as u32,
+            sched_flags: match config.reset_on_fork {
+                true => SCHED_FLAG_RESET_ON_FORK,
+                false => 0,
+            },
+            sched_priority: config.priority as u32,
+            sched_runtime: config.runtime,
+            sched_deadline: config.deadline,
+            sched_period: config.period,
+            ..Default::default()
+        };
+        return sched_setattr(pid, &attr);
+    }
+    let flags = match config.reset_on_fork {
+        true => libc::SCHED_RESET_ON_FORK,
+        false => 0,
+    };
+    let param = libc::sched_param {
+        sched_priority: config.priority,
+    };
+    let res = unsafe { libc::sched_setscheduler(pid.as_raw(), policy | flags, &param) };
+    Errno::result(res).map(drop)
+}
+
+/// Apply the configured policy to pid, or to all its tasks
+pub fn set_sched(config: &Config) -> UResult<()> {
+    let pids = match config.all_tasks && config.pid.as_raw() != 0 {
+        true => oe_taskset::taskset_common::process_tasks(config.pid),
+        false => vec![config.pid],
+    };
+    for pid in pids {
+        if let Err(e) = set_sched_one(config, pid) {
+            let pid = match pid.as_raw() {
+                0 => getpid(),
+                _ => pid,
+            };
+            return Err(USimpleError::new(
+                EXIT_FAILURE,
+                format!("failed to set pid {}'s policy: {}", pid, e.desc()),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Generate chrt general Config
+pub fn parse_chrt_cmd_args(args: impl uucore::Args, about: &str, usage: &str) -> UResult<Config> {
+    let command = chrt_app(about, usage);
+    let arg_list = args.collect_lossy();
+    Config::from(&command.try_get_matches_from(arg_list)?)
+}
+
+/// Command arguments setting
+pub fn chrt_app<'a>(about: &'a str, usage: &'a str) -> Command<'a> {
+    let policies = [
+        options::BATCH,
+        options::DEADLINE,
+        options::FIFO,
+        options::IDLE,
+        options::OTHER,
+        options::RR,
+    ];
+    let policy_arg = |id: &'a str, short: char, help: &'a str| {
+        let others: Vec<&str> = policies.iter().copied().filter(|p| *p != id).collect();
+        Arg::new(id)
+            .short(short)
+            .long(id)
+            .conflicts_with_all(&others)
+            .help(help)
+    };
+    Command::new(uucore::util_name())
+        .version(crate_version!())
+        .about(about)
+        .override_usage(format_usage(usage))
+        .infer_long_args(true)
+        .arg_required_else_help(true)
+        .arg(policy_arg(options::BATCH, 'b', "set policy to SCHED_BATCH"))
+        .arg(policy_arg(
+            options::DEADLINE,
+            'd',
+            "set policy to SCHED_DEADLINE",
+        ))
+        .arg(policy_arg(options::FIFO, 'f', "set policy to SCHED_FIFO"))
+        .arg(policy_arg(options::IDLE, 'i', "set policy to SCHED_IDLE"))
+        .arg(policy_arg(options::OTHER, 'o', "set policy to SCHED_OTHER"))
+        .arg(policy_arg(
+            options::RR,
+            'r',
+            "set policy to SCHED_RR (default)",
+        ))
+        .arg(
+            Arg::new(options::RESET_ON_FORK)
+                .short('R')
+                .long(options::RESET_ON_FORK)
+                .help("set SCHED_RESET_ON_FORK for FIFO or RR"),
+        )
+        .arg(
+            Arg::new(options::SCHED_RUNTIME)
+                .short('T')
+                .long(options::SCHED_RUNTIME)
+                .takes_value(true)
+                .value_name("ns")
+                .help("runtime parameter for DEADLINE"),
+        )
+        .arg(
+            Arg::new(options::SCHED_PERIOD)
+                .short('P')
+                .long(options::SCHED_PERIOD)
+                .takes_value(true)
+                .value_name("ns")
+                .help("period parameter for DEADLINE"),
+        )
+        .arg(
+            Arg::new(options::SCHED_DEADLINE)
+                .short('D')
+                .long(options::SCHED_DEADLINE)
+                .takes_value(true)
+                .value_name("ns")
+                .help("deadline parameter for DEADLINE"),
+        )
+        .arg(
+            Arg::new(options::ALLTASKS)
+                .short('a')
+                .long(options::ALLTASKS)
+                .help("operate on all the tasks (threads) for a given pid"),
+        )
+        .arg(
+            Arg::new(options::MAX)
+                .short('m')
+                .long(options::MAX)
+                .help("show min and max valid priorities"),
+        )
+        .arg(
+            Arg::new(options::PID)
+                .short('p')
+                .long(options::PID)
+                .help("operate on existing given pid"),
+        )
+        .arg(
+            Arg::new(options::VERBOSE)
+                .short('v')
+                .long(options::VERBOSE)
+                .help("display status information"),
+        )
+        .after_help(ADDITIONAL_HELP)
+        .arg(Arg::new(options::PRIORITY).index(1).hide(true))
+        .arg(
+            Arg::new(options::PID_CMD)
+                .index(2)
+                .multiple_values(true)
+                .hide(true),
+        )
+        .trailing_var_arg(true)
+}
@@ -10,22 +10,23 @@ use uucore::error::{UResult, USimpleError};
 use uucore::format_usage;
 
 use std::ffi::CString;
+use std::fs;
 use std::path::Path;
 use std::process::exit;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
 use nix::errno::Errno;
-use nix::fcntl::{flock, open, FlockArg, OFlag};
+use nix::fcntl::{fcntl, flock, open, FcntlArg, FlockArg, OFlag};
 use nix::sys::signal::{
     sigaction, signal, SaFlags, SigAction, SigEvent, SigHandler, SigSet, SigevNotify, Signal,
 };
-use nix::sys::stat::Mode;
+use nix::sys::stat::{fstat, major, minor, stat, FileStat, Mode, SFlag};
 use nix::sys::time::TimeSpec;
 use nix::sys::timer::{Expiration, Timer, TimerSetTimeFlags};
 use nix::sys::wait::{waitpid, WaitPidFlag, WaitStatus};
 use nix::time::{clock_gettime, ClockId};
-use nix::unistd::{access, close, execvp, fork, AccessFlags, ForkResult};
+use nix::unistd::{access, close, execvp, fork, unlink, AccessFlags, ForkResult};
 
 use clap::{crate_version, Arg, Command};
 
@@ -70,10 +71,24 @@ pub struct Config {
     pub nofork: bool,
     ///
     pub verbose: bool,
+    /// Print the lock holders instead of locking.
+    pub holders: bool,
+    /// Use OFD byte-range locks (fcntl) instead of flock(2).
+    pub fcntl: bool,
+    /// First byte of the fcntl lock range.
+    pub start: i64,
+    /// Length of the fcntl lock range, 0 means up to the end of the file.
+    pub length: i64,
     ///
     pub file_to_lock: Option<String>,
     ///
     pub fd_to_lock: Option<i32>,
+    /// Additional lock files given with --lock.
+    pub extra_files_to_lock: Vec<String>,
+    /// Remove the lock files once the command has finished.
+    pub cleanup: bool,
+    /// Wait until the lock is free and exit without running a command.
+    pub wait_for_release: bool,
     ///
     pub cmd_normal: Option<Vec<String>>,
     ///
@@ -100,6 +115,20 @@ pub mod options {
     pub static NOFORK: &str = "no-fork";
     ///
     pub static VERBOSE: &str = "verbose";
+    /// Print the lock holders instead of locking.
+    pub static HOLDERS: &str = "holders";
+    /// Use OFD byte-range locks.
+    pub static FCNTL: &str = "fcntl";
+    /// First byte of the fcntl lock range.
+    pub static START: &str = "start";
+    /// Length of the fcntl lock range.
+    pub static LENGTH: &str = "length";
+    /// Additional file to lock.
+    pub static LOCK: &str = "lock";
+    /// Remove the lock files when done.
+    pub static CLEANUP: &str = "cleanup";
+    /// Wait for the current holder to release the lock.
+    pub static WAIT_FOR_RELEASE: &str = "wait-for-release";
     ///
     pub static FILE: &str = "file";
     ///
@@ -169,6 +198,9 @@ impl Config {
             })
             .transpose()?;
 
+        let start = parse_range_arg(args_matches, options::START, "invalid start offset")?;
+        let length = parse_range_arg(args_matches, options::LENGTH, "invalid length")?;
+
         Ok(Self {
             shared: args_matches.contains_id(options::SHARED),
             exclusive: args_matches.contains_id(options::EXCLUSIVE),
@@ -179,14 +211,37 @@ impl Config {
             close: args_matches.contains_id(options::CLOSE),
             nofork: args_matches.contains_id(options::NOFORK),
             verbose: args_matches.contains_id(options::VERBOSE),
+            holders: args_matches.contains_id(options::HOLDERS),
+            fcntl: args_matches.contains_id(options::FCNTL),
+            start,
+            length,
             file_to_lock: file,
             fd_to_lock: file_descriptor,
+            extra_files_to_lock: args_matches
+                .get_many::<String>(options::LOCK)
+                .map(|v| v.map(String::from).collect())
+                .unwrap_or_default(),
+            cleanup: args_matches.contains_id(options::CLEANUP),
+            wait_for_release: args_matches.contains_id(options::WAIT_FOR_RELEASE),
             cmd_shell: command_shell,
             cmd_normal: command_normal,
         })
     }
 }
 
+fn parse_range_arg(args_matches: &clap::ArgMatches, id: &str, msg: &str) -> UResult<i64> {
+    match args_matches.get_one::<String>(id) {
+        Some(value) => match value.parse::<i64>() {
+            Ok(num) if num >= 0 => Ok(num),
+            _ => Err(USimpleError::new(
+                FLOCK_CMD_PARSE_ERROR,
+                format!("{}: {}", msg, value.quote()),
+            )),
+        },
+        None => Ok(0),
+    }
+}
+
 ///
 pub fn parse_flock_cmd_args(args: impl uucore::Args, about: &str, usage: &str) -> UResult<Config> {
     let command = flock_app(about, usage);
@@ -262,6 +317,52 @@ pub fn flock_app<'a>(about: &'a str, usage: &'a str) -> Command<'a> {
                 .long(options::VERBOSE)
                 .help("increase verbosity"),
         )
+        .arg(
+            Arg::new(options::HOLDERS)
+                .long(options::HOLDERS)
+                .help("show the processes holding a lock on the file and exit"),
+        )
+        .arg(
+            Arg::new(options::FCNTL)
+                .long(options::FCNTL)
+                .help("use fcntl(2) open file description locks instead of flock(2)"),
+        )
+        .arg(
+            Arg::new(options::START)
+                .long(options::START)
+                .value_name("offset")
+                .takes_value(true)
+                .requires(options::FCNTL)
+                .help("first byte of the range to lock (with --fcntl)"),
+        )
+        .arg(
+            Arg::new(options::LENGTH)
+                .long(options::LENGTH)
+                .value_name("bytes")
+                .takes_value(true)
+                .requires(options::FCNTL)
+                .help("length of the range to lock, 0 means to EOF (with --fcntl)"),
+        )
+        .arg(
+            Arg::new(options::LOCK)
+                .long(options::LOCK)
+                .value_name("file")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .value_hint(clap::ValueHint::FilePath)
+                .help("also lock this file; all locks are taken in a fixed order"),
+        )
+        .arg(
+            Arg::new(options::CLEANUP)
+                .long(options::CLEANUP)
+                .help("remove the lock file after the command exits"),
+        )
+        .arg(
+            Arg::new(options::WAIT_FOR_RELEASE)
+                .long(options::WAIT_FOR_RELEASE)
+                .conflicts_with_all(&[options::COMMAND_SHELL, options::COMMAND_NORMAL])
+                .help("wait until the lock is released, then exit"),
+        )
         .arg(Arg::new(options::HELP).short('h').long(options::HELP))
         .arg(Arg::new(options::VERSION).short('v').long(options::VERSION))
         .arg(
@@ -290,8 +391,6 @@ pub fn flock_app<'a>(about: &'a str, usage: &'a str) -> Command<'a> {
 ///
 pub fn handle_input(mut config: Config) -> UResult<i32> {
     let mut status: i32 = 0;
-    let mut fd: i32;
-    let mut open_flags: OFlag = OFlag::from_bits_truncate(0);
     let mut cmd_args: Vec<String> = Vec::new();
     let mut flock_type = FlockArg::LockExclusive;
     let mut flock_block_bool: bool = false;
@@ -329,6 +428,17 @@ pub fn handle_input(mut config: Config) -> UResult<i32> {
         ));
     }
 
+    if config.nofork && config.cleanup {
+        return Err(USimpleError::new(
+            EX_USAGE,
+            "the --no-fork and --cleanup options are incompatible",
+        ));
+    }
+
+    if config.holders {
+        return print_lock_holders(&config);
+    }
+
     if config.cmd_shell.is_some() {
         if config.cmd_normal.is_some() {
             let command_tmp = config.cmd_normal.take().unwrap()[1].clone();
@@ -348,16 +458,7 @@ pub fn handle_input(mut config: Config) -> UResult<i32> {
         cmd_args = config.cmd_normal.clone().unwrap();
     }
 
-    if config.file_to_lock.is_some() {
-        fd = open_file(config.file_to_lock.clone().unwrap(), &mut open_flags)?;
-    } else if config.fd_to_lock.is_some() {
-        fd = config.fd_to_lock.unwrap();
-    } else {
-        return Err(USimpleError::new(
-            EX_USAGE,
-            "requires file descriptor, file or directory",
-        ));
-    }
+    let mut targets = open_lock_targets(&config)?;
 
     if config.timeout_secs.is_some() {
         if config.timeout_secs.unwrap() == 0 {
@@ -408,70 +509,12 @@ pub fn handle_input(mut config: Config) -> UResult<i32> {
         _ => flock_type,
     };
 
-    loop {
-        match flock(fd, flock_arg) {
-            Ok(_) => {
-                // Jump out of the while loop
-                break;
-            }
-            Err(e) => {
-                match e {
-                    // Operation would block
-                    Errno::EWOULDBLOCK => {
-                        // -n option set and failed to lock.
-                        if config.verbose {
-                            eprintln!("failed to get lock");
-                        }
-
-                        return Err(USimpleError::new(
-                            conflict_exit_code,
-                            "failed to get lock".to_string(),
-                        ));
-                    }
-                    // Interrupted system call
-                    Errno::EINTR => {
-                        // Signal received
-                        if unsafe { TIMEOUT_EXPIRED.load(Ordering::Relaxed) } {
-                            // -w option set and failed to lock.
-                            if config.verbose {
-                                eprintln!("timeout while waiting to get lock");
-                            }
-                            return Err(USimpleError::new(conflict_exit_code, ""));
-                        }
-                        // Otherwise try again
-                        continue;
-                    }
-                    // Bad file number || I/O error
-                    Errno::EBADF | Errno::EIO => {
-                        if !open_flags.intersects(OFlag::O_RDWR)
-                            && flock_type != FlockArg::LockShared
-                            && (config).file_to_lock.is_some()
-                            && access(
-                                (config).file_to_lock.clone().unwrap().as_str(),
-                                AccessFlags::R_OK | AccessFlags::W_OK,
-                            )
-                            .is_ok()
-                        {
-                            let _ = close(fd);
-                            open_flags = OFlag::O_RDWR;
-                            fd =
-                                open_file((config).file_to_lock.clone().unwrap(), &mut open_flags)?;
-
-                            if open_flags.intersects(OFlag::O_RDWR) {
-                                continue;
-                            }
-                        }
-                        return Err(USimpleError::new(EX_DATAERR, ""));
-                    }
-
-                    _ => {
-                        if e == Errno::ENOLCK || e == Errno::ENOMEM {
-                            return Err(USimpleError::new(EX_OSERR, ""));
-                        }
-                        return Err(USimpleError::new(EX_DATAERR, ""));
-                    }
-                }
-            }
+    // Take the locks one by one in canonical order, so that concurrent
+    // invocations locking the same set of files cannot deadlock.
+    for idx in 0..targets.len() {
+        if let Err(e) = acquire_lock(&config, &mut targets[idx], flock_arg, conflict_exit_code) {
+            release_locks(&config, &targets[..idx]);
+            return Err(e);
         }
     }
 
@@ -488,6 +531,11 @@ pub fn handle_input(mut config: Config) -> UResult<i32> {
         );
     }
 
+    if config.wait_for_release {
+        release_locks(&config, &targets);
+        return Ok(0);
+    }
+
     if !cmd_args.is_empty() {
         /* Clear any inherited settings */
         let _sig_res = unsafe { signal(Signal::SIGCHLD, SigHandler::SigDfl) };
@@ -510,7 +558,9 @@ pub fn handle_input(mut config: Config) -> UResult<i32> {
                 // child
                 ForkResult::Child => {
                     if config.close {
-                        let _ = close(fd);
+                        for target in &targets {
+                            let _ = close(target.fd);
+                        }
                     }
                     run_program(cmd_args);
                 }
@@ -549,9 +599,224 @@ pub fn handle_input(mut config: Config) -> UResult<i32> {
         }
     }
 
+    if config.cleanup {
+        cleanup_lock_files(&targets);
+    }
+
     Ok(status)
 }
 
+/// A file or file descriptor to lock.
+pub struct LockTarget {
+    /// Path of the lock file, `None` for an inherited file descriptor.
+    pub path: Option<String>,
+    /// Descriptor the lock is taken on.
+    pub fd: i32,
+    /// Flags the lock file was opened with.
+    pub open_flags: OFlag,
+}
+
+/// Open every lock target and sort them by device and inode.
+///
+/// Targets naming the same file are merged, as two flock(2) locks on
+/// separate descriptors of one file would conflict with each other.
+fn open_lock_targets(config: &Config) -> UResult<Vec<LockTarget>> {
+    let mut targets: Vec<LockTarget> = Vec::new();
+    if let Some(file_path) = &config.file_to_lock {
+        let mut open_flags = OFlag::from_bits_truncate(0);
+        let fd = open_file(file_path.clone(), &mut open_flags)?;
+        targets.push(LockTarget {
+            path: Some(file_path.clone()),
+            fd,
+            open_flags,
+        });
+    } else if let Some(fd) = config.fd_to_lock {
+        targets.push(LockTarget {
+            path: None,
+            fd,
+            open_flags: OFlag::from_bits_truncate(0),
+        });
+    } else {
+        return Err(USimpleError::new(
+            EX_USAGE,
+            "requires file descriptor, file or directory",
+        ));
+    }
+    for file_path in &config.extra_files_to_lock {
+        let mut open_flags = OFlag::from_bits_truncate(0);
+        let fd = open_file(file_path.clone(), &mut open_flags)?;
+        targets.push(LockTarget {
+            path: Some(file_path.clone()),
+            fd,
+            open_flags,
+        });
+    }
+    if targets.len() == 1 {
+        return Ok(targets);
+    }
+
+    let mut keyed_targets: Vec<((u64, u64), LockTarget)> = Vec::new();
+    for target in targets {
+        let st = fstat(target.fd).map_err(|_e| USimpleError::new(EX_DATAERR, ""))?;
+        keyed_targets.push(((st.st_dev, st.st_ino), target));
+    }
+    keyed_targets.sort_by_key(|(key, _target)| *key);
+
+    let mut sorted_targets: Vec<LockTarget> = Vec::new();
+    let mut last_key: Option<(u64, u64)> = None;
+    for (key, target) in keyed_targets {
+        if last_key == Some(key) {
+            if target.path.is_some() {
+                let _ = close(target.fd);
+            }
+            continue;
+        }
+        last_key = Some(key);
+        sorted_targets.push(target);
+    }
+    Ok(sorted_targets)
+}
+
+/// Lock a single target, retrying on signals until the timeout expires.
+fn acquire_lock(
+    config: &Config,
+    target: &mut LockTarget,
+    flock_arg: FlockArg,
+    conflict_exit_code: i32,
+) -> UResult<()> {
+    loop {
+        let lock_res = if config.fcntl {
+            ofd_lock(target.fd, flock_arg, config.start, config.length)
+        } else {
+            flock(target.fd, flock_arg)
+        };
+        match lock_res {
+            Ok(_) => {
+                // The lock file may have been removed by a --cleanup holder, or
+                // replaced, while we were waiting; lock the file now at the path.
+                if flock_arg != FlockArg::Unlock
+                    && flock_arg != FlockArg::UnlockNonblock
+                    && target.path.is_some()
+                    && !lock_file_is_current(target)
+                {
+                    let _ = close(target.fd);
+                    target.fd = open_file(target.path.clone().unwrap(), &mut target.open_flags)?;
+                    continue;
+                }
+                return Ok(());
+            }
+            Err(e) => {
+                match e {
+                    // Operation would block, fcntl(2) may also report EACCES
+                    Errno::EWOULDBLOCK | Errno::EACCES => {
+                        // -n option set and failed to lock.
+                        if config.verbose {
+                            eprintln!("failed to get lock");
+                            print_verbose_holders(target.fd);
+                        }
+
+                        return Err(USimpleError::new(
+                            conflict_exit_code,
+                            "failed to get lock".to_string(),
+                        ));
+                    }
+                    // Interrupted system call
+                    Errno::EINTR => {
+                        // Signal received
+                        if unsafe { TIMEOUT_EXPIRED.load(Ordering::Relaxed) } {
+                            // -w option set and failed to lock.
+                            if config.verbose {
+                                eprintln!("timeout while waiting to get lock");
+                                print_verbose_holders(target.fd);
+                            }
+                            return Err(USimpleError::new(conflict_exit_code, ""));
+                        }
+                        // Otherwise try again
+                        continue;
+                    }
+                    // Bad file number || I/O error
+                    Errno::EBADF | Errno::EIO => {
+                        if !target.open_flags.intersects(OFlag::O_RDWR)
+                            && !matches!(
+                                flock_arg,
+                                FlockArg::LockShared | FlockArg::LockSharedNonblock
+                            )
+                            && target.path.is_some()
+                            && access(
+                                target.path.clone().unwrap().as_str(),
+                                AccessFlags::R_OK | AccessFlags::W_OK,
+                            )
+                            .is_ok()
+                        {
+                            let _ = close(target.fd);
+                            target.open_flags = OFlag::O_RDWR;
+                            target.fd =
+                                open_file(target.path.clone().unwrap(), &mut target.open_flags)?;
+
+                            if target.open_flags.intersects(OFlag::O_RDWR) {
+                                continue;
+                            }
+                        }
+                        return Err(USimpleError::new(EX_DATAERR, ""));
+                    }
+
+                    _ => {
+                        if e == Errno::ENOLCK || e == Errno::ENOMEM {
+                            return Err(USimpleError::new(EX_OSERR, ""));
+                        }
+                        return Err(USimpleError::new(EX_DATAERR, ""));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Drop the locks taken so far after a later target failed.
+fn release_locks(config: &Config, targets: &[LockTarget]) {
+    for target in targets {
+        let _ = if config.fcntl {
+            ofd_lock(target.fd, FlockArg::Unlock, config.start, config.length)
+        } else {
+            flock(target.fd, FlockArg::Unlock)
+        };
+    }
+}
+
+/// Check that the path of a target still names the file its descriptor refers to.
+fn lock_file_is_current(target: &LockTarget) -> bool {
+    match (&target.path, fstat(target.fd)) {
+        (Some(file_path), Ok(fd_st)) => match stat(file_path.as_str()) {
+            Ok(path_st) => path_st.st_dev == fd_st.st_dev && path_st.st_ino == fd_st.st_ino,
+            Err(_e) => false,
+        },
+        _ => true,
+    }
+}
+
+/// Unlink the lock files while the locks are still held.
+///
+/// Only regular files which the path still resolves to are removed, so a lock
+/// file recreated by another process in the meantime is left alone.
+fn cleanup_lock_files(targets: &[LockTarget]) {
+    for target in targets {
+        let file_path = match &target.path {
+            Some(file_path) => file_path,
+            None => continue,
+        };
+        let is_regular = match fstat(target.fd) {
+            Ok(st) => SFlag::from_bits_truncate(st.st_mode) & SFlag::S_IFMT == SFlag::S_IFREG,
+            Err(_e) => false,
+        };
+        if !is_regular || !lock_file_is_current(target) {
+            continue;
+        }
+        if let Err(e) = unlink(file_path.as_str()) {
+            eprintln!("cannot remove lock file {}: {}", file_path, e.desc());
+        }
+    }
+}
+
 ///
 pub fn open_file(file_path: String, flags: &mut OFlag) -> UResult<i32> {
     let mut fl: OFlag = if flags.bits() == 0 {
@@ -611,6 +876,242 @@ pub fn open_file(file_path: String, flags: &mut OFlag) -> UResult<i32> {
     Ok(fd)
 }
 
+/// Take, or release, an OFD lock on a byte range, following the semantics of `flock_arg`.
+fn ofd_lock(fd: i32, flock_arg: FlockArg, start: i64, length: i64) -> nix::Result<()> {
+    let (lock_type, nonblocking) = match flock_arg {
+        FlockArg::LockShared => (libc::F_RDLCK, false),
+        FlockArg::LockSharedNonblock => (libc::F_RDLCK, true),
+        FlockArg::LockExclusive => (libc::F_WRLCK, false),
+        FlockArg::LockExclusiveNonblock => (libc::F_WRLCK, true),
+        FlockArg::Unlock => (libc::F_UNLCK, false),
+        _ => (libc::F_UNLCK, true),
+    };
+    let fl = libc::flock {
+        l_type: lock_type as libc::c_short,
+        l_whence: libc::SEEK_SET as libc::c_short,
+        l_start: start,
+        l_len: length,
+        // Must be zero for OFD locks.
+        l_pid: 0,
+    };
+    let arg = if nonblocking {
+        FcntlArg::F_OFD_SETLK(&fl)
+    } else {
+        FcntlArg::F_OFD_SETLKW(&fl)
+    };
+    fcntl(fd, arg).map(drop)
+}
+
+/// A lock from `/proc/locks` that is held on the lock target.
+pub struct LockHolder {
+    /// Process holding the lock, `-1` when the kernel does not report one.
+    pub pid: i32,
+    /// Lock class: FLOCK, POSIX, OFDLCK, LEASE...
+    pub lock_class: String,
+    /// Lock mode: READ or WRITE.
+    pub lock_mode: String,
+    /// First byte of the locked range.
+    pub start: String,
+    /// Last byte of the locked range, or EOF.
+    pub end: String,
+    /// Command line of the holding process.
+    pub command: String,
+}
+
+/// Parse `/proc/locks` and return the granted locks on the given device and inode.
+///
+/// Entries for waiters (`->`) are skipped. OFD locks are not owned by a process,
+/// so their holders are found by scanning `/proc/<pid>/fdinfo`.
+pub fn get_lock_holders(st: &FileStat) -> UResult<Vec<LockHolder>> {
+    let content = fs::read_to_string("/proc/locks")
+        .map_err(|e| USimpleError::new(EX_OSERR, format!("cannot read /proc/locks: {}", e)))?;
+    let mut holders: Vec<LockHolder> = Vec::new();
+
+    for entry in content.lines().filter_map(ProcLockEntry::parse) {
+        if !entry.is_on(st) {
+            continue;
+        }
+        let pids = if entry.pid > 0 {
+            vec![entry.pid]
+        } else {
+            find_fdinfo_lock_owners(&entry)
+        };
+        if pids.is_empty() {
+            holders.push(entry.to_holder(entry.pid, "-".to_string()));
+        }
+        for pid in pids {
+            holders.push(entry.to_holder(pid, get_process_cmdline(pid)));
+        }
+    }
+    Ok(holders)
+}
+
+/// One line of `/proc/locks`, also found after `lock:` in `/proc/<pid>/fdinfo/<fd>`.
+struct ProcLockEntry<'a> {
+    lock_class: &'a str,
+    lock_mode: &'a str,
+    pid: i32,
+    dev_ino: &'a str,
+    start: &'a str,
+    end: &'a str,
+}
+
+impl<'a> ProcLockEntry<'a> {
+    /// Split a lock line into its fields. Blocked waiters yield `None`.
+    fn parse(line: &'a str) -> Option<Self> {
+        let mut fields = line.split_whitespace();
+        // Skip the "1:" ordinal.
+        fields.next()?;
+        let lock_class = fields.next()?;
+        if lock_class == "->" {
+            return None;
+        }
+        // ADVISORY / MANDATORY / ACTIVE / BREAKING...
+        fields.next()?;
+        Some(Self {
+            lock_mode: fields.next()?,
+            pid: fields.next()?.parse::<i32>().ok()?,
+            dev_ino: fields.next()?,
+            start: fields.next()?,
+            end: fields.next()?,
+            lock_class,
+        })
+    }
+
+    /// Compare the `MAJOR:MINOR:INODE` field (major and minor in hex) with a stat result.
+    fn is_on(&self, st: &FileStat) -> bool {
+        let parts: Vec<&str> = self.dev_ino.split(':').collect();
+        if parts.len() != 3 {
+            return false;
+        }
+        match (
+            u64::from_str_radix(parts[0], 16),
+            u64::from_str_radix(parts[1], 16),
+            parts[2].parse::<u64>(),
+        ) {
+            (Ok(maj), Ok(min), Ok(ino)) => {
+                maj == major(st.st_dev) && min == minor(st.st_dev) && ino == st.st_ino
+            }
+            _ => false,
+        }
+    }
+
+    fn to_holder(&self, pid: i32, command: String) -> LockHolder {
+        LockHolder {
+            pid,
+            lock_class: self.lock_class.to_string(),
+            lock_mode: self.lock_mode.to_string(),
+            start: self.start.to_string(),
+            end: self.end.to_string(),
+            command,
+        }
+    }
+}
+
+/// Find the processes whose open file descriptions carry the given lock.
+fn find_fdinfo_lock_owners(entry: &ProcLockEntry) -> Vec<i32> {
+    let mut pids: Vec<i32> = Vec::new();
+    let proc_dir = match fs::read_dir("/proc") {
+        Ok(proc_dir) => proc_dir,
+        Err(_e) => return pids,
+    };
+    for proc_entry in proc_dir.flatten() {
+        let pid = match proc_entry.file_name().to_string_lossy().parse::<i32>() {
+            Ok(pid) => pid,
+            Err(_e) => continue,
+        };
+        let fdinfo_dir = match fs::read_dir(proc_entry.path().join("fdinfo")) {
+            Ok(fdinfo_dir) => fdinfo_dir,
+            Err(_e) => continue,
+        };
+        let holds_lock = fdinfo_dir.flatten().any(|fdinfo| {
+            fs::read_to_string(fdinfo.path())
+                .map(|content| {
+                    content
+                        .lines()
+                        .filter_map(|l| l.strip_prefix("lock:"))
+                        .filter_map(ProcLockEntry::parse)
+                        .any(|l| l.lock_class == entry.lock_class && l.dev_ino == entry.dev_ino)
+                })
+                .unwrap_or(false)
+        });
+        if holds_lock {
+            pids.push(pid);
+        }
+    }
+    pids
+}
+
+/// Return the command line of a process, or its `[comm]` for kernel threads.
+fn get_process_cmdline(pid: i32) -> String {
+    let cmdline = fs::read(format!("/proc/{}/cmdline", pid))
+        .map(|raw| {
+            raw.split(|b| *b == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                .collect::<Vec<String>>()
+                .join(" ")
+        })
+        .unwrap_or_default();
+    if !cmdline.is_empty() {
+        return cmdline;
+    }
+    match fs::read_to_string(format!("/proc/{}/comm", pid)) {
+        Ok(comm) => format!("[{}]", comm.trim_end()),
+        Err(_e) => "-".to_string(),
+    }
+}
+
+fn print_lock_holders(config: &Config) -> UResult<i32> {
+    let st = if let Some(file_path) = &config.file_to_lock {
+        stat(file_path.as_str()).map_err(|e| {
+            USimpleError::new(
+                EX_NOINPUT,
+                format!("cannot stat {}: {}", file_path.quote(), e.desc()),
+            )
+        })?
+    } else if let Some(fd) = config.fd_to_lock {
+        fstat(fd).map_err(|e| {
+            USimpleError::new(EX_NOINPUT, format!("cannot stat fd {}: {}", fd, e.desc()))
+        })?
+    } else {
+        return Err(USimpleError::new(
+            EX_USAGE,
+            "requires file descriptor, file or directory",
+        ));
+    };
+
+    println!(
+        "{:<8}{:<7}{:<6}{:<10}{:<10}COMMAND",
+        "PID", "TYPE", "MODE", "START", "END"
+    );
+    for holder in get_lock_holders(&st)? {
+        println!(
+            "{:<8}{:<7}{:<6}{:<10}{:<10}{}",
+            holder.pid,
+            holder.lock_class,
+            holder.lock_mode,
+            holder.start,
+            holder.end,
+            holder.command
+        );
+    }
+    Ok(0)
+}
+
+fn print_verbose_holders(fd: i32) {
+    let holders = match fstat(fd) {
+        Ok(st) => get_lock_holders(&st).unwrap_or_default(),
+        Err(_e) => Vec::new(),
+    };
+    for holder in holders {
+        eprintln!(
+            "lock held by pid {} ({} {}): {}",
+            holder.pid, holder.lock_class, holder.lock_mode, holder.command
+        );
+    }
+}
+
 fn run_program(cmd_args: Vec<String>) {
     let args: Vec<CString> = cmd_args
         .iter()
@@ -680,7 +1181,7 @@ fn cancel_timer(mut timer: Timer) {
 
 fn gettime_monotonic() -> TimeSpec {
     let mut clock_time = TimeSpec::new(0, 0);
-    let clock_time_res = clock_gettime(ClockId::CLOCK_REALTIME);
+    let clock_time_res = clock_gettime(ClockId::CLOCK_MONOTONIC);
     match clock_time_res {
         Ok(time_spec) => clock_time = time_spec,
         Err(_e) => {}
@@ -5,18 +5,53 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
-use std::io::BufRead;
-use uucore::error::UResult;
+use std::io::{BufRead, BufReader, Write};
+use uucore::display::Quotable;
+use uucore::error::{UResult, USimpleError};
 use uucore::format_usage;
 
-use std::fs::File;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use clap::{crate_version, Arg, Command};
 
 /// MEMINFO
 pub static MEMINFO: &str = "/proc/meminfo";
+/// Cgroup membership of the current process.
+pub static PROC_SELF_CGROUP: &str = "/proc/self/cgroup";
+/// Mount point of the cgroup hierarchies.
+pub static CGROUP_ROOT: &str = "/sys/fs/cgroup";
+/// NUMA nodes, each with its own meminfo.
+pub static NODE_DIR: &str = "/sys/devices/system/node";
+/// Huge page pools, one directory per page size.
+pub static HUGEPAGES_DIR: &str = "/sys/kernel/mm/hugepages";
+/// Block devices, searched for zram disks.
+pub static SYS_BLOCK_DIR: &str = "/sys/block";
+
+/// Fields of `/proc/meminfo` shown by `--detail`, and whether they count pages.
+const DETAIL_FIELDS: [(&str, bool); 16] = [
+    ("Slab", false),
+    ("SReclaimable", false),
+    ("SUnreclaim", false),
+    ("KernelStack", false),
+    ("Dirty", false),
+    ("Writeback", false),
+    ("AnonHugePages", false),
+    ("Committed_AS", false),
+    ("CommitLimit", false),
+    ("HugePages_Total", true),
+    ("HugePages_Free", true),
+    ("HugePages_Rsvd", true),
+    ("HugePages_Surp", true),
+    ("Hugepagesize", false),
+    ("Zswap", false),
+    ("Zswapped", false),
+];
 
 ///
+#[derive(Default)]
 struct MemInfo {
     mem_total: u64,
     mem_free: u64,
@@ -24,8 +59,44 @@ struct MemInfo {
     mem_available: u64,
     buffers: u64,
     cached: u64,
+    s_reclaimable: u64,
     swap_total: u64,
     swap_free: u64,
+    low_total: u64,
+    low_free: u64,
+    high_total: u64,
+    high_free: u64,
+    commit_limit: u64,
+    committed_as: u64,
+}
+
+impl MemInfo {
+    /// Page cache plus reclaimable slab, the procps "cache" column.
+    fn cache(&self) -> u64 {
+        self.cached + self.s_reclaimable
+    }
+
+    /// Memory that is not available, as computed by procps-ng 4.x.
+    fn used(&self) -> u64 {
+        if self.mem_available <= self.mem_total {
+            self.mem_total - self.mem_available
+        } else {
+            self.mem_total.saturating_sub(self.mem_free)
+        }
+    }
+
+    fn swap_used(&self) -> u64 {
+        self.swap_total.saturating_sub(self.swap_free)
+    }
+
+    /// Low memory, which is all memory on kernels without highmem.
+    fn low(&self) -> (u64, u64) {
+        if self.low_total == 0 {
+            (self.mem_total, self.mem_free)
+        } else {
+            (self.low_total, self.low_free)
+        }
+    }
 }
 
 enum Unit {
@@ -80,11 +151,27 @@ pub struct Config {
     ///
     pub total: bool,
     ///
-    pub seconds: Option<u32>,
+    pub seconds: Option<f64>,
     ///
     pub count: Option<u32>,
     ///
     pub wide: bool,
+    /// Report the limits of a memory cgroup, an empty path means our own cgroup.
+    pub cgroup: Option<String>,
+    /// Show slab, commit, huge page, dirty page and compressed swap usage.
+    pub detail: bool,
+    /// Show the memory of each NUMA node.
+    pub node: bool,
+    /// Print one JSON object per sample.
+    pub json: bool,
+    /// Print one CSV row per sample, after a header row.
+    pub csv: bool,
+    /// Print the sample timestamps as seconds since the epoch instead of ISO-8601.
+    pub epoch: bool,
+    /// Print swap, cache, used and free memory on a single line.
+    pub line: bool,
+    /// Show a row with the committed memory and the commit limit.
+    pub committed: bool,
 }
 /// options
 pub mod options {
@@ -124,12 +211,49 @@ pub mod options {
     pub static COUNT: &str = "count";
     ///
     pub static WIDE: &str = "wide";
+    /// Report cgroup memory instead of host memory.
+    pub static CGROUP: &str = "cgroup";
+    /// Show extended memory categories.
+    pub static DETAIL: &str = "detail";
+    /// Show per-NUMA-node memory.
+    pub static NODE: &str = "node";
+    /// JSON output.
+    pub static JSON: &str = "json";
+    /// CSV output.
+    pub static CSV: &str = "csv";
+    /// Timestamp format of JSON and CSV output.
+    pub static TIMESTAMP: &str = "timestamp";
+    /// Single line output.
+    pub static LINE: &str = "line";
+    /// Show the committed memory row.
+    pub static COMMITTED: &str = "committed";
 }
 
 impl Config {
     /// from stdin
-    pub fn from(options: &clap::ArgMatches) -> Self {
-        Self {
+    pub fn from(options: &clap::ArgMatches) -> UResult<Self> {
+        let seconds = options
+            .value_of(options::SECONDS)
+            .map(|v| match v.replace(',', ".").parse::<f64>() {
+                Ok(secs) if secs > 0.0 && secs.is_finite() => Ok(secs),
+                _ => Err(USimpleError::new(
+                    1,
+                    format!("seconds argument {} failed", v.quote()),
+                )),
+            })
+            .transpose()?;
+        let count = options
+            .value_of(options::COUNT)
+            .map(|v| match v.parse::<u32>() {
+                Ok(count) if count > 0 => Ok(count),
+                _ => Err(USimpleError::new(
+                    1,
+                    format!("failed to parse count argument: {}", v.quote()),
+                )),
+            })
+            .transpose()?;
+
+        Ok(Self {
             bytes: options.is_present(options::BYTES),
             kilo: options.is_present(options::KILO),
             mega: options.is_present(options::MEGA),
@@ -145,12 +269,22 @@ impl Config {
             si: options.is_present(options::SI),
             lohi: options.is_present(options::LOHI),
             total: options.is_present(options::TOTAL),
-            seconds: options
-                .value_of(options::SECONDS)
-                .map(|v| v.parse().unwrap()),
-            count: options.value_of(options::COUNT).map(|v| v.parse().unwrap()),
+            seconds,
+            count,
             wide: options.is_present(options::WIDE),
-        }
+            cgroup: if options.is_present(options::CGROUP) {
+                Some(options.value_of(options::CGROUP).unwrap_or("").to_string())
+            } else {
+                None
+            },
+            detail: options.is_present(options::DETAIL),
+            node: options.is_present(options::NODE),
+            json: options.is_present(options::JSON),
+            csv: options.is_present(options::CSV),
+            epoch: options.value_of(options::TIMESTAMP) == Some("epoch"),
+            line: options.is_present(options::LINE),
+            committed: options.is_present(options::COMMITTED),
+        })
     }
 }
 
@@ -163,7 +297,7 @@ pub fn parse_free_cmd_args<'a>(
     let app = free_app(about, usage);
     let matches = app.get_matches_from_safe(args)?;
 
-    Ok(Config::from(&matches))
+    Config::from(&matches)
 }
 
 ///
@@ -303,122 +437,662 @@ pub fn free_app<'a>(about: &'a str, usage: &'a str) -> Command<'a> {
                 .help("repeat printing N times, then exit"),
         )
         .arg(Arg::new("wide").short('w').long("wide").help("wide output"))
+        .arg(
+            Arg::new("line")
+                .short('L')
+                .long("line")
+                .conflicts_with_all(&["lohi", "total", "wide", "committed"])
+                .help("show output on a single line"),
+        )
+        .arg(
+            Arg::new("committed")
+                .short('v')
+                .long("committed")
+                .help("show committed memory and commit limit"),
+        )
+        .arg(
+            Arg::new("cgroup")
+                .long("cgroup")
+                .value_name("path")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .help("show memory of a cgroup (default: our own) instead of the host"),
+        )
+        .arg(
+            Arg::new("detail")
+                .long("detail")
+                .help("show slab, commit, huge page, dirty and compressed memory"),
+        )
+        .arg(
+            Arg::new("node")
+                .long("node")
+                .help("show memory of each NUMA node"),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .conflicts_with_all(&["csv", "human", "lohi", "detail", "node", "line"])
+                .help("print each sample as a JSON object"),
+        )
+        .arg(
+            Arg::new("csv")
+                .long("csv")
+                .conflicts_with_all(&["json", "human", "lohi", "detail", "node", "line"])
+                .help("print each sample as a CSV row"),
+        )
+        .arg(
+            Arg::new("timestamp")
+                .long("timestamp")
+                .value_name("format")
+                .takes_value(true)
+                .possible_values(["iso", "epoch"])
+                .help("timestamp format of --json and --csv samples (default: iso)"),
+        )
 }
 
 /// handle input
 pub fn handle_input(config: &Config) -> UResult<()> {
-    if config.seconds.is_some() && config.count.is_none() {
-        loop {
-            print_memory(config);
-            std::thread::sleep(std::time::Duration::from_secs(
-                config.seconds.unwrap() as u64
-            ));
-        }
+    // Without -s and -c print once; -c alone samples every second.
+    let samples = match (config.seconds, config.count) {
+        (_, Some(count)) => Some(count),
+        (Some(_), None) => None,
+        (None, None) => Some(1),
+    };
+    let interval = Duration::from_secs_f64(config.seconds.unwrap_or(1.0));
+
+    if config.csv {
+        println!(
+            "timestamp,mem_total,mem_used,mem_free,mem_shared,mem_buffers,mem_cache,\
+             mem_available,swap_total,swap_used,swap_free"
+        );
     }
-    if config.count.is_some() && config.seconds.is_none() {
-        for _ in 0..config.count.unwrap() {
-            print_memory(config);
-            std::thread::sleep(std::time::Duration::from_secs(1));
+
+    // Follow an absolute schedule, so the time spent printing does not add up.
+    let start = Instant::now();
+    let mut next_sample = start;
+    let mut taken: u32 = 0;
+    loop {
+        print_memory(config)?;
+        let _ = std::io::stdout().flush();
+        taken += 1;
+        if samples == Some(taken) {
+            break;
         }
+        next_sample += interval;
+        std::thread::sleep(next_sample.saturating_duration_since(Instant::now()));
     }
-    if config.count.is_some() && config.seconds.is_some() {
-        for _ in 0..config.count.unwrap() {
-            print_memory(config);
-            std::thread::sleep(std::time::Duration::from_secs(
-                config.seconds.unwrap() as u64
-            ));
-        }
+    Ok(())
+}
+
+fn print_memory(config: &Config) -> UResult<()> {
+    let unit = anaylze_unit(config);
+    let mem_info = match &config.cgroup {
+        Some(cgroup) => get_cgroup_mem_info(cgroup, &get_mem_info()?)?,
+        None => get_mem_info()?,
+    };
+    if config.json || config.csv {
+        record_print(&mem_info, unit, config);
+        return Ok(());
+    }
+    if config.line {
+        line_print(&mem_info, config);
+    } else if config.human {
+        hum_print(&mem_info, config);
+    } else {
+        raw_print(&mem_info, unit, config);
     }
-    if config.count.is_none() && config.seconds.is_none() {
-        print_memory(config);
+    if config.detail {
+        let content = fs::read_to_string(MEMINFO).unwrap_or_default();
+        println!();
+        print_detail(&parse_meminfo_fields(&content), config);
+        print_hugepage_pools(config);
+        print_zram(config);
+    }
+    if config.node {
+        print_nodes(config);
     }
     Ok(())
 }
 
-fn print_memory(config: &Config) {
-    let unit = anaylze_unit(config);
+/// Print a sample as a JSON object or CSV row, in the selected unit.
+fn record_print(meminfo: &MemInfo, unit: Unit, config: &Config) {
+    let now = SystemTime::now();
+    let timestamp = if config.epoch {
+        let since_epoch = now.duration_since(UNIX_EPOCH).unwrap_or_default();
+        format!(
+            "{}.{:03}",
+            since_epoch.as_secs(),
+            since_epoch.subsec_millis()
+        )
+    } else {
+        format_iso8601(now)
+    };
+    let values = [
+        ("total", meminfo.mem_total),
+        ("used", meminfo.used()),
+        ("free", meminfo.mem_free),
+        ("shared", meminfo.shared),
+        ("buffers", meminfo.buffers),
+        ("cache", meminfo.cache()),
+        ("available", meminfo.mem_available),
+    ]
+    .map(|(name, kb)| (name, convert_unit(kb, &unit)));
+    let swap_values = [
+        ("total", meminfo.swap_total),
+        ("used", meminfo.swap_used()),
+        ("free", meminfo.swap_free),
+    ]
+    .map(|(name, kb)| (name, convert_unit(kb, &unit)));
+
+    if config.csv {
+        let row: Vec<String> = values
+            .iter()
+            .chain(swap_values.iter())
+            .map(|(_name, value)| value.to_string())
+            .collect();
+        println!("{},{}", timestamp, row.join(","));
+    } else {
+        let object = |fields: &[(&str, u64)]| {
+            fields
+                .iter()
+                .map(|(name, value)| format!("\"{}\":{}", name, value))
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+        let timestamp = if config.epoch {
+            timestamp
+        } else {
+            format!("\"{}\"", timestamp)
+        };
+        println!(
+            "{{\"timestamp\":{},\"unit\":\"{}\",\"mem\":{{{}}},\"swap\":{{{}}}}}",
+            timestamp,
+            unit_name(&unit),
+            object(&values),
+            object(&swap_values)
+        );
+    }
+}
+
+fn unit_name(unit: &Unit) -> &'static str {
+    match unit {
+        Unit::Bytes => "B",
+        Unit::Kilo => "kB",
+        Unit::Mega => "MB",
+        Unit::Giga => "GB",
+        Unit::Tera => "TB",
+        Unit::Peta => "PB",
+        Unit::Kibi => "KiB",
+        Unit::Mebi => "MiB",
+        Unit::Gibi => "GiB",
+        Unit::Tebi => "TiB",
+        Unit::Pebi => "PiB",
+    }
+}
+
+/// Format a time as an ISO-8601 UTC timestamp with milliseconds.
+fn format_iso8601(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (hour, min, sec) = ((secs / 3600) % 24, (secs / 60) % 60, secs % 60);
+
+    // Civil date from days since 1970-01-01, proleptic Gregorian calendar.
+    let days = (secs / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        hour,
+        min,
+        sec,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Parse `Key: value [kB]` lines, as found in `/proc/meminfo`, and the
+/// `Node N Key: value kB` lines of a NUMA node meminfo.
+fn parse_meminfo_fields(content: &str) -> Vec<(String, u64)> {
+    let mut fields = Vec::new();
+    for line in content.lines() {
+        let mut parts = line.split_whitespace().peekable();
+        if parts.peek() == Some(&"Node") {
+            parts.next();
+            parts.next();
+        }
+        if let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+            if let Ok(value) = value.parse::<u64>() {
+                fields.push((key.trim_end_matches(':').to_string(), value));
+            }
+        }
+    }
+    fields
+}
+
+fn meminfo_field(fields: &[(String, u64)], key: &str) -> Option<u64> {
+    fields.iter().find(|(k, _v)| k == key).map(|(_k, v)| *v)
+}
+
+/// Format a size in kB with the selected unit, or scaled with `-h`.
+fn format_size(kb: u64, config: &Config) -> String {
     if config.human {
-        hum_print(&get_mem_info(), config.lohi, config.total, config.wide);
-        return;
+        let human = get_suit_unit(kb);
+        format!("{:.1}{}", human.num, human.unit)
     } else {
-        raw_print(
-            &get_mem_info(),
-            unit,
-            config.lohi,
-            config.total,
-            config.wide,
+        convert_unit(kb, &anaylze_unit(config)).to_string()
+    }
+}
+
+fn print_detail(fields: &[(String, u64)], config: &Config) {
+    for (key, is_count) in DETAIL_FIELDS.iter() {
+        if let Some(value) = meminfo_field(fields, key) {
+            let value = if *is_count {
+                value.to_string()
+            } else {
+                format_size(value, config)
+            };
+            println!("{:<20}{:>12}", format!("{}:", key), value);
+        }
+    }
+}
+
+/// Print the size, free, reserved and surplus pages of each huge page pool.
+fn print_hugepage_pools(config: &Config) {
+    let mut pools: Vec<(u64, PathBuf)> = match fs::read_dir(HUGEPAGES_DIR) {
+        Ok(dir) => dir
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let size = name.strip_prefix("hugepages-")?.strip_suffix("kB")?;
+                Some((size.parse::<u64>().ok()?, entry.path()))
+            })
+            .collect(),
+        Err(_e) => return,
+    };
+    if pools.is_empty() {
+        return;
+    }
+    pools.sort();
+    let read_count = |dir: &Path, name: &str| {
+        fs::read_to_string(dir.join(name))
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+
+    println!();
+    println!(
+        "{:<12}{:>12}{:>12}{:>12}{:>12}",
+        "HugePages", "total", "free", "reserved", "surplus"
+    );
+    for (size, dir) in pools {
+        println!(
+            "{:<12}{:>12}{:>12}{:>12}{:>12}",
+            format_size(size, config),
+            read_count(&dir, "nr_hugepages"),
+            read_count(&dir, "free_hugepages"),
+            read_count(&dir, "resv_hugepages"),
+            read_count(&dir, "surplus_hugepages")
         );
     }
 }
-/// Get the memory info.
-fn get_mem_info() -> MemInfo {
-    let mut mem_info = MemInfo {
-        mem_total: 0,
-        mem_free: 0,
-        mem_available: 0,
-        buffers: 0,
-        cached: 0,
-        swap_total: 0,
-        swap_free: 0,
-        shared: 0,
+
+/// Print the original, compressed and total memory used by each zram disk.
+fn print_zram(config: &Config) {
+    let mut disks: Vec<(String, Vec<u64>)> = match fs::read_dir(SYS_BLOCK_DIR) {
+        Ok(dir) => dir
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if !name.starts_with("zram") {
+                    return None;
+                }
+                let mm_stat = fs::read_to_string(entry.path().join("mm_stat")).ok()?;
+                let values: Vec<u64> = mm_stat
+                    .split_whitespace()
+                    .filter_map(|v| v.parse::<u64>().ok())
+                    .collect();
+                if values.len() < 3 {
+                    return None;
+                }
+                Some((name, values))
+            })
+            .collect(),
+        Err(_e) => return,
     };
+    if disks.is_empty() {
+        return;
+    }
+    disks.sort();
 
-    let mut meminfo = std::io::BufReader::new(File::open(MEMINFO).unwrap());
+    println!();
+    println!("{:<8}{:>12}{:>12}{:>12}", "Zram", "orig", "compr", "used");
+    for (name, values) in disks {
+        println!(
+            "{:<8}{:>12}{:>12}{:>12}",
+            name,
+            format_size(values[0] / 1024, config),
+            format_size(values[1] / 1024, config),
+            format_size(values[2] / 1024, config)
+        );
+    }
+}
 
-    let mut line = String::new();
+/// Print one `free` style row per NUMA node, and its details with `--detail`.
+fn print_nodes(config: &Config) {
+    let mut nodes: Vec<(u32, Vec<(String, u64)>)> = match fs::read_dir(NODE_DIR) {
+        Ok(dir) => dir
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let id = name.strip_prefix("node")?.parse::<u32>().ok()?;
+                let content = fs::read_to_string(entry.path().join("meminfo")).ok()?;
+                Some((id, parse_meminfo_fields(&content)))
+            })
+            .collect(),
+        Err(_e) => return,
+    };
+    nodes.sort_by_key(|(id, _fields)| *id);
 
-    loop {
-        line.clear();
-        meminfo.read_line(&mut line).unwrap();
-        if line.is_empty() {
-            break;
+    println!();
+    println!("               total        used        free      shared  buff/cache   available");
+    for (id, fields) in nodes.iter() {
+        let field = |key: &str| meminfo_field(fields, key).unwrap_or(0);
+        let total = field("MemTotal");
+        let free = field("MemFree").min(total);
+        let cache = field("FilePages").min(total - free);
+        let available =
+            (free + field("Active(file)") + field("Inactive(file)") + field("SReclaimable"))
+                .min(total);
+        // Used is what is not available, as on the Mem: line.
+        println!(
+            "{:<8}{:>12}{:>12}{:>12}{:>12}{:>12}{:>12}",
+            format!("Node{}:", id),
+            format_size(total, config),
+            format_size(total - available, config),
+            format_size(free, config),
+            format_size(field("Shmem"), config),
+            format_size(cache, config),
+            format_size(available, config)
+        );
+    }
+    if config.detail {
+        for (id, fields) in nodes.iter() {
+            println!();
+            println!("Node{}:", id);
+            print_detail(fields, config);
         }
+    }
+}
+/// Get the memory info.
+fn get_mem_info() -> UResult<MemInfo> {
+    let meminfo = File::open(MEMINFO)
+        .map_err(|e| USimpleError::new(1, format!("cannot open {}: {}", MEMINFO, e)))?;
+    parse_mem_info(BufReader::new(meminfo))
+}
+
+/// Parse `/proc/meminfo`. Unknown fields are ignored, and fields missing on
+/// old kernels get the procps fallbacks, e.g. MemAvailable becomes MemFree.
+fn parse_mem_info(meminfo: impl BufRead) -> UResult<MemInfo> {
+    let mut mem_info = MemInfo::default();
+    let mut has_total = false;
+    let mut has_available = false;
+
+    for (idx, line) in meminfo.lines().enumerate() {
+        let line =
+            line.map_err(|e| USimpleError::new(1, format!("cannot read {}: {}", MEMINFO, e)))?;
         let mut parts = line.split_whitespace();
-        match parts.next() {
-            Some("MemTotal:") => {
-                mem_info.mem_total = parts.next().unwrap().parse().unwrap();
+        let key = match parts.next() {
+            Some(key) => key,
+            None => continue,
+        };
+        let field = match key {
+            "MemTotal:" => {
+                has_total = true;
+                &mut mem_info.mem_total
             }
-            Some("MemFree:") => {
-                mem_info.mem_free = parts.next().unwrap().parse().unwrap();
+            "MemFree:" => &mut mem_info.mem_free,
+            "MemAvailable:" => {
+                has_available = true;
+                &mut mem_info.mem_available
             }
-            Some("MemAvailable:") => {
-                mem_info.mem_available = parts.next().unwrap().parse().unwrap();
+            "Buffers:" => &mut mem_info.buffers,
+            "Cached:" => &mut mem_info.cached,
+            "SReclaimable:" => &mut mem_info.s_reclaimable,
+            "SwapTotal:" => &mut mem_info.swap_total,
+            "SwapFree:" => &mut mem_info.swap_free,
+            "Shmem:" => &mut mem_info.shared,
+            "LowTotal:" => &mut mem_info.low_total,
+            "LowFree:" => &mut mem_info.low_free,
+            "HighTotal:" => &mut mem_info.high_total,
+            "HighFree:" => &mut mem_info.high_free,
+            "CommitLimit:" => &mut mem_info.commit_limit,
+            "Committed_AS:" => &mut mem_info.committed_as,
+            _ => continue,
+        };
+        let value = parts.next().unwrap_or("");
+        *field = value.parse::<u64>().map_err(|_| {
+            USimpleError::new(
+                1,
+                format!(
+                    "invalid value {} for {} in {} line {}",
+                    value.quote(),
+                    key.trim_end_matches(':'),
+                    MEMINFO,
+                    idx + 1
+                ),
+            )
+        })?;
+    }
+
+    if !has_total {
+        return Err(USimpleError::new(
+            1,
+            format!("MemTotal not found in {}", MEMINFO),
+        ));
+    }
+    if !has_available {
+        mem_info.mem_available = mem_info.mem_free;
+    }
+    mem_info.mem_free = mem_info.mem_free.min(mem_info.mem_total);
+    Ok(mem_info)
+}
+
+/// Memory controller files of a cgroup directory.
+enum MemCgroup {
+    V1(PathBuf),
+    V2(PathBuf),
+}
+
+impl MemCgroup {
+    /// Recognise a cgroup directory by its memory controller files.
+    fn from_dir(dir: &Path) -> Option<Self> {
+        if dir.join("memory.max").is_file() {
+            Some(Self::V2(dir.to_path_buf()))
+        } else if dir.join("memory.limit_in_bytes").is_file() {
+            Some(Self::V1(dir.to_path_buf()))
+        } else {
+            None
+        }
+    }
+
+    /// Find the cgroup directory for a cgroup path, trying the unified
+    /// hierarchy first. Inside a container the hierarchy is usually mounted
+    /// at our own cgroup, so with `strip_missing` leading components that do
+    /// not exist are dropped.
+    fn from_cgroup_path(cgroup_path: &str, v1_path: &str, strip_missing: bool) -> Option<Self> {
+        let candidates = [
+            (Path::new(CGROUP_ROOT).to_path_buf(), cgroup_path),
+            (Path::new(CGROUP_ROOT).join("memory"), v1_path),
+        ];
+        for (root, rel_path) in candidates.iter() {
+            let components: Vec<&str> = rel_path.split('/').filter(|c| !c.is_empty()).collect();
+            let max_skip = if strip_missing { components.len() } else { 0 };
+            for skip in 0..=max_skip {
+                let dir = components[skip..]
+                    .iter()
+                    .fold(root.clone(), |dir, c| dir.join(c));
+                if let Some(cgroup) = Self::from_dir(&dir) {
+                    return Some(cgroup);
+                }
             }
-            Some("Buffers:") => {
-                mem_info.buffers = parts.next().unwrap().parse().unwrap();
+        }
+        None
+    }
+
+    /// Locate the memory cgroup of the current process from `/proc/self/cgroup`.
+    fn current() -> Option<Self> {
+        let content = fs::read_to_string(PROC_SELF_CGROUP).ok()?;
+        let mut v2_path = "/";
+        let mut v1_path = "/";
+        for line in content.lines() {
+            let fields: Vec<&str> = line.splitn(3, ':').collect();
+            if fields.len() != 3 {
+                continue;
             }
-            Some("Cached:") => {
-                mem_info.cached = parts.next().unwrap().parse().unwrap();
+            if fields[0] == "0" && fields[1].is_empty() {
+                v2_path = fields[2];
+            } else if fields[1].split(',').any(|c| c == "memory") {
+                v1_path = fields[2];
             }
-            Some("SwapTotal:") => {
-                mem_info.swap_total = parts.next().unwrap().parse().unwrap();
+        }
+        Self::from_cgroup_path(v2_path, v1_path, true)
+    }
+
+    fn read_value(dir: &Path, name: &str) -> Option<u64> {
+        let value = fs::read_to_string(dir.join(name)).ok()?;
+        match value.trim() {
+            "max" => Some(u64::MAX),
+            v => v.parse::<u64>().ok(),
+        }
+    }
+
+    fn read_stat(dir: &Path) -> HashMap<String, u64> {
+        let mut stat = HashMap::new();
+        if let Ok(content) = fs::read_to_string(dir.join("memory.stat")) {
+            for line in content.lines() {
+                let mut parts = line.split_whitespace();
+                if let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+                    if let Ok(value) = value.parse::<u64>() {
+                        stat.insert(key.to_string(), value);
+                    }
+                }
             }
-            Some("SwapFree:") => {
-                mem_info.swap_free = parts.next().unwrap().parse().unwrap();
+        }
+        stat
+    }
+
+    /// Map the cgroup counters onto the `/proc/meminfo` columns, in kB.
+    /// Unlimited memory and swap fall back to the host totals.
+    fn mem_info(&self, host: &MemInfo) -> Option<MemInfo> {
+        let kb = |bytes: u64| bytes / 1024;
+        let stat_value = |stat: &HashMap<String, u64>, key: &str| *stat.get(key).unwrap_or(&0);
+        let (limit, usage, swap_limit, swap_usage, cache, shmem, file_lru, slab);
+        match self {
+            Self::V2(dir) => {
+                let stat = Self::read_stat(dir);
+                limit = Self::read_value(dir, "memory.max")?;
+                usage = Self::read_value(dir, "memory.current")?;
+                swap_limit = Self::read_value(dir, "memory.swap.max").unwrap_or(u64::MAX);
+                swap_usage = Self::read_value(dir, "memory.swap.current").unwrap_or(0);
+                cache = stat_value(&stat, "file");
+                shmem = stat_value(&stat, "shmem");
+                file_lru = stat_value(&stat, "active_file") + stat_value(&stat, "inactive_file");
+                slab = stat_value(&stat, "slab_reclaimable");
             }
-            Some("Shmem:") => {
-                mem_info.shared = parts.next().unwrap().parse().unwrap();
+            Self::V1(dir) => {
+                let stat = Self::read_stat(dir);
+                limit = Self::read_value(dir, "memory.limit_in_bytes")?
+                    .min(*stat.get("hierarchical_memory_limit").unwrap_or(&u64::MAX));
+                usage = Self::read_value(dir, "memory.usage_in_bytes")?;
+                swap_limit = Self::read_value(dir, "memory.memsw.limit_in_bytes")
+                    .map(|memsw| memsw.saturating_sub(limit))
+                    .unwrap_or(u64::MAX);
+                swap_usage = Self::read_value(dir, "memory.memsw.usage_in_bytes")
+                    .map(|memsw| memsw.saturating_sub(usage))
+                    .unwrap_or(0);
+                cache = stat_value(&stat, "total_cache");
+                shmem = stat_value(&stat, "total_shmem");
+                file_lru = stat_value(&stat, "total_active_file")
+                    + stat_value(&stat, "total_inactive_file");
+                slab = 0;
             }
-            _ => {}
         }
+
+        let mem_total = kb(limit).min(host.mem_total);
+        let mem_used = kb(usage).min(mem_total);
+        let mem_free = mem_total - mem_used;
+        let cached = kb(cache).min(mem_used);
+        let swap_total = kb(swap_limit).min(host.swap_total);
+        let swap_used = kb(swap_usage).min(swap_total);
+        Some(MemInfo {
+            mem_total,
+            mem_free,
+            shared: kb(shmem),
+            mem_available: (mem_free + kb(file_lru) + kb(slab)).min(mem_total),
+            buffers: 0,
+            cached,
+            s_reclaimable: kb(slab).min(mem_used - cached),
+            swap_total,
+            swap_free: swap_total - swap_used,
+            ..MemInfo::default()
+        })
     }
+}
 
-    mem_info
+/// Get the memory info of a cgroup, given as a cgroup directory, a path in
+/// the cgroup hierarchy, or empty for the cgroup of the current process.
+fn get_cgroup_mem_info(cgroup: &str, host: &MemInfo) -> UResult<MemInfo> {
+    let mem_cgroup = if cgroup.is_empty() {
+        MemCgroup::current()
+    } else {
+        MemCgroup::from_dir(Path::new(cgroup))
+            .or_else(|| MemCgroup::from_cgroup_path(cgroup, cgroup, false))
+    };
+    let not_found = || {
+        USimpleError::new(
+            1,
+            format!(
+                "cannot find memory cgroup {}",
+                if cgroup.is_empty() {
+                    "of this process"
+                } else {
+                    cgroup
+                }
+            ),
+        )
+    };
+    mem_cgroup
+        .ok_or_else(not_found)?
+        .mem_info(host)
+        .ok_or_else(not_found)
 }
 
-fn raw_print(meminfo: &MemInfo, unit: Unit, lohi: bool, t: bool, w: bool) {
-    let total = convert_unit(meminfo.mem_total, &unit);
-    let used = convert_unit(meminfo.mem_total - meminfo.mem_free, &unit);
-    let free = convert_unit(meminfo.mem_free, &unit);
-    let shared = convert_unit(meminfo.shared, &unit);
-    let buff = convert_unit(meminfo.buffers, &unit);
-    let cache = convert_unit(meminfo.cached, &unit);
-    let available = convert_unit(meminfo.mem_available, &unit);
-    let swap_total = convert_unit(meminfo.swap_total, &unit);
-    let swap_used = convert_unit(meminfo.swap_total - meminfo.swap_free, &unit);
-    let swap_free = convert_unit(meminfo.swap_free, &unit);
+fn raw_print(meminfo: &MemInfo, unit: Unit, config: &Config) {
+    let conv = |value: u64| convert_unit(value, &unit);
+    let total = conv(meminfo.mem_total);
+    let used = conv(meminfo.used());
+    let free = conv(meminfo.mem_free);
+    let shared = conv(meminfo.shared);
+    let buff = conv(meminfo.buffers);
+    let cache = conv(meminfo.cache());
+    let available = conv(meminfo.mem_available);
+    let swap_total = conv(meminfo.swap_total);
+    let swap_used = conv(meminfo.swap_used());
+    let swap_free = conv(meminfo.swap_free);
 
-    if w {
+    if config.wide {
         println!("               total        used        free      shared     buffers       cache   available");
         println!(
             "Mem:{:>16}{:>12}{:>12}{:>12}{:>12}{:>12}{:>12}",
@@ -431,19 +1105,30 @@ fn raw_print(meminfo: &MemInfo, unit: Unit, lohi: bool, t: bool, w: bool) {
         println!(
             "Mem:{:>16}{:>12}{:>12}{:>12}{:>12}{:>12}",
             total,
-            used - buff - cache,
+            used,
             free,
             shared,
             buff + cache,
             available
         );
     }
-    if lohi {
-        println!("Low:{:>16}{:>12}{:>12}", total, total - free, free);
-        println!("High:{:>15}{:>12}{:>12}", 0, 0, 0);
+    if config.lohi {
+        let (low_total, low_free) = meminfo.low();
+        println!(
+            "Low:{:>16}{:>12}{:>12}",
+            conv(low_total),
+            conv(low_total.saturating_sub(low_free)),
+            conv(low_free)
+        );
+        println!(
+            "High:{:>15}{:>12}{:>12}",
+            conv(meminfo.high_total),
+            conv(meminfo.high_total.saturating_sub(meminfo.high_free)),
+            conv(meminfo.high_free)
+        );
     }
     println!("Swap:{:>15}{:>12}{:>12}", swap_total, swap_used, swap_free);
-    if t {
+    if config.total {
         println!(
             "Total:{:>14}{:>12}{:>12}",
             total + swap_total,
@@ -451,6 +1136,25 @@ fn raw_print(meminfo: &MemInfo, unit: Unit, lohi: bool, t: bool, w: bool) {
             free + swap_free
         );
     }
+    if config.committed {
+        println!(
+            "Comm:{:>15}{:>12}{:>12}",
+            conv(meminfo.commit_limit),
+            conv(meminfo.committed_as),
+            conv(meminfo.commit_limit.saturating_sub(meminfo.committed_as))
+        );
+    }
+}
+
+/// Print the procps `--line` summary.
+fn line_print(meminfo: &MemInfo, config: &Config) {
+    println!(
+        "SwapUse {:>11} CachUse {:>11} MemUse {:>11} MemFree {:>11}",
+        format_size(meminfo.swap_used(), config),
+        format_size(meminfo.buffers + meminfo.cache(), config),
+        format_size(meminfo.used(), config),
+        format_size(meminfo.mem_free, config)
+    );
 }
 
 fn convert_unit(value: u64, unit: &Unit) -> u64 {
@@ -545,19 +1249,19 @@ fn get_suit_unit(num: u64) -> HumanUnit {
     }
 }
 
-fn hum_print(meminfo: &MemInfo, lohi: bool, t: bool, w: bool) {
+fn hum_print(meminfo: &MemInfo, config: &Config) {
     let total = get_suit_unit(meminfo.mem_total);
-    let used = get_suit_unit(meminfo.mem_total - meminfo.mem_free);
+    let used = get_suit_unit(meminfo.used());
     let free = get_suit_unit(meminfo.mem_free);
     let shared = get_suit_unit(meminfo.shared);
-    let buff_cache = get_suit_unit(meminfo.buffers + meminfo.cached);
+    let buff_cache = get_suit_unit(meminfo.buffers + meminfo.cache());
     let buff = get_suit_unit(meminfo.buffers);
-    let cache = get_suit_unit(meminfo.cached);
+    let cache = get_suit_unit(meminfo.cache());
     let available = get_suit_unit(meminfo.mem_available);
     let swap_total = get_suit_unit(meminfo.swap_total);
-    let swap_used = get_suit_unit(meminfo.swap_total - meminfo.swap_free);
+    let swap_used = get_suit_unit(meminfo.swap_used());
     let swap_free = get_suit_unit(meminfo.swap_free);
-    if w {
+    if config.wide {
         println!("               total        used        free      shared     buffers       cache   available");
         println!(
             "Mem:       {:>8.1}{} {:>8.1}{}  {:>8.1}{}   {:>8.1}{}  {:>8.1}{} {:>8.1}{} {:>8.1}{}",
@@ -584,10 +1288,8 @@ fn hum_print(meminfo: &MemInfo, lohi: bool, t: bool, w: bool) {
             "Mem:       {:>8.1}{} {:>8.1}{}  {:>8.1}{}   {:>8.1}{}  {:>8.1}{} {:>8.1}{}",
             total.num,
             total.unit,
-            get_suit_unit(meminfo.mem_total - meminfo.mem_free - meminfo.buffers - meminfo.cached)
-                .num,
-            get_suit_unit(meminfo.mem_total - meminfo.mem_free - meminfo.buffers - meminfo.cached)
-                .unit,
+            used.num,
+            used.unit,
             free.num,
             free.unit,
             shared.num,
@@ -598,14 +1300,31 @@ fn hum_print(meminfo: &MemInfo, lohi: bool, t: bool, w: bool) {
             available.unit,
         );
     }
-    if lohi {
+    if config.lohi {
+        let (low_total, low_free) = meminfo.low();
+        let low_total_unit = get_suit_unit(low_total);
+        let low_used = get_suit_unit(low_total.saturating_sub(low_free));
+        let low_free = get_suit_unit(low_free);
+        let high_total = get_suit_unit(meminfo.high_total);
+        let high_used = get_suit_unit(meminfo.high_total.saturating_sub(meminfo.high_free));
+        let high_free = get_suit_unit(meminfo.high_free);
         println!(
             "Low:       {:>8.1}{} {:>8.1}{}   {:>8.1}{}",
-            total.num, total.unit, used.num, used.unit, free.num, free.unit
+            low_total_unit.num,
+            low_total_unit.unit,
+            low_used.num,
+            low_used.unit,
+            low_free.num,
+            low_free.unit
         );
         println!(
             "High:      {:>8.1}{} {:>8.1}{}   {:>8.1}{}",
-            0, "B", 0, "B", 0, "B"
+            high_total.num,
+            high_total.unit,
+            high_used.num,
+            high_used.unit,
+            high_free.num,
+            high_free.unit
         );
     }
     println!(
@@ -617,21 +1336,22 @@ fn hum_print(meminfo: &MemInfo, lohi: bool, t: bool, w: bool) {
         swap_free.num,
         swap_free.unit
     );
-    if t {
+    if config.total {
+        let all_total = get_suit_unit(meminfo.mem_total + meminfo.swap_total);
+        let all_used = get_suit_unit(meminfo.used() + meminfo.swap_used());
+        let all_free = get_suit_unit(meminfo.mem_free + meminfo.swap_free);
         println!(
             "Total:     {:>8.1}{} {:>8.1}{} {:>8.1}{}",
-            get_suit_unit(meminfo.mem_total + meminfo.swap_total).num,
-            get_suit_unit(meminfo.mem_total + meminfo.swap_total).unit,
-            get_suit_unit(
-                meminfo.mem_total + meminfo.swap_total - meminfo.mem_free - meminfo.swap_free
-            )
-            .num,
-            get_suit_unit(
-                meminfo.mem_total + meminfo.swap_total - meminfo.mem_free - meminfo.swap_free
-            )
-            .unit,
-            get_suit_unit(meminfo.mem_free + meminfo.swap_free).num,
-            get_suit_unit(meminfo.mem_free + meminfo.swap_free).unit
+            all_total.num, all_total.unit, all_used.num, all_used.unit, all_free.num, all_free.unit
+        );
+    }
+    if config.committed {
+        let limit = get_suit_unit(meminfo.commit_limit);
+        let committed = get_suit_unit(meminfo.committed_as);
+        let left = get_suit_unit(meminfo.commit_limit.saturating_sub(meminfo.committed_as));
+        println!(
+            "Comm:      {:>8.1}{} {:>8.1}{}   {:>8.1}{}",
+            limit.num, limit.unit, committed.num, committed.unit, left.num, left.unit
         );
     }
 }
@@ -0,0 +1,30 @@
+//! This file is part of the easybox package.
+//
+// (c) openeuler developers
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use clap::Command;
+use getfacl_common::Config;
+use uucore::{error::UResult, help_section, help_usage};
+
+/// getfacl common functions
+pub mod getfacl_common;
+
+const ABOUT: &str = help_section!("about", "getfacl.md");
+const USAGE: &str = help_usage!("getfacl.md");
+
+#[uucore::main]
+/// This the main of getfacl
+///
+pub fn oemain(args: impl uucore::Args) -> UResult<()> {
+    let config: Config = getfacl_common::parse_getfacl_cmd_args(args, ABOUT, USAGE)?;
+    getfacl_common::handle_input(&config)
+}
+
+/// This the oe_app of getfacl
+///
+pub fn oe_app<'a>() -> Command<'a> {
+    getfacl_common::getfacl_app(ABOUT, USAGE)
+}
@@ -0,0 +1,355 @@
+//! This file is part of the easybox package.
+//
+// (c) openeuler developers
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::ffi::OsString;
+use std::fs;
+use std::io::{stdout, BufWriter, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+use clap::{crate_version, Arg, ArgMatches, Command};
+use libc::EXIT_FAILURE;
+use oe_attr::lib_acl::{
+    acl_read, group_name, perm_to_text, user_name, Acl, AclTag, ACL_ACCESS_XATTR, ACL_DEFAULT_XATTR,
+};
+use oe_attr::lib_attr::xattr_strerror;
+use oe_attr::lib_dump::{header_name, strip_os_error, FILE_HEADER};
+use uucore::error::{UResult, USimpleError};
+use uucore::{format_usage, show};
+use walkdir::WalkDir;
+
+/// Column that `#effective:` comments are aligned to
+const EFFECTIVE_COLUMN: usize = 32;
+
+/// When to print the rights left by the mask
+#[derive(PartialEq, Eq)]
+pub enum Effective {
+    /// Only for entries the mask restricts
+    Some,
+    /// For every entry the mask applies to
+    All,
+    /// Never
+    None,
+}
+
+/// Config
+pub struct Config {
+    /// Print the access ACL
+    pub access: bool,
+    /// Print the default ACL
+    pub default: bool,
+    /// Leave out the `#` header lines
+    pub omit_header: bool,
+    /// When to print effective rights
+    pub effective: Effective,
+    /// Skip files that only have the base ACL entries
+    pub skip_base: bool,
+    /// Recurse into directories
+    pub recursive: bool,
+    /// Follow all symbolic links while recursing
+    pub logical: bool,
+    /// Print user and group ids instead of names
+    pub numeric: bool,
+    /// Keep leading '/' in file names
+    pub absolute_names: bool,
+    /// Files to examine
+    pub paths: Vec<OsString>,
+}
+
+/// Command Options
+pub mod options {
+    /// Option -a
+    pub const ACCESS: &str = "access";
+    /// Option -d
+    pub const DEFAULT: &str = "default";
+    /// Option -c
+    pub const OMIT_HEADER: &str = "omit-header";
+    /// Option -e
+    pub const ALL_EFFECTIVE: &str = "all-effective";
+    /// Option -E
+    pub const NO_EFFECTIVE: &str = "no-effective";
+    /// Option -s
+    pub const SKIP_BASE: &str = "skip-base";
+    /// Option -R
+    pub const RECURSIVE: &str = "recursive";
+    /// Option -L
+    pub const LOGICAL: &str = "logical";
+    /// Option -P
+    pub const PHYSICAL: &str = "physical";
+    /// Option -n
+    pub const NUMERIC: &str = "numeric";
+    /// Option -p
+    pub const ABSOLUTE_NAMES: &str = "absolute-names";
+    /// Operand pathname
+    pub const PATHNAME: &str = "pathname";
+}
+
+impl Config {
+    /// Generate getfacl general Config
+    pub fn from(args_matches: &ArgMatches) -> UResult<Self> {
+        let access = args_matches.contains_id(options::ACCESS);
+        let default = args_matches.contains_id(options::DEFAULT);
+        let effective = if args_matches.contains_id(options::NO_EFFECTIVE) {
+            Effective::None
+        } else if args_matches.contains_id(options::ALL_EFFECTIVE) {
+            Effective::All
+        } else {
+            Effective::Some
+        };
+
+        Ok(Self {
+            // Neither of -a and -d means both.
+            access: access || !default,
+            default: default || !access,
+            omit_header: args_matches.contains_id(options::OMIT_HEADER),
+            effective,
+            skip_base: args_matches.contains_id(options::SKIP_BASE),
+            recursive: args_matches.contains_id(options::RECURSIVE),
+            logical: last_index(args_matches, options::LOGICAL)
+                > last_index(args_matches, options::PHYSICAL),
+            numeric: args_matches.contains_id(options::NUMERIC),
+            absolute_names: args_matches.contains_id(options::ABSOLUTE_NAMES),
+            paths: args_matches
+                .get_many::<OsString>(options::PATHNAME)
+                .map(|v| v.cloned().collect())
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Index of the last occurrence of a flag, so that the later of -L and -P wins
+fn last_index(args_matches: &ArgMatches, id: &str) -> Option<usize> {
+    args_matches.indices_of(id).and_then(|i| i.max())
+}
+
+/// Generate getfacl general Config
+pub fn parse_getfacl_cmd_args(
+    args: impl uucore::Args,
+    about: &str,
+    usage: &str,
+) -> UResult<Config> {
+    let command = getfacl_app(about, usage);
+    let arg_list = args.collect_lossy();
+    Config::from(&command.try_get_matches_from(arg_list)?)
+}
+
+/// Command arguments setting
+pub fn getfacl_app<'a>(about: &'a str, usage: &'a str) -> Command<'a> {
+    Command::new(uucore::util_name())
+        .version(crate_version!())
+        .about(about)
+        .override_usage(format_usage(usage))
+        .arg(
+            Arg::new(options::ACCESS)
+                .short('a')
+                .long(options::ACCESS)
+                .help("display the file access control list only"),
+        )
+        .arg(
+            Arg::new(options::DEFAULT)
+                .short('d')
+                .long(options::DEFAULT)
+                .help("display the default access control list only"),
+        )
+        .arg(
+            Arg::new(options::OMIT_HEADER)
+                .short('c')
+                .long(options::OMIT_HEADER)
+                .help("do not display the comment header"),
+        )
+        .arg(
+            Arg::new(options::ALL_EFFECTIVE)
+                .short('e')
+                .long(options::ALL_EFFECTIVE)
+                .help("print all effective rights"),
+        )
+        .arg(
+            Arg::new(options::NO_EFFECTIVE)
+                .short('E')
+                .long(options::NO_EFFECTIVE)
+                .help("print no effective rights"),
+        )
+        .arg(
+            Arg::new(options::SKIP_BASE)
+                .short('s')
+                .long(options::SKIP_BASE)
+                .help("skip files that only have the base entries"),
+        )
+        .arg(
+            Arg::new(options::RECURSIVE)
+                .short('R')
+                .long(options::RECURSIVE)
+                .help("recurse into subdirectories"),
+        )
+        .arg(
+            Arg::new(options::LOGICAL)
+                .short('L')
+                .long(options::LOGICAL)
+                .multiple_occurrences(true)
+                .help("logical walk, follow symbolic links"),
+        )
+        .arg(
+            Arg::new(options::PHYSICAL)
+                .short('P')
+                .long(options::PHYSICAL)
+                .multiple_occurrences(true)
+                .help("physical walk, do not follow symbolic links"),
+        )
+        .arg(
+            Arg::new(options::NUMERIC)
+                .short('n')
+                .long(options::NUMERIC)
+                .help("print numeric user/group identifiers"),
+        )
+        .arg(
+            Arg::new(options::ABSOLUTE_NAMES)
+                .short('p')
+                .long(options::ABSOLUTE_NAMES)
+                .help("don't strip leading '/' in pathnames"),
+        )
+        .arg(
+            Arg::new(options::PATHNAME)
+                .index(1)
+                .multiple_values(true)
+                .required(true)
+                .allow_invalid_utf8(true)
+                .value_parser(clap::value_parser!(OsString)),
+        )
+}
+
+/// Text form of the setuid, setgid and sticky bits, if any is set
+fn flags_text(mode: u32) -> Option<String> {
+    if mode & 0o7000 == 0 {
+        return None;
+    }
+    let flag = |bit: u32, c: char| if mode & bit != 0 { c } else { '-' };
+    Some(format!(
+        "{}{}{}",
+        flag(0o4000, 's'),
+        flag(0o2000, 's'),
+        flag(0o1000, 't')
+    ))
+}
+
+/// Based on __acl_to_any_text(): one line per entry, with the rights left by
+/// the mask in a comment aligned with tabs
+fn write_acl<W: Write>(config: &Config, acl: &Acl, prefix: &str, out: &mut W) -> UResult<()> {
+    for entry in &acl.entries {
+        let mut line = format!(
+            "{}{}{}",
+            prefix,
+            entry.tag.to_text(config.numeric),
+            perm_to_text(entry.perm)
+        );
+        let effective = acl.effective(entry);
+        let show = acl.get(AclTag::Mask).is_some()
+            && entry.tag.is_masked()
+            && match config.effective {
+                Effective::All => true,
+                Effective::Some => effective != entry.perm,
+                Effective::None => false,
+            };
+        if show {
+            let mut column = line.len();
+            loop {
+                line.push('\t');
+                column = (column / 8 + 1) * 8;
+                if column >= EFFECTIVE_COLUMN {
+                    break;
+                }
+            }
+            line.push_str("#effective:");
+            line.push_str(&perm_to_text(effective));
+        }
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// Print the ACLs of one file
+fn print_file<W: Write>(config: &Config, path: &Path, out: &mut W) -> UResult<()> {
+    let error =
+        |msg: String| USimpleError::new(EXIT_FAILURE, format!("{}: {}", path.display(), msg));
+    let meta = fs::metadata(path).map_err(|e| error(strip_os_error(&e.to_string()).to_string()))?;
+    let access = acl_read(path, ACL_ACCESS_XATTR, true)
+        .map_err(|e| error(xattr_strerror(e)))?
+        .unwrap_or_else(|| Acl::from_mode(meta.mode()));
+    let default = if meta.is_dir() {
+        acl_read(path, ACL_DEFAULT_XATTR, true)
+            .map_err(|e| error(xattr_strerror(e)))?
+            .unwrap_or_default()
+    } else {
+        Acl::default()
+    };
+    if config.skip_base && access.is_minimal() && default.is_empty() {
+        return Ok(());
+    }
+
+    if !config.omit_header {
+        out.write_all(FILE_HEADER.as_bytes())?;
+        out.write_all(&header_name(path, config.absolute_names))?;
+        writeln!(out)?;
+        writeln!(out, "# owner: {}", user_name(meta.uid(), config.numeric))?;
+        writeln!(out, "# group: {}", group_name(meta.gid(), config.numeric))?;
+        if let Some(flags) = flags_text(meta.mode()) {
+            writeln!(out, "# flags: {}", flags)?;
+        }
+    }
+    if config.access {
+        write_acl(config, &access, "", out)?;
+    }
+    if config.default {
+        write_acl(config, &default, "default:", out)?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Print the ACLs of every path, walking directories with -R
+pub fn handle_input(config: &Config) -> UResult<()> {
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    for path in &config.paths {
+        let path = Path::new(path);
+        if !config.recursive {
+            if let Err(e) = print_file(config, path, &mut out) {
+                show!(e);
+            }
+            continue;
+        }
+        let walker = WalkDir::new(path)
+            .follow_links(config.logical)
+            .sort_by_file_name();
+        for entry in walker {
+            match entry {
+                // A physical walk leaves out symbolic links below the top.
+                Ok(entry) if entry.depth() > 0 && entry.path_is_symlink() && !config.logical => {}
+                Ok(entry) => {
+                    if let Err(e) = print_file(config, entry.path(), &mut out) {
+                        show!(e);
+                    }
+                }
+                Err(err) => {
+                    let msg = err
+                        .io_error()
+                        .map(|e| e.to_string())
+                        .unwrap_or_else(|| err.to_string());
+                    show!(USimpleError::new(
+                        EXIT_FAILURE,
+                        format!(
+                            "{}: {}",
+                            err.path().unwrap_or(path).display(),
+                            strip_os_error(&msg)
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,30 @@
+//! This file is part of the easybox package.
+//
+// (c) openeuler developers
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use clap::Command;
+use getfattr_common::Config;
+use uucore::{error::UResult, help_section, help_usage};
+
+/// getfattr common functions
+pub mod getfattr_common;
+
+const ABOUT: &str = help_section!("about", "getfattr.md");
+const USAGE: &str = help_usage!("getfattr.md");
+
+#[uucore::main]
+/// This the main of getfattr
+///
+pub fn oemain(args: impl uucore::Args) -> UResult<()> {
+    let config: Config = getfattr_common::parse_getfattr_cmd_args(args, ABOUT, USAGE)?;
+    getfattr_common::handle_input(&config)
+}
+
+/// This the oe_app of getfattr
+///
+pub fn oe_app<'a>() -> Command<'a> {
+    getfattr_common::getfattr_app(ABOUT, USAGE)
+}
@@ -0,0 +1,330 @@
+//! This file is part of the easybox package.
+//
+// (c) openeuler developers
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::ffi::{OsStr, OsString};
+use std::io::{stdout, BufWriter, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+use clap::{crate_version, Arg, ArgMatches, Command};
+use libc::EXIT_FAILURE;
+use oe_attr::lib_attr::{xattr_names, xattr_strerror, xattr_value};
+use oe_attr::lib_dump::{
+    encode_value, header_name, quote, strip_os_error, ValueEncoding, FILE_HEADER,
+};
+use regex::bytes::Regex;
+use uucore::display::Quotable;
+use uucore::error::{UResult, USimpleError, UUsageError};
+use uucore::{format_usage, show};
+use walkdir::WalkDir;
+
+/// Default pattern of attribute names to dump, the user namespace
+const DEFAULT_MATCH: &str = "^user\\.";
+
+/// Config
+pub struct Config {
+    /// Only the attribute with this full name
+    pub name: Option<OsString>,
+    /// Print values as well as names
+    pub dump: bool,
+    /// Value encoding, chosen per value if unset
+    pub encoding: Option<ValueEncoding>,
+    /// Attribute names to include
+    pub pattern: Regex,
+    /// Follow symbolic links to read their targets' attributes
+    pub dereference: bool,
+    /// Keep leading '/' in file names
+    pub absolute_names: bool,
+    /// Print only the raw values
+    pub only_values: bool,
+    /// Recurse into directories
+    pub recursive: bool,
+    /// Follow all symbolic links while recursing
+    pub logical: bool,
+    /// Files to examine
+    pub paths: Vec<OsString>,
+}
+
+/// Command Options
+pub mod options {
+    /// Option -n
+    pub const NAME: &str = "name";
+    /// Option -d
+    pub const DUMP: &str = "dump";
+    /// Option -e
+    pub const ENCODING: &str = "encoding";
+    /// Option -h
+    pub const NO_DEREFERENCE: &str = "no-dereference";
+    /// Option -m
+    pub const MATCH: &str = "match";
+    /// Option --absolute-names
+    pub const ABSOLUTE_NAMES: &str = "absolute-names";
+    /// Option --only-values
+    pub const ONLY_VALUES: &str = "only-values";
+    /// Option -R
+    pub const RECURSIVE: &str = "recursive";
+    /// Option -L
+    pub const LOGICAL: &str = "logical";
+    /// Option -P
+    pub const PHYSICAL: &str = "physical";
+    /// Operand pathname
+    pub const PATHNAME: &str = "pathname";
+}
+
+impl Config {
+    /// Generate getfattr general Config
+    pub fn from(args_matches: &ArgMatches) -> UResult<Self> {
+        let encoding = match args_matches.get_one::<String>(options::ENCODING) {
+            Some(name) => Some(ValueEncoding::from_name(name).ok_or_else(|| {
+                UUsageError::new(
+                    EXIT_FAILURE,
+                    format!("unrecognized encoding {}", name.quote()),
+                )
+            })?),
+            None => None,
+        };
+        // "-" selects every attribute.
+        let pattern = match args_matches.get_one::<String>(options::MATCH) {
+            Some(p) if p == "-" => "",
+            Some(p) => p.as_str(),
+            None => DEFAULT_MATCH,
+        };
+        let pattern = Regex::new(pattern).map_err(|e| {
+            USimpleError::new(
+                EXIT_FAILURE,
+                format!("invalid pattern {}: {}", pattern.quote(), e),
+            )
+        })?;
+
+        Ok(Self {
+            name: args_matches.get_one::<OsString>(options::NAME).cloned(),
+            dump: args_matches.contains_id(options::DUMP),
+            encoding,
+            pattern,
+            dereference: !args_matches.contains_id(options::NO_DEREFERENCE),
+            absolute_names: args_matches.contains_id(options::ABSOLUTE_NAMES),
+            only_values: args_matches.contains_id(options::ONLY_VALUES),
+            recursive: args_matches.contains_id(options::RECURSIVE),
+            logical: last_index(args_matches, options::LOGICAL)
+                > last_index(args_matches, options::PHYSICAL),
+            paths: args_matches
+                .get_many::<OsString>(options::PATHNAME)
+                .map(|v| v.cloned().collect())
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Index of the last occurrence of a flag, so that the later of -L and -P wins
+fn last_index(args_matches: &ArgMatches, id: &str) -> Option<usize> {
+    args_matches.indices_of(id).and_then(|i| i.max())
+}
+
+/// Generate getfattr general Config
+pub fn parse_getfattr_cmd_args(
+    args: impl uucore::Args,
+    about: &str,
+    usage: &str,
+) -> UResult<Config> {
+    let command = getfattr_app(about, usage);
+    let arg_list = args.collect_lossy();
+    Config::from(&command.try_get_matches_from(arg_list)?)
+}
+
+/// Command arguments setting
+pub fn getfattr_app<'a>(about: &'a str, usage: &'a str) -> Command<'a> {
+    Command::new(uucore::util_name())
+        .version(crate_version!())
+        .about(about)
+        .override_usage(format_usage(usage))
+        .arg(
+            Arg::new(options::NAME)
+                .short('n')
+                .long(options::NAME)
+                .takes_value(true)
+                .value_name("name")
+                .allow_invalid_utf8(true)
+                .value_parser(clap::value_parser!(OsString))
+                .help("dump the value of the named extended attribute"),
+        )
+        .arg(
+            Arg::new(options::DUMP)
+                .short('d')
+                .long(options::DUMP)
+                .help("dump the values of all matching extended attributes"),
+        )
+        .arg(
+            Arg::new(options::ENCODING)
+                .short('e')
+                .long(options::ENCODING)
+                .takes_value(true)
+                .value_name("en")
+                .help("encode values (as 'text', 'hex' or 'base64')"),
+        )
+        .arg(
+            Arg::new(options::MATCH)
+                .short('m')
+                .long(options::MATCH)
+                .takes_value(true)
+                .value_name("pattern")
+                .allow_hyphen_values(true)
+                .help("only get attributes with names matching pattern (default \"^user\\\\.\", \"-\" for all)"),
+        )
+        .arg(
+            Arg::new(options::NO_DEREFERENCE)
+                .short('h')
+                .long(options::NO_DEREFERENCE)
+                .help("do not dereference symbolic links"),
+        )
+        .arg(
+            Arg::new(options::ABSOLUTE_NAMES)
+                .long(options::ABSOLUTE_NAMES)
+                .help("don't strip leading '/' in pathnames"),
+        )
+        .arg(
+            Arg::new(options::ONLY_VALUES)
+                .long(options::ONLY_VALUES)
+                .help("print the bare values only"),
+        )
+        .arg(
+            Arg::new(options::RECURSIVE)
+                .short('R')
+                .long(options::RECURSIVE)
+                .help("recurse into subdirectories"),
+        )
+        .arg(
+            Arg::new(options::LOGICAL)
+                .short('L')
+                .long(options::LOGICAL)
+                .multiple_occurrences(true)
+                .help("logical walk, follow symbolic links"),
+        )
+        .arg(
+            Arg::new(options::PHYSICAL)
+                .short('P')
+                .long(options::PHYSICAL)
+                .multiple_occurrences(true)
+                .help("physical walk, do not follow symbolic links"),
+        )
+        .arg(
+            Arg::new("help")
+                .long("help")
+                .help("display this help"),
+        )
+        .arg(
+            Arg::new(options::PATHNAME)
+                .index(1)
+                .multiple_values(true)
+                .required(true)
+                .allow_invalid_utf8(true)
+                .value_parser(clap::value_parser!(OsString)),
+        )
+}
+
+/// Print the matching attributes of one file
+fn print_file<W: Write>(config: &Config, path: &Path, out: &mut W) -> UResult<()> {
+    let names: Vec<OsString> = match &config.name {
+        Some(name) => vec![name.clone()],
+        None => {
+            let mut names = xattr_names(path, config.dereference).map_err(|err| {
+                USimpleError::new(
+                    EXIT_FAILURE,
+                    format!("{}: {}", path.display(), xattr_strerror(err)),
+                )
+            })?;
+            names.retain(|name| config.pattern.is_match(name.as_bytes()));
+            names.sort();
+            names
+        }
+    };
+
+    let mut lines: Vec<u8> = Vec::new();
+    for name in &names {
+        let value = if config.dump || config.only_values || config.name.is_some() {
+            match xattr_value(path, name, config.dereference) {
+                Ok(value) => Some(value),
+                Err(err) => {
+                    show!(USimpleError::new(
+                        EXIT_FAILURE,
+                        format!(
+                            "{}: {}: {}",
+                            path.display(),
+                            OsStr::new(name).to_string_lossy(),
+                            xattr_strerror(err)
+                        ),
+                    ));
+                    continue;
+                }
+            }
+        } else {
+            None
+        };
+        if config.only_values {
+            out.write_all(&value.unwrap_or_default())?;
+            continue;
+        }
+        lines.extend(quote(name.as_bytes(), b"="));
+        match value {
+            Some(value) if !value.is_empty() => {
+                lines.push(b'=');
+                lines.extend(encode_value(&value, config.encoding));
+            }
+            _ => {}
+        }
+        lines.push(b'\n');
+    }
+
+    if !lines.is_empty() {
+        out.write_all(FILE_HEADER.as_bytes())?;
+        out.write_all(&header_name(path, config.absolute_names))?;
+        out.write_all(b"\n")?;
+        out.write_all(&lines)?;
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Print the attributes of every path, walking directories with -R
+pub fn handle_input(config: &Config) -> UResult<()> {
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    for path in &config.paths {
+        let path = Path::new(path);
+        if !config.recursive {
+            if let Err(e) = print_file(config, path, &mut out) {
+                show!(e);
+            }
+            continue;
+        }
+        let walker = WalkDir::new(path)
+            .follow_links(config.logical)
+            .follow_root_links(config.dereference || config.logical)
+            .sort_by_file_name();
+        for entry in walker {
+            match entry {
+                Ok(entry) => {
+                    if let Err(e) = print_file(config, entry.path(), &mut out) {
+                        show!(e);
+                    }
+                }
+                Err(err) => {
+                    let errno = err
+                        .io_error()
+                        .map(|e| e.to_string())
+                        .unwrap_or_else(|| err.to_string());
+                    let at = err.path().unwrap_or(path);
+                    show!(USimpleError::new(
+                        EXIT_FAILURE,
+                        format!("{}: {}", at.display(), strip_os_error(&errno)),
+                    ));
+                }
+            }
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
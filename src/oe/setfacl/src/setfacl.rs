@@ -0,0 +1,30 @@
+//! This file is part of the easybox package.
+//
+// (c) openeuler developers
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use clap::Command;
+use setfacl_common::Config;
+use uucore::{error::UResult, help_section, help_usage};
+
+/// setfacl common functions
+pub mod setfacl_common;
+
+const ABOUT: &str = help_section!("about", "setfacl.md");
+const USAGE: &str = help_usage!("setfacl.md");
+
+#[uucore::main]
+/// This the main of setfacl
+///
+pub fn oemain(args: impl uucore::Args) -> UResult<()> {
+    let config: Config = setfacl_common::parse_setfacl_cmd_args(args, ABOUT, USAGE)?;
+    setfacl_common::handle_input(&config)
+}
+
+/// This the oe_app of setfacl
+///
+pub fn oe_app<'a>() -> Command<'a> {
+    setfacl_common::setfacl_app(ABOUT, USAGE)
+}
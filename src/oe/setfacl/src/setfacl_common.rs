@@ -0,0 +1,595 @@
+//! This file is part of the easybox package.
+//
+// (c) openeuler developers
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::ffi::{CString, OsString};
+use std::fs::{self, File, Permissions};
+use std::io::{stdin, BufRead, BufReader};
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use clap::{crate_version, Arg, ArgMatches, Command};
+use libc::EXIT_FAILURE;
+use oe_attr::lib_acl::{
+    acl_read, acl_write, parse_acl_spec, Acl, AclSpecEntry, AclTag, ACL_ACCESS_XATTR,
+    ACL_DEFAULT_XATTR, ACL_EXECUTE, ACL_EXECUTE_IF,
+};
+use oe_attr::lib_attr::xattr_strerror;
+use oe_attr::lib_dump::{strip_os_error, unquote, FILE_HEADER};
+use uucore::display::Quotable;
+use uucore::entries::{grp2gid, usr2uid};
+use uucore::error::{FromIo, UResult, USimpleError, UUsageError};
+use uucore::{format_usage, show};
+use walkdir::WalkDir;
+
+const BAD_USAGE_MESSAGE_MISSING: &str = "One of -m, -x, -b, -k or --restore is required";
+const BAD_USAGE_MESSAGE_RESTORE: &str = "--restore cannot be combined with -m, -x, -b, -k or -d";
+
+/// Header lines of getfacl output read by --restore
+const OWNER_HEADER: &str = "# owner: ";
+const GROUP_HEADER: &str = "# group: ";
+const FLAGS_HEADER: &str = "# flags: ";
+
+/// Config
+pub struct Config {
+    /// Entries to add or change
+    pub modify: Vec<AclSpecEntry>,
+    /// Entries to remove
+    pub remove: Vec<AclSpecEntry>,
+    /// Remove all extended entries and the default ACL
+    pub remove_all: bool,
+    /// Remove the default ACL
+    pub remove_default: bool,
+    /// Apply -m and -x to the default ACL
+    pub default: bool,
+    /// Do not recalculate the mask
+    pub no_mask: bool,
+    /// Recalculate the mask even if it was set explicitly
+    pub mask: bool,
+    /// Restore ACLs from getfacl output in this file, or stdin for "-"
+    pub restore: Option<OsString>,
+    /// Recurse into directories
+    pub recursive: bool,
+    /// Follow all symbolic links while recursing
+    pub logical: bool,
+    /// Files to change
+    pub paths: Vec<OsString>,
+}
+
+/// Command Options
+pub mod options {
+    /// Option -m
+    pub const MODIFY: &str = "modify";
+    /// Option -x
+    pub const REMOVE: &str = "remove";
+    /// Option -b
+    pub const REMOVE_ALL: &str = "remove-all";
+    /// Option -k
+    pub const REMOVE_DEFAULT: &str = "remove-default";
+    /// Option -d
+    pub const DEFAULT: &str = "default";
+    /// Option -n
+    pub const NO_MASK: &str = "no-mask";
+    /// Option --mask
+    pub const MASK: &str = "mask";
+    /// Option --restore
+    pub const RESTORE: &str = "restore";
+    /// Option -R
+    pub const RECURSIVE: &str = "recursive";
+    /// Option -L
+    pub const LOGICAL: &str = "logical";
+    /// Option -P
+    pub const PHYSICAL: &str = "physical";
+    /// Operand pathname
+    pub const PATHNAME: &str = "pathname";
+}
+
+/// Parse every occurrence of an ACL specification option
+fn parse_specs(
+    args_matches: &ArgMatches,
+    id: &str,
+    flag: char,
+    with_perms: bool,
+) -> UResult<Vec<AclSpecEntry>> {
+    let mut entries = Vec::new();
+    for spec in args_matches.get_many::<String>(id).into_iter().flatten() {
+        let parsed = parse_acl_spec(spec, with_perms)
+            .map_err(|msg| UUsageError::new(EXIT_FAILURE, format!("Option -{}: {}", flag, msg)))?;
+        entries.extend(parsed);
+    }
+    Ok(entries)
+}
+
+impl Config {
+    /// Generate setfacl general Config
+    pub fn from(args_matches: &ArgMatches) -> UResult<Self> {
+        let modify = parse_specs(args_matches, options::MODIFY, 'm', true)?;
+        let remove = parse_specs(args_matches, options::REMOVE, 'x', false)?;
+        let remove_all = args_matches.contains_id(options::REMOVE_ALL);
+        let remove_default = args_matches.contains_id(options::REMOVE_DEFAULT);
+        let default = args_matches.contains_id(options::DEFAULT);
+        let restore = args_matches.get_one::<OsString>(options::RESTORE).cloned();
+        let paths: Vec<OsString> = args_matches
+            .get_many::<OsString>(options::PATHNAME)
+            .map(|v| v.cloned().collect())
+            .unwrap_or_default();
+
+        let edits = args_matches.contains_id(options::MODIFY)
+            || args_matches.contains_id(options::REMOVE)
+            || remove_all
+            || remove_default;
+        match &restore {
+            Some(_) if edits || default => {
+                return Err(UUsageError::new(EXIT_FAILURE, BAD_USAGE_MESSAGE_RESTORE));
+            }
+            Some(_) => {
+                if let Some(extra) = paths.first() {
+                    return Err(UUsageError::new(
+                        EXIT_FAILURE,
+                        format!("extra operand {}", extra.quote()),
+                    ));
+                }
+            }
+            None if !edits => {
+                return Err(UUsageError::new(EXIT_FAILURE, BAD_USAGE_MESSAGE_MISSING));
+            }
+            None if paths.is_empty() => {
+                return Err(UUsageError::new(EXIT_FAILURE, "missing operand"));
+            }
+            None => {}
+        }
+
+        Ok(Self {
+            modify,
+            remove,
+            remove_all,
+            remove_default,
+            default,
+            no_mask: args_matches.contains_id(options::NO_MASK),
+            mask: args_matches.contains_id(options::MASK),
+            restore,
+            recursive: args_matches.contains_id(options::RECURSIVE),
+            logical: last_index(args_matches, options::LOGICAL)
+                > last_index(args_matches, options::PHYSICAL),
+            paths,
+        })
+    }
+}
+
+/// Index of the last occurrence of a flag, so that the later of -L and -P wins
+fn last_index(args_matches: &ArgMatches, id: &str) -> Option<usize> {
+    args_matches.indices_of(id).and_then(|i| i.max())
+}
+
+/// Generate setfacl general Config
+pub fn parse_setfacl_cmd_args(
+    args: impl uucore::Args,
+    about: &str,
+    usage: &str,
+) -> UResult<Config> {
+    let command = setfacl_app(about, usage);
+    let arg_list = args.collect_lossy();
+    Config::from(&command.try_get_matches_from(arg_list)?)
+}
+
+/// Command arguments setting
+pub fn setfacl_app<'a>(about: &'a str, usage: &'a str) -> Command<'a> {
+    Command::new(uucore::util_name())
+        .version(crate_version!())
+        .about(about)
+        .override_usage(format_usage(usage))
+        .arg(
+            Arg::new(options::MODIFY)
+                .short('m')
+                .long(options::MODIFY)
+                .takes_value(true)
+                .value_name("acl")
+                .multiple_occurrences(true)
+                .help("modify the current ACL(s) of file(s)"),
+        )
+        .arg(
+            Arg::new(options::REMOVE)
+                .short('x')
+                .long(options::REMOVE)
+                .takes_value(true)
+                .value_name("acl")
+                .multiple_occurrences(true)
+                .help("remove entries from the ACL(s) of file(s)"),
+        )
+        .arg(
+            Arg::new(options::REMOVE_ALL)
+                .short('b')
+                .long(options::REMOVE_ALL)
+                .help("remove all extended ACL entries"),
+        )
+        .arg(
+            Arg::new(options::REMOVE_DEFAULT)
+                .short('k')
+                .long(options::REMOVE_DEFAULT)
+                .help("remove the default ACL"),
+        )
+        .arg(
+            Arg::new(options::DEFAULT)
+                .short('d')
+                .long(options::DEFAULT)
+                .help("operations apply to the default ACL"),
+        )
+        .arg(
+            Arg::new(options::NO_MASK)
+                .short('n')
+                .long(options::NO_MASK)
+                .help("don't recalculate the effective rights mask"),
+        )
+        .arg(
+            Arg::new(options::MASK)
+                .long(options::MASK)
+                .help("do recalculate the effective rights mask"),
+        )
+        .arg(
+            Arg::new(options::RESTORE)
+                .long(options::RESTORE)
+                .takes_value(true)
+                .value_name("file")
+                .allow_hyphen_values(true)
+                .allow_invalid_utf8(true)
+                .value_parser(clap::value_parser!(OsString))
+                .help("restore ACLs from getfacl output, \"-\" for stdin"),
+        )
+        .arg(
+            Arg::new(options::RECURSIVE)
+                .short('R')
+                .long(options::RECURSIVE)
+                .help("recurse into subdirectories"),
+        )
+        .arg(
+            Arg::new(options::LOGICAL)
+                .short('L')
+                .long(options::LOGICAL)
+                .multiple_occurrences(true)
+                .help("logical walk, follow symbolic links"),
+        )
+        .arg(
+            Arg::new(options::PHYSICAL)
+                .short('P')
+                .long(options::PHYSICAL)
+                .multiple_occurrences(true)
+                .help("physical walk, do not follow symbolic links"),
+        )
+        .arg(
+            Arg::new(options::PATHNAME)
+                .index(1)
+                .multiple_values(true)
+                .allow_invalid_utf8(true)
+                .value_parser(clap::value_parser!(OsString)),
+        )
+}
+
+/// Which of the two ACLs of a file
+#[derive(Clone, Copy)]
+enum AclKind {
+    Access,
+    Default,
+}
+
+impl AclKind {
+    fn xattr(self) -> &'static str {
+        match self {
+            Self::Access => ACL_ACCESS_XATTR,
+            Self::Default => ACL_DEFAULT_XATTR,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Access => "access",
+            Self::Default => "default",
+        }
+    }
+}
+
+fn path_error(path: &Path, msg: impl std::fmt::Display) -> Box<dyn uucore::error::UError> {
+    USimpleError::new(EXIT_FAILURE, format!("{}: {}", path.display(), msg))
+}
+
+/// Validate an ACL and store it if it differs from what the file had
+fn store_acl(path: &Path, kind: AclKind, acl: &Acl, orig: &Acl) -> UResult<()> {
+    if acl == orig {
+        return Ok(());
+    }
+    if !acl.is_empty() {
+        acl.check().map_err(|msg| {
+            path_error(
+                path,
+                format!(
+                    "Malformed {} ACL '{}': {}",
+                    kind.name(),
+                    acl.to_short_text(),
+                    msg
+                ),
+            )
+        })?;
+    }
+    acl_write(path, kind.xattr(), acl, true).map_err(|e| path_error(path, xattr_strerror(e)))
+}
+
+/// Apply -b, -k, -x and -m, in that order, to one file
+///
+/// Below the top of a recursive walk, entries for the default ACL are
+/// silently ignored on files which are not directories.
+fn modify_file(config: &Config, path: &Path, top: bool) -> UResult<()> {
+    let meta = fs::metadata(path).map_err_context(|| path.maybe_quote().to_string())?;
+    let is_dir = meta.is_dir();
+    let orig_access = acl_read(path, ACL_ACCESS_XATTR, true)
+        .map_err(|e| path_error(path, xattr_strerror(e)))?
+        .unwrap_or_else(|| Acl::from_mode(meta.mode()));
+    let orig_default = if is_dir {
+        acl_read(path, ACL_DEFAULT_XATTR, true)
+            .map_err(|e| path_error(path, xattr_strerror(e)))?
+            .unwrap_or_default()
+    } else {
+        Acl::default()
+    };
+
+    let for_default = |entry: &AclSpecEntry| entry.default || config.default;
+    if !is_dir && top && config.modify.iter().chain(&config.remove).any(for_default) {
+        return Err(path_error(path, "Only directories can have default ACLs"));
+    }
+
+    let mut access = orig_access.clone();
+    let mut default = orig_default.clone();
+    if config.remove_all {
+        access.remove_extended_entries();
+        default = Acl::default();
+    }
+    if config.remove_default {
+        default = Acl::default();
+    }
+    // Per ACL, access first: whether -m or -x changed it, and whether -m set
+    // its mask explicitly.
+    let mut touched = [false, false];
+    let mut explicit_mask = [false, false];
+    for entry in &config.remove {
+        if !for_default(entry) {
+            access.remove(entry.tag);
+            touched[0] = true;
+        } else if is_dir {
+            default.remove(entry.tag);
+            touched[1] = true;
+        }
+    }
+    for entry in &config.modify {
+        let mut perm = entry.perm.unwrap_or_default();
+        if perm & ACL_EXECUTE_IF != 0 {
+            perm &= !ACL_EXECUTE_IF;
+            if is_dir || meta.mode() & 0o111 != 0 {
+                perm |= ACL_EXECUTE;
+            }
+        }
+        let (acl, index) = if !for_default(entry) {
+            (&mut access, 0)
+        } else if is_dir {
+            // A new default ACL starts from the base entries of the access ACL.
+            if default.is_empty() {
+                default = access.clone();
+                default.strip();
+            }
+            (&mut default, 1)
+        } else {
+            continue;
+        };
+        acl.set(entry.tag, perm);
+        touched[index] = true;
+        if entry.tag == AclTag::Mask {
+            explicit_mask[index] = true;
+        }
+    }
+
+    for (i, acl) in [&mut access, &mut default].into_iter().enumerate() {
+        if !touched[i] || acl.is_empty() {
+            continue;
+        }
+        let recalc = config.mask || (!config.no_mask && !explicit_mask[i]);
+        // Named entries always need a mask, even with -n.
+        if recalc || acl.get(AclTag::Mask).is_none() {
+            acl.calc_mask();
+        }
+    }
+
+    store_acl(path, AclKind::Access, &access, &orig_access)?;
+    store_acl(path, AclKind::Default, &default, &orig_default)
+}
+
+/// The ACLs of one file in getfacl output
+struct RestoreFile {
+    path: PathBuf,
+    owner: Option<String>,
+    group: Option<String>,
+    flags: u32,
+    entries: Vec<AclSpecEntry>,
+}
+
+/// Parse the output of getfacl, as read by --restore
+///
+/// Nothing is returned for malformed input, so that a restore never applies
+/// half of it.
+fn parse_restore<R: BufRead>(input: R) -> Result<Vec<RestoreFile>, String> {
+    let mut files: Vec<RestoreFile> = Vec::new();
+    for (n, line) in input.split(b'\n').enumerate() {
+        let lineno = n + 1;
+        let mut line = line.map_err(|e| e.to_string())?;
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        if let Some(path) = line.strip_prefix(FILE_HEADER.as_bytes()) {
+            files.push(RestoreFile {
+                path: PathBuf::from(OsString::from_vec(unquote(path))),
+                owner: None,
+                group: None,
+                flags: 0,
+                entries: Vec::new(),
+            });
+            continue;
+        }
+        let text = String::from_utf8_lossy(&line);
+        if text.trim().is_empty() {
+            continue;
+        }
+        let file = match files.last_mut() {
+            Some(file) => file,
+            None if text.starts_with('#') => continue,
+            None => return Err(format!("No filename found in line {}, aborting", lineno)),
+        };
+        if let Some(owner) = text.strip_prefix(OWNER_HEADER) {
+            file.owner = Some(owner.trim().to_string());
+        } else if let Some(group) = text.strip_prefix(GROUP_HEADER) {
+            file.group = Some(group.trim().to_string());
+        } else if let Some(flags) = text.strip_prefix(FLAGS_HEADER) {
+            for (c, bit) in flags.trim().chars().zip([0o4000, 0o2000, 0o1000]) {
+                if c != '-' {
+                    file.flags |= bit;
+                }
+            }
+        } else {
+            let entries =
+                parse_acl_spec(&text, true).map_err(|msg| format!("{} in line {}", msg, lineno))?;
+            file.entries.extend(entries);
+        }
+    }
+    Ok(files)
+}
+
+/// Resolve an owner or group from getfacl output, by number or by name
+fn resolve_id(name: &str, user: bool) -> Option<u32> {
+    name.parse::<u32>().ok().or_else(|| {
+        if user {
+            usr2uid(name).ok()
+        } else {
+            grp2gid(name).ok()
+        }
+    })
+}
+
+/// Restore the owner, group, ACLs and flags of one file
+fn restore_file(file: &RestoreFile) -> UResult<()> {
+    let path = file.path.as_path();
+    let meta = fs::metadata(path).map_err_context(|| path.maybe_quote().to_string())?;
+
+    let mut access = Acl::default();
+    let mut default = Acl::default();
+    for entry in &file.entries {
+        let acl = if entry.default {
+            &mut default
+        } else {
+            &mut access
+        };
+        acl.set(entry.tag, entry.perm.unwrap_or_default() & !ACL_EXECUTE_IF);
+    }
+    if !default.is_empty() && !meta.is_dir() {
+        return Err(path_error(path, "Only directories can have default ACLs"));
+    }
+
+    let uid = match &file.owner {
+        Some(owner) => resolve_id(owner, true)
+            .ok_or_else(|| path_error(path, format!("Invalid owner {}", owner.quote())))?,
+        None => meta.uid(),
+    };
+    let gid = match &file.group {
+        Some(group) => resolve_id(group, false)
+            .ok_or_else(|| path_error(path, format!("Invalid group {}", group.quote())))?,
+        None => meta.gid(),
+    };
+    if uid != meta.uid() || gid != meta.gid() {
+        let c_path = CString::new(path.as_os_str().as_bytes())
+            .map_err(|_| path_error(path, "Invalid argument"))?;
+        // SAFETY: c_path is a valid NUL terminated string.
+        if unsafe { libc::chown(c_path.as_ptr(), uid, gid) } != 0 {
+            let err = std::io::Error::last_os_error().to_string();
+            return Err(path_error(path, strip_os_error(&err)));
+        }
+    }
+
+    let orig_access = acl_read(path, ACL_ACCESS_XATTR, true)
+        .map_err(|e| path_error(path, xattr_strerror(e)))?
+        .unwrap_or_else(|| Acl::from_mode(meta.mode()));
+    if access.is_empty() {
+        access = orig_access.clone();
+    }
+    store_acl(path, AclKind::Access, &access, &orig_access)?;
+    if meta.is_dir() {
+        let orig_default = acl_read(path, ACL_DEFAULT_XATTR, true)
+            .map_err(|e| path_error(path, xattr_strerror(e)))?
+            .unwrap_or_default();
+        store_acl(path, AclKind::Default, &default, &orig_default)?;
+    }
+
+    // chown() and the access ACL leave the permission bits right, but may
+    // have cleared the setuid and setgid bits.
+    let mode = fs::metadata(path)
+        .map_err_context(|| path.maybe_quote().to_string())?
+        .mode();
+    if mode & 0o7000 != file.flags {
+        fs::set_permissions(path, Permissions::from_mode(mode & 0o777 | file.flags))
+            .map_err_context(|| path.maybe_quote().to_string())?;
+    }
+    Ok(())
+}
+
+/// Restore every file of getfacl output
+fn restore(file: &OsString) -> UResult<()> {
+    let files = if file == "-" {
+        parse_restore(stdin().lock())
+    } else {
+        let f = File::open(file).map_err_context(|| file.maybe_quote().to_string())?;
+        parse_restore(BufReader::new(f))
+    }
+    .map_err(|msg| USimpleError::new(EXIT_FAILURE, msg))?;
+
+    for file in &files {
+        if let Err(e) = restore_file(file) {
+            show!(e);
+        }
+    }
+    Ok(())
+}
+
+/// Change the ACLs of every path, walking directories with -R
+pub fn handle_input(config: &Config) -> UResult<()> {
+    if let Some(file) = &config.restore {
+        return restore(file);
+    }
+    for path in &config.paths {
+        let path = Path::new(path);
+        if !config.recursive {
+            if let Err(e) = modify_file(config, path, true) {
+                show!(e);
+            }
+            continue;
+        }
+        let walker = WalkDir::new(path)
+            .follow_links(config.logical)
+            .sort_by_file_name();
+        for entry in walker {
+            match entry {
+                // A physical walk leaves out symbolic links below the top.
+                Ok(entry) if entry.depth() > 0 && entry.path_is_symlink() && !config.logical => {}
+                Ok(entry) => {
+                    if let Err(e) = modify_file(config, entry.path(), entry.depth() == 0) {
+                        show!(e);
+                    }
+                }
+                Err(err) => {
+                    let msg = err
+                        .io_error()
+                        .map(|e| e.to_string())
+                        .unwrap_or_else(|| err.to_string());
+                    show!(path_error(err.path().unwrap_or(path), strip_os_error(&msg)));
+                }
+            }
+        }
+    }
+    Ok(())
+}
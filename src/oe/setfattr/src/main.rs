@@ -0,0 +1 @@
+uucore::bin!(oe_setfattr);
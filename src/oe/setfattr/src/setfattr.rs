@@ -0,0 +1,30 @@
+//! This file is part of the easybox package.
+//
+// (c) openeuler developers
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use clap::Command;
+use setfattr_common::Config;
+use uucore::{error::UResult, help_section, help_usage};
+
+/// setfattr common functions
+pub mod setfattr_common;
+
+const ABOUT: &str = help_section!("about", "setfattr.md");
+const USAGE: &str = help_usage!("setfattr.md");
+
+#[uucore::main]
+/// This the main of setfattr
+///
+pub fn oemain(args: impl uucore::Args) -> UResult<()> {
+    let config: Config = setfattr_common::parse_setfattr_cmd_args(args, ABOUT, USAGE)?;
+    setfattr_common::handle_input(&config)
+}
+
+/// This the oe_app of setfattr
+///
+pub fn oe_app<'a>() -> Command<'a> {
+    setfattr_common::setfattr_app(ABOUT, USAGE)
+}
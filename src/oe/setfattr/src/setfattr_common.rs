@@ -0,0 +1,238 @@
+//! This file is part of the easybox package.
+//
+// (c) openeuler developers
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{stdin, BufReader};
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+use clap::{crate_version, Arg, ArgMatches, Command};
+use libc::EXIT_FAILURE;
+use oe_attr::lib_attr::{xattr_delete, xattr_store, xattr_strerror};
+use oe_attr::lib_dump::{decode_value, parse_dump};
+use uucore::display::Quotable;
+use uucore::error::{FromIo, UResult, USimpleError, UUsageError};
+use uucore::{format_usage, show};
+
+const BAD_USAGE_MESSAGE: &str = "Only one of -n, -x or --restore allowed";
+const BAD_USAGE_MESSAGE_VALUE: &str = "-v only allowed with -n";
+const BAD_USAGE_MESSAGE_MISSING: &str = "One of -n, -x or --restore is required";
+
+/// Operation types
+#[derive(PartialEq, Eq)]
+pub enum Operation {
+    /// Set the named attribute
+    Set(OsString, Vec<u8>),
+    /// Remove the named attribute
+    Remove(OsString),
+    /// Restore a dump from this file, or stdin for "-"
+    Restore(OsString),
+}
+
+/// Config
+pub struct Config {
+    /// What to do
+    pub operation: Operation,
+    /// Follow symbolic links to change their targets' attributes
+    pub dereference: bool,
+    /// Files to change
+    pub paths: Vec<OsString>,
+}
+
+/// Command Options
+pub mod options {
+    /// Option -n
+    pub const NAME: &str = "name";
+    /// Option -v
+    pub const VALUE: &str = "value";
+    /// Option -x
+    pub const REMOVE: &str = "remove";
+    /// Option -h
+    pub const NO_DEREFERENCE: &str = "no-dereference";
+    /// Option --restore
+    pub const RESTORE: &str = "restore";
+    /// Operand pathname
+    pub const PATHNAME: &str = "pathname";
+}
+
+impl Config {
+    /// Generate setfattr general Config
+    pub fn from(args_matches: &ArgMatches) -> UResult<Self> {
+        let name = args_matches.get_one::<OsString>(options::NAME);
+        let remove = args_matches.get_one::<OsString>(options::REMOVE);
+        let restore = args_matches.get_one::<OsString>(options::RESTORE);
+        let value = args_matches.get_one::<OsString>(options::VALUE);
+        let paths: Vec<OsString> = args_matches
+            .get_many::<OsString>(options::PATHNAME)
+            .map(|v| v.cloned().collect())
+            .unwrap_or_default();
+
+        let given = [name.is_some(), remove.is_some(), restore.is_some()];
+        if given.iter().filter(|&&g| g).count() > 1 {
+            return Err(UUsageError::new(EXIT_FAILURE, BAD_USAGE_MESSAGE));
+        }
+        if value.is_some() && name.is_none() {
+            return Err(UUsageError::new(EXIT_FAILURE, BAD_USAGE_MESSAGE_VALUE));
+        }
+
+        let operation = match (name, remove, restore) {
+            (Some(name), _, _) => {
+                let value = match value {
+                    Some(value) => decode_value(value.as_bytes()).ok_or_else(|| {
+                        USimpleError::new(
+                            EXIT_FAILURE,
+                            format!("{}: bad input encoding", value.quote()),
+                        )
+                    })?,
+                    None => Vec::new(),
+                };
+                Operation::Set(name.clone(), value)
+            }
+            (_, Some(name), _) => Operation::Remove(name.clone()),
+            (_, _, Some(file)) => {
+                if let Some(extra) = paths.first() {
+                    return Err(UUsageError::new(
+                        EXIT_FAILURE,
+                        format!("extra operand {}", extra.quote()),
+                    ));
+                }
+                Operation::Restore(file.clone())
+            }
+            _ => return Err(UUsageError::new(EXIT_FAILURE, BAD_USAGE_MESSAGE_MISSING)),
+        };
+        if paths.is_empty() && !matches!(operation, Operation::Restore(_)) {
+            return Err(UUsageError::new(EXIT_FAILURE, "missing operand"));
+        }
+
+        Ok(Self {
+            operation,
+            dereference: !args_matches.contains_id(options::NO_DEREFERENCE),
+            paths,
+        })
+    }
+}
+
+/// Generate setfattr general Config
+pub fn parse_setfattr_cmd_args(
+    args: impl uucore::Args,
+    about: &str,
+    usage: &str,
+) -> UResult<Config> {
+    let command = setfattr_app(about, usage);
+    let arg_list = args.collect_lossy();
+    Config::from(&command.try_get_matches_from(arg_list)?)
+}
+
+/// Command arguments setting
+pub fn setfattr_app<'a>(about: &'a str, usage: &'a str) -> Command<'a> {
+    Command::new(uucore::util_name())
+        .version(crate_version!())
+        .about(about)
+        .override_usage(format_usage(usage))
+        .arg(
+            Arg::new(options::NAME)
+                .short('n')
+                .long(options::NAME)
+                .takes_value(true)
+                .value_name("name")
+                .allow_invalid_utf8(true)
+                .value_parser(clap::value_parser!(OsString))
+                .help("set the value of the named extended attribute"),
+        )
+        .arg(
+            Arg::new(options::VALUE)
+                .short('v')
+                .long(options::VALUE)
+                .takes_value(true)
+                .value_name("value")
+                .allow_hyphen_values(true)
+                .allow_invalid_utf8(true)
+                .value_parser(clap::value_parser!(OsString))
+                .help("use value as the attribute value"),
+        )
+        .arg(
+            Arg::new(options::REMOVE)
+                .short('x')
+                .long(options::REMOVE)
+                .takes_value(true)
+                .value_name("name")
+                .allow_invalid_utf8(true)
+                .value_parser(clap::value_parser!(OsString))
+                .help("remove the named extended attribute"),
+        )
+        .arg(
+            Arg::new(options::NO_DEREFERENCE)
+                .short('h')
+                .long(options::NO_DEREFERENCE)
+                .help("do not dereference symbolic links"),
+        )
+        .arg(
+            Arg::new(options::RESTORE)
+                .long(options::RESTORE)
+                .takes_value(true)
+                .value_name("file")
+                .allow_hyphen_values(true)
+                .allow_invalid_utf8(true)
+                .value_parser(clap::value_parser!(OsString))
+                .help("restore extended attributes from a getfattr --dump file, \"-\" for stdin"),
+        )
+        .arg(Arg::new("help").long("help").help("display this help"))
+        .arg(
+            Arg::new(options::PATHNAME)
+                .index(1)
+                .multiple_values(true)
+                .allow_invalid_utf8(true)
+                .value_parser(clap::value_parser!(OsString)),
+        )
+}
+
+/// Restore every attribute of a parsed dump
+fn restore(config: &Config, file: &OsString) -> UResult<()> {
+    let files = if file == "-" {
+        parse_dump(stdin().lock())
+    } else {
+        let f = File::open(file).map_err_context(|| file.maybe_quote().to_string())?;
+        parse_dump(BufReader::new(f))
+    }
+    .map_err(|msg| USimpleError::new(EXIT_FAILURE, msg))?;
+
+    for dump in files {
+        for (name, value) in &dump.attrs {
+            if let Err(err) = xattr_store(&dump.path, name, value, config.dereference) {
+                show!(USimpleError::new(
+                    EXIT_FAILURE,
+                    format!("{}: {}", dump.path.display(), xattr_strerror(err)),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Apply the operation to every path
+pub fn handle_input(config: &Config) -> UResult<()> {
+    let (name, value) = match &config.operation {
+        Operation::Restore(file) => return restore(config, file),
+        Operation::Set(name, value) => (name, Some(value)),
+        Operation::Remove(name) => (name, None),
+    };
+    for path in &config.paths {
+        let path = Path::new(path);
+        let res = match value {
+            Some(value) => xattr_store(path, name, value, config.dereference),
+            None => xattr_delete(path, name, config.dereference),
+        };
+        if let Err(err) = res {
+            show!(USimpleError::new(
+                EXIT_FAILURE,
+                format!("{}: {}", path.display(), xattr_strerror(err)),
+            ));
+        }
+    }
+    Ok(())
+}
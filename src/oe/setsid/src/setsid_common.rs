@@ -5,7 +5,7 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
-use uucore::error::UResult;
+use uucore::error::{UResult, USimpleError};
 use uucore::format_usage;
 use uucore::msg_log::{err_c, errtryhelp_c, warnx_c};
 
@@ -13,16 +13,47 @@ use clap::{crate_version, Arg, Command};
 use errno::errno;
 use fork::{fork, Fork};
 use nix::{
-    libc::{ioctl, wait, ENOENT, EXIT_FAILURE, STDIN_FILENO, TIOCSCTTY, WEXITSTATUS, WIFEXITED},
-    unistd::{execvp, getpgrp, getpid, setsid},
+    errno::Errno,
+    fcntl::{open, OFlag},
+    libc::{
+        ioctl, syscall, SYS_close_range, ENOENT, EXIT_FAILURE, STDERR_FILENO, STDIN_FILENO,
+        STDOUT_FILENO, TIOCSCTTY,
+    },
+    mount::{mount, MsFlags},
+    sched::{unshare, CloneFlags},
+    sys::{
+        signal::{kill, sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal},
+        stat::{umask, Mode},
+        wait::{waitpid, WaitPidFlag, WaitStatus},
+    },
+    unistd::{
+        chdir, close, dup2, execvp, getgid, getpgrp, getpid, getuid, pipe2, read, setsid, write,
+        Pid,
+    },
 };
 use std::ffi::CString;
+use std::fs;
+use std::os::unix::io::RawFd;
+use std::sync::atomic::{AtomicI32, Ordering};
 
 /// Exit code when execvp failed and errno is not ENOENT.
 pub static EX_EXEC_FAILED: i32 = 126;
 /// Exit code when execvp failed and errno is ENOENT.
 pub static EX_EXEC_ENOENT: i32 = 127;
 
+/// Signals a supervising process passes on to its child.
+const FORWARD_SIGNALS: [Signal; 6] = [
+    Signal::SIGHUP,
+    Signal::SIGINT,
+    Signal::SIGQUIT,
+    Signal::SIGTERM,
+    Signal::SIGUSR1,
+    Signal::SIGUSR2,
+];
+
+/// Child which receives forwarded signals, 0 if there is none yet.
+static FORWARD_PID: AtomicI32 = AtomicI32::new(0);
+
 /// Config.
 pub struct Config {
     ///
@@ -31,6 +62,24 @@ pub struct Config {
     pub fork: bool,
     ///
     pub wait: bool,
+    /// Write the pid of the program to this file
+    pub pidfile: Option<String>,
+    /// Redirect stdin from /dev/null and stdout and stderr to this file
+    pub stdio: Option<String>,
+    /// Change to this directory before running the program
+    pub chdir: Option<String>,
+    /// File mode creation mask for the program
+    pub umask: Option<u32>,
+    /// Close all inherited file descriptors above 2
+    pub close_fds: bool,
+    /// Namespaces to unshare before running the program
+    pub namespaces: CloneFlags,
+    /// User id inside the new user namespace mapped to ours
+    pub map_user: Option<u32>,
+    /// Group id inside the new user namespace mapped to ours
+    pub map_group: Option<u32>,
+    /// Mount a new /proc in the new mount namespace
+    pub mount_proc: bool,
     ///
     pub command: Vec<String>,
 }
@@ -43,6 +92,36 @@ pub mod options {
     pub static FORK: &str = "fork";
     ///
     pub static WAIT: &str = "wait";
+    /// Option --pidfile
+    pub static PIDFILE: &str = "pidfile";
+    /// Option --stdio
+    pub static STDIO: &str = "stdio";
+    /// Option --chdir
+    pub static CHDIR: &str = "chdir";
+    /// Option --umask
+    pub static UMASK: &str = "umask";
+    /// Option --close-fds
+    pub static CLOSE_FDS: &str = "close-fds";
+    /// Option --pid
+    pub static PID: &str = "pid";
+    /// Option --mount
+    pub static MOUNT: &str = "mount";
+    /// Option --uts
+    pub static UTS: &str = "uts";
+    /// Option --ipc
+    pub static IPC: &str = "ipc";
+    /// Option --net
+    pub static NET: &str = "net";
+    /// Option --user
+    pub static USER: &str = "user";
+    /// Option --map-root-user
+    pub static MAP_ROOT_USER: &str = "map-root-user";
+    /// Option --map-user
+    pub static MAP_USER: &str = "map-user";
+    /// Option --map-group
+    pub static MAP_GROUP: &str = "map-group";
+    /// Option --mount-proc
+    pub static MOUNT_PROC: &str = "mount-proc";
     ///
     pub static COMMAND: &str = "command";
 }
@@ -50,10 +129,58 @@ pub mod options {
 impl Config {
     ///
     pub fn from(options: &clap::ArgMatches) -> UResult<Self> {
+        let umask = match options.value_of(options::UMASK) {
+            Some(mask) => match u32::from_str_radix(mask, 8) {
+                Ok(v) if v <= 0o777 => Some(v),
+                _ => {
+                    return Err(USimpleError::new(
+                        EXIT_FAILURE,
+                        format!("invalid umask: '{}'", mask),
+                    ))
+                }
+            },
+            None => None,
+        };
+
+        let mut namespaces = CloneFlags::empty();
+        for (id, flag) in [
+            (options::PID, CloneFlags::CLONE_NEWPID),
+            (options::MOUNT, CloneFlags::CLONE_NEWNS),
+            (options::UTS, CloneFlags::CLONE_NEWUTS),
+            (options::IPC, CloneFlags::CLONE_NEWIPC),
+            (options::NET, CloneFlags::CLONE_NEWNET),
+            (options::USER, CloneFlags::CLONE_NEWUSER),
+        ] {
+            if options.contains_id(id) {
+                namespaces |= flag;
+            }
+        }
+        let root = options.contains_id(options::MAP_ROOT_USER);
+        let map_user =
+            parse_id(options, options::MAP_USER, "user")?.or(if root { Some(0) } else { None });
+        let map_group =
+            parse_id(options, options::MAP_GROUP, "group")?.or(if root { Some(0) } else { None });
+        if map_user.is_some() || map_group.is_some() {
+            namespaces |= CloneFlags::CLONE_NEWUSER;
+        }
+        let mount_proc = options.contains_id(options::MOUNT_PROC);
+        if mount_proc {
+            namespaces |= CloneFlags::CLONE_NEWNS;
+        }
+
         Ok(Self {
             ctty: options.contains_id(options::CTTY),
             fork: options.contains_id(options::FORK),
             wait: options.contains_id(options::WAIT),
+            pidfile: options.value_of(options::PIDFILE).map(String::from),
+            stdio: options.value_of(options::STDIO).map(String::from),
+            chdir: options.value_of(options::CHDIR).map(String::from),
+            umask,
+            close_fds: options.contains_id(options::CLOSE_FDS),
+            namespaces,
+            map_user,
+            map_group,
+            mount_proc,
             command: options
                 .values_of(options::COMMAND)
                 .unwrap_or_default()
@@ -63,6 +190,19 @@ impl Config {
     }
 }
 
+fn parse_id(options: &clap::ArgMatches, id: &str, name: &str) -> UResult<Option<u32>> {
+    match options.value_of(id) {
+        Some(v) => match v.parse::<u32>() {
+            Ok(v) => Ok(Some(v)),
+            Err(_) => Err(USimpleError::new(
+                EXIT_FAILURE,
+                format!("invalid {} id: '{}'", name, v),
+            )),
+        },
+        None => Ok(None),
+    }
+}
+
 ///
 pub fn parse_setsid_cmd_args(args: impl uucore::Args, about: &str, usage: &str) -> UResult<Config> {
     let command = setsid_app(about, usage);
@@ -98,19 +238,122 @@ pub fn setsid_app<'a>(about: &'a str, usage: &'a str) -> Command<'a> {
                 .help("wait program to exit, and use the same return")
                 .display_order(2),
         )
+        .arg(
+            Arg::new(options::PIDFILE)
+                .long(options::PIDFILE)
+                .takes_value(true)
+                .value_name("file")
+                .help("write the pid of the program to file")
+                .display_order(3),
+        )
+        .arg(
+            Arg::new(options::STDIO)
+                .long(options::STDIO)
+                .takes_value(true)
+                .value_name("file")
+                .help("read stdin from /dev/null, append stdout and stderr to file")
+                .display_order(4),
+        )
+        .arg(
+            Arg::new(options::CHDIR)
+                .long(options::CHDIR)
+                .takes_value(true)
+                .value_name("dir")
+                .help("change to dir before running the program")
+                .display_order(5),
+        )
+        .arg(
+            Arg::new(options::UMASK)
+                .long(options::UMASK)
+                .takes_value(true)
+                .value_name("mask")
+                .help("set the octal file mode creation mask")
+                .display_order(6),
+        )
+        .arg(
+            Arg::new(options::CLOSE_FDS)
+                .long(options::CLOSE_FDS)
+                .help("close inherited file descriptors above 2")
+                .display_order(7),
+        )
+        .arg(
+            Arg::new(options::PID)
+                .long(options::PID)
+                .help("unshare pid namespace, with a minimal init")
+                .display_order(8),
+        )
+        .arg(
+            Arg::new(options::MOUNT)
+                .long(options::MOUNT)
+                .help("unshare mounts namespace")
+                .display_order(9),
+        )
+        .arg(
+            Arg::new(options::UTS)
+                .long(options::UTS)
+                .help("unshare UTS namespace (hostname etc)")
+                .display_order(10),
+        )
+        .arg(
+            Arg::new(options::IPC)
+                .long(options::IPC)
+                .help("unshare System V IPC namespace")
+                .display_order(11),
+        )
+        .arg(
+            Arg::new(options::NET)
+                .long(options::NET)
+                .help("unshare network namespace")
+                .display_order(12),
+        )
+        .arg(
+            Arg::new(options::USER)
+                .long(options::USER)
+                .help("unshare user namespace")
+                .display_order(13),
+        )
+        .arg(
+            Arg::new(options::MAP_ROOT_USER)
+                .long(options::MAP_ROOT_USER)
+                .conflicts_with_all(&[options::MAP_USER, options::MAP_GROUP])
+                .help("map current user and group to root (implies --user)")
+                .display_order(14),
+        )
+        .arg(
+            Arg::new(options::MAP_USER)
+                .long(options::MAP_USER)
+                .takes_value(true)
+                .value_name("uid")
+                .help("map current user to uid (implies --user)")
+                .display_order(15),
+        )
+        .arg(
+            Arg::new(options::MAP_GROUP)
+                .long(options::MAP_GROUP)
+                .takes_value(true)
+                .value_name("gid")
+                .help("map current group to gid (implies --user)")
+                .display_order(16),
+        )
+        .arg(
+            Arg::new(options::MOUNT_PROC)
+                .long(options::MOUNT_PROC)
+                .help("mount proc filesystem first (implies --mount)")
+                .display_order(17),
+        )
         .arg(
             Arg::new("help")
                 .short('h')
                 .long("help")
                 .help("display this help")
-                .display_order(3),
+                .display_order(18),
         )
         .arg(
             Arg::new("version")
                 .short('V')
                 .long("version")
                 .help("display version")
-                .display_order(4),
+                .display_order(19),
         )
         .arg(
             Arg::new(options::COMMAND)
@@ -125,6 +368,189 @@ pub fn setsid_app<'a>(about: &'a str, usage: &'a str) -> Command<'a> {
         .trailing_var_arg(true)
 }
 
+/// Write pid to path through a temporary file, so readers never see a partial pidfile.
+fn write_pidfile(path: &str, pid: Pid) -> std::io::Result<()> {
+    let tmp = format!("{}.{}.tmp", path, getpid());
+    fs::write(&tmp, format!("{}\n", pid))?;
+    if let Err(e) = fs::rename(&tmp, path) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Open /dev/null for stdin and path for stdout and stderr.
+fn open_stdio(path: &str) -> UResult<(RawFd, RawFd)> {
+    let open_err = |path: &str, e: Errno| {
+        USimpleError::new(EXIT_FAILURE, format!("cannot open {}: {}", path, e.desc()))
+    };
+    let null_fd = open(
+        "/dev/null",
+        OFlag::O_RDONLY | OFlag::O_CLOEXEC,
+        Mode::empty(),
+    )
+    .map_err(|e| open_err("/dev/null", e))?;
+    let out_fd = open(
+        path,
+        OFlag::O_WRONLY | OFlag::O_CREAT | OFlag::O_APPEND | OFlag::O_CLOEXEC,
+        Mode::from_bits_truncate(0o666),
+    )
+    .map_err(|e| open_err(path, e))?;
+    Ok((null_fd, out_fd))
+}
+
+/// Close every file descriptor above 2, by walking /proc/self/fd if close_range(2) is missing.
+fn close_inherited_fds() {
+    if unsafe { syscall(SYS_close_range, 3u32, u32::MAX, 0u32) } == 0 {
+        return;
+    }
+    let fds: Vec<RawFd> = match fs::read_dir("/proc/self/fd") {
+        Ok(dir) => dir
+            .flatten()
+            .filter_map(|e| e.file_name().to_string_lossy().parse::<RawFd>().ok())
+            .filter(|fd| *fd > STDERR_FILENO)
+            .collect(),
+        Err(_) => return,
+    };
+    for fd in fds {
+        let _ = close(fd);
+    }
+}
+
+/// Wait for child and turn its termination into our exit status.
+fn wait_child(child: Pid) -> UResult<()> {
+    loop {
+        match waitpid(child, None) {
+            Ok(WaitStatus::Exited(_, code)) => return Err(code.into()),
+            // Report death by signal like shells and supervisors do.
+            Ok(WaitStatus::Signaled(_, signal, _)) => return Err((128 + signal as i32).into()),
+            Ok(_) | Err(Errno::EINTR) => continue,
+            Err(_) => err_c(EXIT_FAILURE, "waitpid"),
+        }
+    }
+}
+
+extern "C" fn forward_signal(signal: nix::libc::c_int) {
+    let pid = FORWARD_PID.load(Ordering::SeqCst);
+    if pid > 0 {
+        unsafe { nix::libc::kill(pid, signal) };
+    }
+}
+
+/// Wait for child while passing on termination signals to it. If reap_all is
+/// set, as for the init of a pid namespace, every other child is reaped too.
+/// Returns the exit code of child, 128+signum if it was killed by a signal.
+fn supervise(child: Pid, reap_all: bool) -> i32 {
+    FORWARD_PID.store(child.as_raw(), Ordering::SeqCst);
+    let action = SigAction::new(
+        SigHandler::Handler(forward_signal),
+        SaFlags::SA_RESTART,
+        SigSet::empty(),
+    );
+    for signal in FORWARD_SIGNALS {
+        let _ = unsafe { sigaction(signal, &action) };
+    }
+    let wait_pid = match reap_all {
+        true => None,
+        false => Some(child),
+    };
+    loop {
+        match waitpid(wait_pid, Some(WaitPidFlag::__WALL)) {
+            Ok(WaitStatus::Exited(pid, code)) if pid == child => return code,
+            Ok(WaitStatus::Signaled(pid, signal, _)) if pid == child => return 128 + signal as i32,
+            Ok(_) | Err(Errno::EINTR) => continue,
+            Err(_) => {
+                // Nothing left to wait for, make sure child is gone
+                let _ = kill(child, Signal::SIGKILL);
+                return EXIT_FAILURE;
+            }
+        }
+    }
+}
+
+fn write_proc_file(path: &str, content: &str) -> UResult<()> {
+    fs::write(path, content).map_err(|e| {
+        USimpleError::new(
+            EXIT_FAILURE,
+            format!(
+                "write failed {}: {}",
+                path,
+                Errno::from_i32(e.raw_os_error().unwrap_or(0)).desc()
+            ),
+        )
+    })
+}
+
+fn mount_err(what: &str, e: Errno) -> Box<dyn uucore::error::UError> {
+    USimpleError::new(EXIT_FAILURE, format!("{} failed: {}", what, e.desc()))
+}
+
+/// Mount a fresh proc filesystem on /proc.
+fn mount_proc() -> UResult<()> {
+    mount(
+        Some("proc"),
+        "/proc",
+        Some("proc"),
+        MsFlags::MS_NOSUID | MsFlags::MS_NODEV | MsFlags::MS_NOEXEC,
+        None::<&str>,
+    )
+    .map_err(|e| mount_err("mount /proc", e))
+}
+
+/// Unshare the requested namespaces and set up user id mappings. With a new
+/// pid namespace, the calling process forks a minimal init and only returns in
+/// a grandchild running inside the namespace; the others exit with its status.
+fn enter_namespaces(config: &Config) -> UResult<()> {
+    let (uid, gid) = (getuid(), getgid());
+    if let Err(e) = unshare(config.namespaces) {
+        return Err(USimpleError::new(
+            EXIT_FAILURE,
+            format!("unshare failed: {}", e.desc()),
+        ));
+    }
+
+    if let Some(map_group) = config.map_group {
+        // Unprivileged processes may only map gids after denying setgroups(2)
+        write_proc_file("/proc/self/setgroups", "deny")?;
+        write_proc_file("/proc/self/gid_map", &format!("{} {} 1", map_group, gid))?;
+    }
+    if let Some(map_user) = config.map_user {
+        write_proc_file("/proc/self/uid_map", &format!("{} {} 1", map_user, uid))?;
+    }
+
+    if config.namespaces.contains(CloneFlags::CLONE_NEWNS) {
+        // Keep our mounts from propagating back to the parent namespace
+        mount(
+            Some("none"),
+            "/",
+            None::<&str>,
+            MsFlags::MS_REC | MsFlags::MS_PRIVATE,
+            None::<&str>,
+        )
+        .map_err(|e| mount_err("cannot change root filesystem propagation", e))?;
+    }
+
+    if config.namespaces.contains(CloneFlags::CLONE_NEWPID) {
+        // Our next child becomes pid 1 of the new namespace.
+        match fork() {
+            Ok(Fork::Parent(init)) => std::process::exit(supervise(Pid::from_raw(init), false)),
+            Ok(Fork::Child) => {}
+            Err(_) => err_c(EXIT_FAILURE, "fork"),
+        }
+        if config.mount_proc {
+            mount_proc()?;
+        }
+        match fork() {
+            Ok(Fork::Parent(child)) => std::process::exit(supervise(Pid::from_raw(child), true)),
+            Ok(Fork::Child) => {}
+            Err(_) => err_c(EXIT_FAILURE, "fork"),
+        }
+    } else if config.mount_proc {
+        mount_proc()?;
+    }
+    Ok(())
+}
+
 ///
 pub fn handle_input(config: Config) -> UResult<()> {
     if config.command.is_empty() {
@@ -132,24 +558,59 @@ pub fn handle_input(config: Config) -> UResult<()> {
         errtryhelp_c(EXIT_FAILURE);
     }
 
+    // Open the redirection target early, so errors reach the caller's stderr.
+    let stdio_fds = match &config.stdio {
+        Some(path) => Some(open_stdio(path)?),
+        None => None,
+    };
+
     if config.fork || getpgrp() == getpid() {
+        // The child waits on this pipe until the parent has written the pidfile.
+        let sync_pipe = match config.pidfile {
+            Some(_) => Some(
+                pipe2(OFlag::O_CLOEXEC)
+                    .map_err(|e| USimpleError::new(EXIT_FAILURE, format!("pipe: {}", e.desc())))?,
+            ),
+            None => None,
+        };
         match fork() {
             Ok(Fork::Parent(child)) => {
-                let mut status: i32 = 0;
+                let child = Pid::from_raw(child);
+                if let (Some(pidfile), Some((sync_read, sync_write))) = (&config.pidfile, sync_pipe)
+                {
+                    let _ = close(sync_read);
+                    if write_pidfile(pidfile, child).is_err() {
+                        // Closing the pipe without a byte makes the child give up.
+                        err_c(EXIT_FAILURE, &format!("cannot write pidfile {}", pidfile));
+                    }
+                    let _ = write(sync_write, b"1");
+                    let _ = close(sync_write);
+                }
                 if !config.wait {
                     return Ok(());
                 }
-                if unsafe { wait(&mut status) } != child {
-                    err_c(EXIT_FAILURE, "wait");
-                }
-                if WIFEXITED(status) {
-                    return Err(WEXITSTATUS(status).into());
+                return wait_child(child);
+            }
+            Ok(Fork::Child) => {
+                if let Some((sync_read, sync_write)) = sync_pipe {
+                    let _ = close(sync_write);
+                    let mut buf = [0u8; 1];
+                    if read(sync_read, &mut buf) != Ok(1) {
+                        std::process::exit(EXIT_FAILURE);
+                    }
+                    let _ = close(sync_read);
                 }
-                err_c(status, &format!("child {} did not exit normally", child));
             }
-            Ok(Fork::Child) => {}
             Err(_) => err_c(EXIT_FAILURE, "fork"),
         }
+    } else if let Some(pidfile) = &config.pidfile {
+        if write_pidfile(pidfile, getpid()).is_err() {
+            err_c(EXIT_FAILURE, &format!("cannot write pidfile {}", pidfile));
+        }
+    }
+
+    if !config.namespaces.is_empty() {
+        enter_namespaces(&config)?;
     }
 
     match setsid() {
@@ -161,6 +622,29 @@ pub fn handle_input(config: Config) -> UResult<()> {
         err_c(EXIT_FAILURE, "failed to set the controlling terminal");
     }
 
+    if let Some(mask) = config.umask {
+        umask(Mode::from_bits_truncate(mask));
+    }
+
+    if let Some(dir) = &config.chdir {
+        if chdir(dir.as_str()).is_err() {
+            err_c(EXIT_FAILURE, &format!("cannot change directory to {}", dir));
+        }
+    }
+
+    if let Some((null_fd, out_fd)) = stdio_fds {
+        if dup2(null_fd, STDIN_FILENO).is_err()
+            || dup2(out_fd, STDOUT_FILENO).is_err()
+            || dup2(out_fd, STDERR_FILENO).is_err()
+        {
+            err_c(EXIT_FAILURE, "failed to redirect standard streams");
+        }
+    }
+
+    if config.close_fds {
+        close_inherited_fds();
+    }
+
     let c_args: Vec<CString> = config
         .command
         .iter()
@@ -5,89 +5,312 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
+use std::fs;
 use std::io::Write;
 
 use libc::EXIT_FAILURE;
 use nix::{errno::Errno, sched::CpuSet};
 use uucore::error::{UResult, USimpleError};
 
-/// Parse mask into CpuSet
-pub fn cpumask_parse(mask: &String) -> Result<CpuSet, Errno> {
-    let mut set = CpuSet::new();
-    let mut end = mask.len();
-    let mut cpu = 0;
-    if mask.starts_with("0x") {
-        end -= 2;
+const SYS_CPU_DIR: &str = "/sys/devices/system/cpu";
+const SYS_NODE_DIR: &str = "/sys/devices/system/node";
+/// List token which keeps only one hardware thread of every core
+pub const SMT_SIBLINGS_OFF: &str = "smt-siblings-off";
+
+/// Topology selectors accepted in a CPU list, e.g. `node:0` or `core:0-3`
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Selector {
+    /// NUMA node, from /sys/devices/system/node/node*/cpulist
+    Node,
+    /// Physical core, numbered in order of its lowest online CPU
+    Core,
+    /// Physical package, from topology/physical_package_id
+    Socket,
+    /// Last level cache, from the highest data cache/index*/id
+    Llc,
+}
+
+impl Selector {
+    fn from_prefix(token: &str) -> Option<(Self, &str)> {
+        let (name, ids) = token.split_once(':')?;
+        let selector = match name {
+            "node" => Self::Node,
+            "core" => Self::Core,
+            "socket" => Self::Socket,
+            "llc" => Self::Llc,
+            _ => return None,
+        };
+        Some((selector, ids))
+    }
+
+    /// Topology id of cpu, None if it can not be determined
+    fn cpu_id(self, cpu: usize) -> Option<usize> {
+        let cpu_dir = format!("{}/cpu{}", SYS_CPU_DIR, cpu);
+        match self {
+            Self::Socket => read_sysfs_usize(&format!("{}/topology/physical_package_id", cpu_dir)),
+            Self::Llc => {
+                let mut llc: Option<(usize, usize)> = None;
+                for entry in fs::read_dir(format!("{}/cache", cpu_dir)).ok()?.flatten() {
+                    let index_dir = entry.path();
+                    if !entry.file_name().to_string_lossy().starts_with("index") {
+                        continue;
+                    }
+                    let cache_type = fs::read_to_string(index_dir.join("type")).unwrap_or_default();
+                    if cache_type.trim() == "Instruction" {
+                        continue;
+                    }
+                    let level = read_sysfs_usize(&index_dir.join("level").to_string_lossy());
+                    let id = read_sysfs_usize(&index_dir.join("id").to_string_lossy());
+                    if let (Some(level), Some(id)) = (level, id) {
+                        match llc {
+                            Some((l, _)) if l >= level => {}
+                            _ => llc = Some((level, id)),
+                        }
+                    }
+                }
+                llc.map(|(_, id)| id)
+            }
+            Self::Node | Self::Core => None,
+        }
+    }
+
+    /// Resolve the given topology ids into the set of CPUs they contain
+    fn resolve(self, ids: &[usize]) -> Result<CpuSet, Errno> {
+        let mut set = CpuSet::new();
+        match self {
+            Self::Node => {
+                for id in ids {
+                    let path = format!("{}/node{}/cpulist", SYS_NODE_DIR, id);
+                    let node_set = read_sysfs_list(&path).ok_or(Errno::ENOENT)?;
+                    cpuset_union(&mut set, &node_set)?;
+                }
+                return Ok(set);
+            }
+            Self::Core => {
+                let cores = physical_cores();
+                for id in ids {
+                    let core = cores.get(*id).ok_or(Errno::ENOENT)?;
+                    for cpu in core {
+                        set.set(*cpu)?;
+                    }
+                }
+                return Ok(set);
+            }
+            Self::Socket | Self::Llc => {}
+        }
+        let mut found = vec![false; ids.len()];
+        for cpu in online_cpus() {
+            if let Some(id) = self.cpu_id(cpu) {
+                if let Some(pos) = ids.iter().position(|i| *i == id) {
+                    found[pos] = true;
+                    set.set(cpu)?;
+                }
+            }
+        }
+        if found.contains(&false) {
+            return Err(Errno::ENOENT);
+        }
+        Ok(set)
     }
-    for (idx, c) in mask.chars().rev().enumerate() {
-        if idx >= end {
-            break;
+}
+
+fn read_sysfs_usize(path: &str) -> Option<usize> {
+    fs::read_to_string(path).ok()?.trim().parse::<usize>().ok()
+}
+
+fn read_sysfs_list(path: &str) -> Option<CpuSet> {
+    let content = fs::read_to_string(path).ok()?;
+    let content = content.trim();
+    if content.is_empty() {
+        return Some(CpuSet::new());
+    }
+    cpulist_parse_raw(content).ok()
+}
+
+/// List of online CPUs, all possible CPUs if the online file is unreadable
+fn online_cpus() -> Vec<usize> {
+    let set = read_sysfs_list(&format!("{}/online", SYS_CPU_DIR)).unwrap_or_else(|| {
+        let mut all = CpuSet::new();
+        for cpu in 0..CpuSet::count() {
+            let _ = all.set(cpu);
         }
-        if c == ',' {
+        all
+    });
+    cpuset_cpus(&set)
+}
+
+/// Online CPUs grouped by physical core, ordered by lowest CPU of each core
+fn physical_cores() -> Vec<Vec<usize>> {
+    let online = online_cpus();
+    let mut cores: Vec<Vec<usize>> = Vec::new();
+    for cpu in &online {
+        if cores.iter().any(|core| core.contains(cpu)) {
             continue;
         }
-        let c_ascii: i32 = c as i32;
-        let val = match c {
-            '0'..='9' => c_ascii - '0' as i32,
-            'a'..='f' => c_ascii + 10 - 'a' as i32,
-            'A'..='F' => c_ascii + 10 - 'A' as i32,
-            _ => -1,
+        let path = format!("{}/cpu{}/topology/thread_siblings_list", SYS_CPU_DIR, cpu);
+        let core = match read_sysfs_list(&path) {
+            Some(siblings) => cpuset_cpus(&siblings)
+                .into_iter()
+                .filter(|c| online.contains(c))
+                .collect(),
+            None => vec![*cpu],
         };
-        if val < 0 {
-            return Err(Errno::EINVAL);
+        cores.push(core);
+    }
+    cores
+}
+
+fn cpuset_cpus(set: &CpuSet) -> Vec<usize> {
+    (0..CpuSet::count())
+        .filter(|cpu| set.is_set(*cpu).unwrap_or(false))
+        .collect()
+}
+
+fn cpuset_union(set: &mut CpuSet, other: &CpuSet) -> Result<(), Errno> {
+    for cpu in cpuset_cpus(other) {
+        set.set(cpu)?;
+    }
+    Ok(())
+}
+
+/// Drop every CPU whose lower-numbered hardware thread sibling is also in set
+fn cpuset_drop_smt_siblings(set: &CpuSet) -> Result<CpuSet, Errno> {
+    let mut res = CpuSet::new();
+    let cores = physical_cores();
+    for core in &cores {
+        if let Some(cpu) = core.iter().find(|c| set.is_set(**c).unwrap_or(false)) {
+            res.set(*cpu)?;
         }
-        if val & 1 > 0 {
-            set.set(cpu)?;
+    }
+    // CPUs unknown to sysfs (e.g. offline ones) are kept as requested
+    for cpu in cpuset_cpus(set) {
+        if !cores.iter().any(|core| core.contains(&cpu)) {
+            res.set(cpu)?;
         }
-        if val & 2 > 0 {
-            set.set(cpu + 1)?;
+    }
+    Ok(res)
+}
+
+/// Parse one "a", "a-b" or "a-b:s" range into (first, last, stride)
+fn parse_range(p: &str) -> Result<(usize, usize, usize), Errno> {
+    let range_loc = match p.find('-') {
+        Some(i) => i,
+        None => p.len(),
+    };
+    let step_loc = match p.find(':') {
+        Some(i) => i,
+        None => p.len(),
+    };
+    if range_loc > step_loc {
+        return Err(Errno::EINVAL);
+    }
+    let a_res = p[0..range_loc].parse::<usize>();
+    let b_res = match range_loc == p.len() {
+        true => a_res.clone(),
+        false => p[range_loc + 1..step_loc].parse::<usize>(),
+    };
+    let s_res = match step_loc == p.len() {
+        true => Ok(1),
+        false => p[step_loc + 1..].parse::<usize>(),
+    };
+    match (a_res, b_res, s_res) {
+        (Ok(a), Ok(b), Ok(s)) if a <= b && s > 0 => Ok((a, b, s)),
+        _ => Err(Errno::EINVAL),
+    }
+}
+
+fn range_values(p: &str) -> Result<Vec<usize>, Errno> {
+    let (a, b, s) = parse_range(p)?;
+    Ok((a..=b).step_by(s).collect())
+}
+
+/// Parse a list of plain CPU numbers and ranges, without topology selectors
+fn cpulist_parse_raw(list: &str) -> Result<CpuSet, Errno> {
+    let mut set = CpuSet::new();
+    for p in list.split(',') {
+        for cpu in range_values(p)? {
+            set.set(cpu)?;
         }
-        if val & 4 > 0 {
-            set.set(cpu + 2)?;
+    }
+    Ok(set)
+}
+
+/// Parse a single CPU list token: a range or a topology selector such as `node:0`
+pub fn cpulist_token_parse(token: &str) -> Result<CpuSet, Errno> {
+    match Selector::from_prefix(token) {
+        Some((selector, ids)) => selector.resolve(&range_values(ids)?),
+        None => cpulist_parse_raw(token),
+    }
+}
+
+/// Whether the string uses topology selectors instead of plain numbers
+pub fn is_topology_list(list: &str) -> bool {
+    list.split(',')
+        .any(|p| p == SMT_SIBLINGS_OFF || Selector::from_prefix(p).is_some())
+}
+
+/// Parse mask into CpuSet
+///
+/// Masks may be split into comma separated 32-bit groups, as printed by
+/// the kernel in /proc/<pid>/status on machines with more than 32 CPUs.
+/// Every group but the first must then have all 8 digits.
+pub fn cpumask_parse(mask: &String) -> Result<CpuSet, Errno> {
+    if is_topology_list(mask) {
+        return cpulist_parse(mask);
+    }
+    let mut set = CpuSet::new();
+    let body = mask.strip_prefix("0x").unwrap_or(mask);
+    let groups: Vec<&str> = body.split(',').collect();
+    for (group_idx, group) in groups.iter().rev().enumerate() {
+        let is_first = group_idx + 1 == groups.len();
+        if group.is_empty()
+            || (groups.len() > 1 && group.len() > 8)
+            || (!is_first && group.len() != 8)
+        {
+            return Err(Errno::EINVAL);
         }
-        if val & 8 > 0 {
-            set.set(cpu + 3)?;
+        let mut cpu = group_idx * 32;
+        for c in group.chars().rev() {
+            let val = match c.to_digit(16) {
+                Some(v) => v,
+                None => return Err(Errno::EINVAL),
+            };
+            for bit in 0..4 {
+                if val & (1 << bit) > 0 {
+                    set.set(cpu + bit)?;
+                }
+            }
+            cpu += 4;
         }
-        cpu += 4;
     }
     Ok(set)
 }
 
 /// Parse list into CpuSet
+///
+/// Besides numbers and ranges with an optional stride, every token may be a
+/// topology selector (`node:`, `core:`, `socket:` or `llc:` followed by a
+/// range of ids) or `smt-siblings-off`, which keeps a single hardware thread
+/// per core of the resulting set, or of all online CPUs if used alone.
 pub fn cpulist_parse(list: &String) -> Result<CpuSet, Errno> {
     let mut set = CpuSet::new();
+    let mut smt_off = false;
+    let mut has_cpus = false;
     for p in list.split(',') {
-        let range_loc = match p.find('-') {
-            Some(i) => i,
-            None => p.len(),
-        };
-        let step_loc = match p.find(':') {
-            Some(i) => i,
-            None => p.len(),
-        };
-        if range_loc > step_loc {
-            return Err(Errno::EINVAL);
-        }
-        let a_res = p[0..range_loc].parse::<usize>();
-        let b_res = match range_loc == p.len() {
-            true => a_res.clone(),
-            false => p[range_loc + 1..step_loc].parse::<usize>(),
-        };
-        let s_res = match step_loc == p.len() {
-            true => Ok(1),
-            false => p[step_loc + 1..].parse::<usize>(),
-        };
-        if a_res.is_err() || b_res.is_err() || s_res.is_err() {
-            return Err(Errno::EINVAL);
-        }
-        let (mut a, b, s) = (a_res.unwrap(), b_res.unwrap(), s_res.unwrap());
-        if a > b {
-            return Err(Errno::EINVAL);
+        if p == SMT_SIBLINGS_OFF {
+            smt_off = true;
+            continue;
         }
-        while a <= b {
-            set.set(a)?;
-            a += s;
+        cpuset_union(&mut set, &cpulist_token_parse(p)?)?;
+        has_cpus = true;
+    }
+    if smt_off {
+        if !has_cpus {
+            for cpu in online_cpus() {
+                set.set(cpu)?;
+            }
         }
+        set = cpuset_drop_smt_siblings(&set)?;
     }
     Ok(set)
 }
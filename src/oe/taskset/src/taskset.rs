@@ -6,8 +6,6 @@
 // that was distributed with this source code.
 
 use clap::Command;
-use nix::unistd::Pid;
-use procfs::process::Process;
 use uucore::{error::UResult, help_section, help_usage};
 
 /// Taskset common functions
@@ -25,14 +23,23 @@ const USAGE: &str = help_usage!("taskset.md");
 pub fn oemain(args: impl uucore::Args) -> UResult<()> {
     let mut config: taskset_common::Config =
         taskset_common::parse_taskset_cmd_args(args, ABOUT, USAGE)?;
+    if config.explain {
+        return taskset_common::explain_set(&config);
+    }
+    if let Some(cgroup) = &config.cgroup {
+        let pids = taskset_common::cgroup_tasks(cgroup, config.all_tasks)?;
+        taskset_common::do_taskset_each(&mut config, pids);
+        return Ok(());
+    }
+    if config.tree {
+        let pids = taskset_common::process_tree_tasks(config.pid, config.all_tasks);
+        taskset_common::do_taskset_each(&mut config, pids);
+        return Ok(());
+    }
     if config.all_tasks && config.command.is_none() {
-        let pc = match Process::new(config.pid.into()) {
-            Ok(v) => v,
-            _ => return Ok(()), // Process not exist, we just finish normally.
-        };
-        let pc_tasks_it = pc.tasks().unwrap();
-        for task in pc_tasks_it {
-            config.pid = Pid::from_raw(task.unwrap().tid);
+        // If the process does not exist, we just finish normally.
+        for task in taskset_common::process_tasks(config.pid) {
+            config.pid = task;
             taskset_common::do_taskset(&mut config)?;
         }
     } else {
@@ -5,14 +5,17 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::ffi::CString;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 use nix::errno::Errno;
 use nix::sched::{sched_getaffinity, sched_setaffinity, CpuSet};
 use nix::unistd::{getpid, Pid};
 use procfs::process::Process;
 use uucore::error::{UResult, USimpleError, UUsageError};
-use uucore::{format_usage, show_error};
+use uucore::{format_usage, show, show_error};
 
 use clap::{crate_version, Arg, ArgMatches, Command};
 use libc::EXIT_FAILURE;
@@ -21,6 +24,7 @@ use crate::lib_cpuset;
 
 const EX_EXEC_FAILED: i32 = 126; /* Program located, but not usable. */
 const EX_EXEC_ENOENT: i32 = 127; /* Could not find program to exec.  */
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
 const ADDITIONAL_HELP: &str = "The default behavior is to run a new command:
     taskset 03 sshd -b 1024
 You can retrieve the mask of an existing task:
@@ -30,7 +34,13 @@ Or set it:
 List format uses a comma-separated list instead of a mask:
     taskset -pc 0,3,7-11 700
 Ranges in list format can take a stride argument:
-    e.g. 0-31:2 is equivalent to mask 0x55555555";
+    e.g. 0-31:2 is equivalent to mask 0x55555555
+Lists may select CPUs by topology:
+    taskset -c node:0,smt-siblings-off sshd -b 1024
+Use --explain to print the CPUs a mask or list resolves to.
+Retarget a process with all its descendants, or everything in a cgroup:
+    taskset -a --tree -pc 0-3 700
+    taskset --dry-run --cgroup system.slice/sshd.service -c 0-3";
 
 /// Config
 pub struct Config {
@@ -45,6 +55,16 @@ pub struct Config {
     pub all_tasks: bool,
     /// Use CPU list instead of mask
     pub use_list: bool,
+    /// Only print how the mask or list resolves
+    pub explain: bool,
+    /// The mask or list as given on the command line
+    pub mask_or_list: Option<String>,
+    /// Is --tree option set
+    pub tree: bool,
+    /// Operate on the processes of this cgroup directory
+    pub cgroup: Option<String>,
+    /// Only report the affinity that would be set
+    pub dry_run: bool,
 }
 
 /// Command Options
@@ -59,6 +79,14 @@ pub mod options {
     pub static PID_CMD: &str = "pidcmd";
     ///
     pub static MASK_CPULIST: &str = "mask-cpu-list";
+    ///
+    pub static EXPLAIN: &str = "explain";
+    ///
+    pub static TREE: &str = "tree";
+    ///
+    pub static CGROUP: &str = "cgroup";
+    ///
+    pub static DRY_RUN: &str = "dry-run";
 }
 
 impl Config {
@@ -70,10 +98,23 @@ impl Config {
         let mask_cpulist_option = args_matches.get_one::<String>(options::MASK_CPULIST);
         let pid_set = args_matches.contains_id(options::PID);
         let cpulist_set = args_matches.contains_id(options::CPULIST);
+        let explain = args_matches.contains_id(options::EXPLAIN);
+        let cgroup = args_matches.get_one::<String>(options::CGROUP).cloned();
+        let dry_run = args_matches.contains_id(options::DRY_RUN);
         let mut pid_num = 0;
         let mut command: Option<Vec<String>> = None;
         let mut new_set: Option<CpuSet> = None;
-        if pid_cmd_option.is_some() {
+        if cgroup.is_some() {
+            if pid_cmd_option.is_some() {
+                return Err(UUsageError::new(
+                    EXIT_FAILURE,
+                    "bad usage: --cgroup takes no pid or command",
+                ));
+            }
+            if let Some(mask_cpulist) = mask_cpulist_option {
+                new_set = Some(Self::generate_set(mask_cpulist, cpulist_set)?);
+            }
+        } else if pid_cmd_option.is_some() {
             // Process the first positional argument.
             let mask_cpulist = mask_cpulist_option.unwrap();
             new_set = Some(Self::generate_set(mask_cpulist, cpulist_set)?);
@@ -92,9 +133,17 @@ impl Config {
             }
         } else if mask_cpulist_option.is_some() && pid_set {
             pid_num = mask_cpulist_option.unwrap().parse::<i32>().unwrap();
+        } else if let (true, Some(mask_cpulist)) = (explain, mask_cpulist_option) {
+            new_set = Some(Self::generate_set(mask_cpulist, cpulist_set)?);
         } else {
             return Err(UUsageError::new(EXIT_FAILURE, "bad usage"));
         }
+        if dry_run && command.is_some() {
+            return Err(UUsageError::new(
+                EXIT_FAILURE,
+                "bad usage: --dry-run cannot be used to launch a command",
+            ));
+        }
 
         Ok(Self {
             pid: Pid::from_raw(pid_num),
@@ -102,6 +151,11 @@ impl Config {
             command,
             all_tasks: args_matches.contains_id(options::ALLTASKS),
             use_list: cpulist_set,
+            explain,
+            mask_or_list: mask_cpulist_option.cloned(),
+            tree: args_matches.contains_id(options::TREE),
+            cgroup,
+            dry_run,
         })
     }
 
@@ -141,22 +195,32 @@ pub fn do_taskset(ts: &mut Config) -> UResult<()> {
                 ))
             }
         };
-        print_affinity(ts, set, false)?;
+        print_affinity(ts, set, "current")?;
     }
 
     if ts.new_set.is_none() {
         return Ok(());
     }
 
+    if ts.dry_run {
+        if pid_is_valid && no_setaffinity(ts.pid) {
+            return Err(USimpleError::new(
+                EXIT_FAILURE,
+                format!(
+                    "pid {}'s affinity cannot be set due to PF_NO_SETAFFINITY flag set",
+                    ts.pid
+                ),
+            ));
+        }
+        return print_affinity(ts, ts.new_set.unwrap(), "planned");
+    }
+
     /* set new mask */
     if let Err(mut e) = sched_setaffinity(ts.pid, ts.new_set.as_ref().unwrap()) {
-        if pid_is_valid {
-            let pc_stat = Process::new(ts.pid.as_raw()).unwrap().stat().unwrap();
-            if e != Errno::EPERM && (pc_stat.flags & libc::PF_NO_SETAFFINITY as u32) > 0 {
-                // Based on taskset 2.39.3 logic
-                show_error!("affinity cannot be set due to PF_NO_SETAFFINITY flag set");
-                e = Errno::EINVAL;
-            }
+        if pid_is_valid && e != Errno::EPERM && no_setaffinity(ts.pid) {
+            // Based on taskset 2.39.3 logic
+            show_error!("affinity cannot be set due to PF_NO_SETAFFINITY flag set");
+            e = Errno::EINVAL;
         }
         return Err(USimpleError::new(
             EXIT_FAILURE,
@@ -175,31 +239,180 @@ pub fn do_taskset(ts: &mut Config) -> UResult<()> {
                 ))
             }
         };
-        print_affinity(ts, set, true)?;
+        print_affinity(ts, set, "new")?;
     }
     Ok(())
 }
 
+/// Print the CPUs each list token and the whole mask or list resolve to
+pub fn explain_set(ts: &Config) -> UResult<()> {
+    let (spec, set) = match (&ts.mask_or_list, ts.new_set) {
+        (Some(spec), Some(set)) => (spec, set),
+        _ => return Err(UUsageError::new(EXIT_FAILURE, "no mask or list to explain")),
+    };
+    if ts.use_list || lib_cpuset::is_topology_list(spec) {
+        for token in spec.split(',') {
+            if token == lib_cpuset::SMT_SIBLINGS_OFF {
+                println!("{}: keep one thread per core", token);
+                continue;
+            }
+            // The whole list parsed already, so every token does too
+            let token_set = lib_cpuset::cpulist_token_parse(token).unwrap();
+            println!("{}: {}", token, lib_cpuset::cpulist_create(token_set)?);
+        }
+    }
+    println!("list: {}", lib_cpuset::cpulist_create(set)?);
+    println!("mask: {}", lib_cpuset::cpumask_create(set)?);
+    Ok(())
+}
+
+/// Whether the kernel refuses affinity changes of pid (e.g. per-CPU kthreads)
+fn no_setaffinity(pid: Pid) -> bool {
+    match Process::new(pid.as_raw()).and_then(|pc| pc.stat()) {
+        Ok(stat) => (stat.flags & libc::PF_NO_SETAFFINITY as u32) > 0,
+        Err(_) => false,
+    }
+}
+
 /// Parse raw CpuSet into string then print it
-fn print_affinity(ts: &Config, set: CpuSet, isnew: bool) -> UResult<()> {
+fn print_affinity(ts: &Config, set: CpuSet, state: &str) -> UResult<()> {
     if ts.use_list {
         let list = lib_cpuset::cpulist_create(set)?;
-        if isnew {
-            println!("pid {}'s new affinity list: {}", ts.pid, list);
-        } else {
-            println!("pid {}'s current affinity list: {}", ts.pid, list);
-        }
+        println!("pid {}'s {} affinity list: {}", ts.pid, state, list);
     } else {
         let mask = lib_cpuset::cpumask_create(set)?;
-        if isnew {
-            println!("pid {}'s new affinity mask: {}", ts.pid, mask);
-        } else {
-            println!("pid {}'s current affinity mask: {}", ts.pid, mask);
+        println!("pid {}'s {} affinity mask: {}", ts.pid, state, mask);
+    }
+    Ok(())
+}
+
+/// Run do_taskset for every pid, reporting failures without stopping
+pub fn do_taskset_each(ts: &mut Config, pids: Vec<Pid>) {
+    for pid in pids {
+        // Tasks may exit while we walk them, skip those silently
+        if Process::new(pid.as_raw()).is_err() {
+            continue;
+        }
+        ts.pid = pid;
+        if let Err(e) = do_taskset(ts) {
+            show!(e);
+        }
+    }
+}
+
+/// Thread ids of process pid, empty if it does not exist
+fn process_tids(pid: i32) -> Vec<i32> {
+    match Process::new(pid).and_then(|pc| pc.tasks()) {
+        Ok(tasks) => tasks.flatten().map(|task| task.tid).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// All tasks (threads) of process pid, empty if it does not exist
+pub fn process_tasks(pid: Pid) -> Vec<Pid> {
+    process_tids(pid.as_raw())
+        .into_iter()
+        .map(Pid::from_raw)
+        .collect()
+}
+
+/// Children of process pid, from /proc/<pid>/task/*/children if the kernel
+/// provides it, otherwise from the parent pids of all processes
+fn process_children(
+    pid: i32,
+    tids: &[i32],
+    ppid_map: &mut Option<HashMap<i32, Vec<i32>>>,
+) -> Vec<i32> {
+    let mut children = Vec::new();
+    for tid in tids {
+        let path = format!("/proc/{}/task/{}/children", pid, tid);
+        match fs::read_to_string(path) {
+            Ok(content) => children.extend(
+                content
+                    .split_whitespace()
+                    .filter_map(|c| c.parse::<i32>().ok()),
+            ),
+            Err(_) => {
+                let map = ppid_map.get_or_insert_with(|| {
+                    let mut map: HashMap<i32, Vec<i32>> = HashMap::new();
+                    if let Ok(procs) = procfs::process::all_processes() {
+                        for stat in procs.flatten().filter_map(|pc| pc.stat().ok()) {
+                            map.entry(stat.ppid).or_default().push(stat.pid);
+                        }
+                    }
+                    map
+                });
+                return map.get(&pid).cloned().unwrap_or_default();
+            }
+        }
+    }
+    children
+}
+
+/// Processes of the tree rooted at pid, or all their threads if all_tasks is set
+pub fn process_tree_tasks(pid: Pid, all_tasks: bool) -> Vec<Pid> {
+    let mut ppid_map = None;
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::from([pid.as_raw()]);
+    let mut res = Vec::new();
+    while let Some(p) = queue.pop_front() {
+        if !seen.insert(p) {
+            continue;
+        }
+        let tids = process_tids(p);
+        if tids.is_empty() {
+            continue;
+        }
+        match all_tasks {
+            true => res.extend(tids.iter().map(|tid| Pid::from_raw(*tid))),
+            false => res.push(Pid::from_raw(p)),
+        }
+        queue.extend(process_children(p, &tids, &mut ppid_map));
+    }
+    res
+}
+
+/// Pids listed in cgroup.procs of dir and all cgroups below it
+fn read_cgroup_procs(dir: &Path, pids: &mut Vec<i32>) -> std::io::Result<()> {
+    let content = fs::read_to_string(dir.join("cgroup.procs"))?;
+    pids.extend(
+        content
+            .split_whitespace()
+            .filter_map(|p| p.parse::<i32>().ok()),
+    );
+    for entry in fs::read_dir(dir)?.flatten() {
+        if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+            // A child cgroup may be removed meanwhile, that is not an error
+            let _ = read_cgroup_procs(&entry.path(), pids);
         }
     }
     Ok(())
 }
 
+/// Processes in the cgroup at path (absolute, or relative to /sys/fs/cgroup)
+/// and its descendants, or all their threads if all_tasks is set
+pub fn cgroup_tasks(path: &str, all_tasks: bool) -> UResult<Vec<Pid>> {
+    let dir = match Path::new(path).join("cgroup.procs").exists() {
+        true => PathBuf::from(path),
+        false => Path::new(CGROUP_ROOT).join(path.trim_start_matches('/')),
+    };
+    let mut pids = Vec::new();
+    if let Err(e) = read_cgroup_procs(&dir, &mut pids) {
+        return Err(USimpleError::new(
+            EXIT_FAILURE,
+            format!("cannot read cgroup {}: {}", path, e),
+        ));
+    }
+    let mut res = Vec::new();
+    for pid in pids {
+        match all_tasks {
+            true => res.extend(process_tids(pid).into_iter().map(Pid::from_raw)),
+            false => res.push(Pid::from_raw(pid)),
+        }
+    }
+    Ok(res)
+}
+
 /// Called if an error produced, format error string
 fn err_affinity(mut pid: Pid, is_set: bool, err: Errno) -> String {
     if pid.as_raw() == 0 {
@@ -272,6 +485,30 @@ pub fn taskset_app<'a>(about: &'a str, usage: &'a str) -> Command<'a> {
                 .long(options::CPULIST)
                 .help("display and specify cpus in list format"),
         )
+        .arg(
+            Arg::new(options::EXPLAIN)
+                .long(options::EXPLAIN)
+                .help("print the CPUs the mask or list resolves to and exit"),
+        )
+        .arg(
+            Arg::new(options::TREE)
+                .long(options::TREE)
+                .requires(options::PID)
+                .help("operate on the given pid and all its descendants"),
+        )
+        .arg(
+            Arg::new(options::CGROUP)
+                .long(options::CGROUP)
+                .takes_value(true)
+                .value_name("path")
+                .conflicts_with_all(&[options::PID, options::TREE])
+                .help("operate on all processes in a cgroup and its children"),
+        )
+        .arg(
+            Arg::new(options::DRY_RUN)
+                .long(options::DRY_RUN)
+                .help("show the affinity that would be set without changing it"),
+        )
         .after_help(ADDITIONAL_HELP)
         .arg(Arg::new(options::MASK_CPULIST).index(1).hide(true))
         .arg(
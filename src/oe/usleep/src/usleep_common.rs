@@ -5,16 +5,17 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
+use std::io::Error;
 use std::num::IntErrorKind;
-use std::thread;
 use std::time::Duration;
 
-use uucore::error::{UResult, UUsageError};
+use uucore::error::{UResult, USimpleError, UUsageError};
 use uucore::format_usage;
 use uucore::msg_log::warnb;
 use uucore::pretty_float::pretty_float;
 
 use clap::{crate_version, Arg, Command};
+use libc::EXIT_FAILURE;
 
 /// usleep cmd parse error code.
 pub static USLEEP_CMD_PARSE_ERROR: i32 = 2;
@@ -22,14 +23,36 @@ pub static USLEEP_CMD_PARSE_ERROR: i32 = 2;
 /// default microseconds value.
 const DEFAULT_MICROSECONDS_VALUE: u64 = 1;
 
+/// Unit suffixes of a sleep interval, with their length in nanoseconds.
+const UNIT_SUFFIXES: [(&str, f64); 5] = [
+    ("ns", 1.0),
+    ("us", 1e3),
+    ("ms", 1e6),
+    ("s", 1e9),
+    ("m", 60e9),
+];
+
+/// Clocks usleep can sleep on.
+const CLOCKS: [(&str, libc::clockid_t); 3] = [
+    ("monotonic", libc::CLOCK_MONOTONIC),
+    ("realtime", libc::CLOCK_REALTIME),
+    ("boottime", libc::CLOCK_BOOTTIME),
+];
+
 /// Config.
 pub struct Config {
     ///
     pub usage: bool,
     ///
     pub oot: bool,
-    ///
-    pub microseconds: u64,
+    /// Relative time to sleep.
+    pub duration: Duration,
+    /// Absolute deadline on `clock`, instead of `duration`.
+    pub until: Option<Duration>,
+    /// Clock to measure the sleep with.
+    pub clock: libc::clockid_t,
+    /// Print the slept time and overshoot.
+    pub report: bool,
 }
 
 /// options.
@@ -44,12 +67,18 @@ pub mod options {
     pub static OOT: &str = "oot";
     ///
     pub static MICROSECONDS: &str = "microseconds";
+    /// Option --until
+    pub static UNTIL: &str = "until";
+    /// Option --clock
+    pub static CLOCK: &str = "clock";
+    /// Option --report
+    pub static REPORT: &str = "report";
 }
 
 impl Config {
     ///
     pub fn from(options: &clap::ArgMatches) -> UResult<Self> {
-        let count: u64 = match options.get_many::<String>(options::MICROSECONDS) {
+        let duration = match options.get_many::<String>(options::MICROSECONDS) {
             Some(mut values) => {
                 let value = values.next().unwrap();
                 if let Some(_) = values.next() {
@@ -58,32 +87,137 @@ impl Config {
                         format!("exactly one argument (number of microseconds) must be used"),
                     ));
                 }
-
-                let (radix, skip) = match value {
-                    v if v.starts_with("0x") || v.starts_with("0X") => (16, 2),
-                    v if v.starts_with("0") => (8, 1),
-                    _ => (10, 0),
-                };
-
-                match u64::from_str_radix(&value[skip..], radix) {
-                    Ok(n) => n,
-                    Err(e) => match e.kind() {
-                        IntErrorKind::PosOverflow => u64::MAX,
-                        _ => 0u64,
-                    },
+                match parse_interval(value) {
+                    Some(d) => d,
+                    None => Duration::from_micros(parse_microseconds(value)),
                 }
             }
-            None => DEFAULT_MICROSECONDS_VALUE,
+            None => Duration::from_micros(DEFAULT_MICROSECONDS_VALUE),
+        };
+
+        let until = match options.get_one::<String>(options::UNTIL) {
+            Some(value) => match parse_deadline(value) {
+                Some(d) => Some(d),
+                None => {
+                    return Err(UUsageError::new(
+                        USLEEP_CMD_PARSE_ERROR,
+                        format!("invalid deadline '{}'", value),
+                    ))
+                }
+            },
+            None => None,
         };
 
+        // Deadlines are wall-clock times unless another clock is asked for.
+        let clock_name = match options.get_one::<String>(options::CLOCK) {
+            Some(v) => v.as_str(),
+            None if until.is_some() => "realtime",
+            None => "monotonic",
+        };
+        // possible_values() guarantees a known clock name.
+        let clock = CLOCKS.iter().find(|(n, _)| *n == clock_name).unwrap().1;
+
         Ok(Self {
             usage: options.contains_id(options::USAGE),
             oot: options.contains_id(options::OOT),
-            microseconds: count,
+            duration,
+            until,
+            clock,
+            report: options.contains_id(options::REPORT),
         })
     }
 }
 
+/// Parse a plain number of microseconds like C usleep does with strtoul():
+/// hexadecimal with 0x, octal with a leading 0, 0 if invalid.
+fn parse_microseconds(value: &str) -> u64 {
+    let (radix, skip) = match value {
+        v if v.starts_with("0x") || v.starts_with("0X") => (16, 2),
+        v if v.starts_with("0") => (8, 1),
+        _ => (10, 0),
+    };
+
+    match u64::from_str_radix(&value[skip..], radix) {
+        Ok(n) => n,
+        Err(e) => match e.kind() {
+            IntErrorKind::PosOverflow => u64::MAX,
+            _ => 0u64,
+        },
+    }
+}
+
+/// Parse a decimal number with an optional unit suffix (microseconds if
+/// there is none), like "1.5ms" or "2.5e3". Returns None for anything else,
+/// including plain integers, which keep their C usleep meaning.
+fn parse_interval(value: &str) -> Option<Duration> {
+    let (number, scale) = match UNIT_SUFFIXES.iter().find(|(u, _)| value.ends_with(u)) {
+        Some((unit, scale)) => (&value[..value.len() - unit.len()], *scale),
+        None if value.contains(['.', 'e', 'E']) && !value.to_lowercase().starts_with("0x") => {
+            (value, 1e3)
+        }
+        None => return None,
+    };
+    if !number.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    match number.parse::<f64>() {
+        Ok(n) if n.is_finite() => Some(Duration::from_nanos((n * scale).round() as u64)),
+        _ => None,
+    }
+}
+
+/// Parse an absolute deadline: seconds on the chosen clock, optionally
+/// prefixed with '@' and suffixed with a unit, or a UTC timestamp such as
+/// 2024-05-01T12:00:00.5Z for the realtime clock.
+fn parse_deadline(value: &str) -> Option<Duration> {
+    let value = value.strip_prefix('@').unwrap_or(value);
+    if let Some(d) = parse_utc_timestamp(value) {
+        return Some(d);
+    }
+    if UNIT_SUFFIXES.iter().any(|(u, _)| value.ends_with(u)) {
+        return parse_interval(value);
+    }
+    parse_interval(&format!("{}s", value))
+}
+
+/// Parse YYYY-MM-DDTHH:MM:SS[.frac]Z into the time since the Unix epoch.
+fn parse_utc_timestamp(value: &str) -> Option<Duration> {
+    let value = value.strip_suffix('Z')?;
+    let (date, time) = value.split_once(['T', ' '])?;
+    let date: Vec<i64> = date
+        .split('-')
+        .map(|f| f.parse::<i64>().ok())
+        .collect::<Option<_>>()?;
+    let (hms, frac) = match time.split_once('.') {
+        Some((hms, frac)) => (hms, format!("0.{}", frac).parse::<f64>().ok()?),
+        None => (time, 0.0),
+    };
+    let hms: Vec<i64> = hms
+        .split(':')
+        .map(|f| f.parse::<i64>().ok())
+        .collect::<Option<_>>()?;
+    if date.len() != 3 || hms.len() != 3 {
+        return None;
+    }
+    let (y, m, d) = (date[0], date[1], date[2]);
+    if !(1..=12).contains(&m) || !(1..=31).contains(&d) || hms[0] > 23 || hms[1] > 59 || hms[2] > 60
+    {
+        return None;
+    }
+    // Days since the epoch of the proleptic Gregorian calendar date.
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((m + 9) % 12) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+    let secs = days * 86400 + hms[0] * 3600 + hms[1] * 60 + hms[2];
+    if secs < 0 {
+        return None;
+    }
+    Some(Duration::from_secs(secs as u64) + Duration::from_secs_f64(frac))
+}
+
 ///
 pub fn parse_usleep_cmd_args(args: impl uucore::Args, about: &str, usage: &str) -> UResult<Config> {
     let command = usleep_app(about, usage);
@@ -125,6 +259,30 @@ pub fn usleep_app<'a>(about: &'a str, usage: &'a str) -> Command<'a> {
                 .help("oot says hey!")
                 .display_order(1),
         )
+        .arg(
+            Arg::new(options::UNTIL)
+                .long(options::UNTIL)
+                .takes_value(true)
+                .value_name("time")
+                .conflicts_with(options::MICROSECONDS)
+                .help("Sleep until an absolute time of the clock")
+                .display_order(4),
+        )
+        .arg(
+            Arg::new(options::CLOCK)
+                .long(options::CLOCK)
+                .takes_value(true)
+                .value_name("clock")
+                .possible_values(CLOCKS.iter().map(|(n, _)| *n))
+                .help("Clock to sleep on")
+                .display_order(5),
+        )
+        .arg(
+            Arg::new(options::REPORT)
+                .long(options::REPORT)
+                .help("Print the slept time and the overshoot")
+                .display_order(6),
+        )
         .arg(
             Arg::new(options::MICROSECONDS)
                 .index(1)
@@ -136,7 +294,9 @@ pub fn usleep_app<'a>(about: &'a str, usage: &'a str) -> Command<'a> {
 pub fn handle_input(config: Config) -> UResult<()> {
     if config.usage {
         println!(
-            "Usage: usleep [-vo?] [-v|--version] [-o|--oot] [-?|--help] [--usage] [microseconds]"
+            "Usage: usleep [-vo?] [-v|--version] [-o|--oot] [-?|--help] [--usage] \
+             [--clock=clock] [--report] [microseconds | interval]\n       \
+             usleep [--clock=clock] [--report] --until=time"
         );
         return Ok(());
     }
@@ -146,12 +306,70 @@ pub fn handle_input(config: Config) -> UResult<()> {
         return Ok(());
     }
 
-    warnb("warning: usleep is deprecated, and will be removed in near future!");
-    warnb(&format!(
-        "warning: use \"sleep {}\" instead...",
-        pretty_float(config.microseconds as f64 / 1e6),
-    ));
+    let start = clock_now(config.clock)?;
+    let deadline = match config.until {
+        Some(until) => until,
+        None => {
+            warnb("warning: usleep is deprecated, and will be removed in near future!");
+            warnb(&format!(
+                "warning: use \"sleep {}\" instead...",
+                pretty_float(config.duration.as_nanos() as f64 / 1e9),
+            ));
+            start.saturating_add(config.duration)
+        }
+    };
+
+    sleep_until(config.clock, deadline)?;
 
-    thread::sleep(Duration::from_micros(config.microseconds));
+    if config.report {
+        let end = clock_now(config.clock)?;
+        let slept = end.saturating_sub(start);
+        let overshoot = end.as_nanos() as i128 - deadline.as_nanos() as i128;
+        println!(
+            "slept {:.9} s, overshoot {:.3} us",
+            slept.as_secs_f64(),
+            overshoot as f64 / 1e3
+        );
+    }
     Ok(())
 }
+
+fn clock_error(what: &str, err: i32) -> Box<dyn uucore::error::UError> {
+    USimpleError::new(
+        EXIT_FAILURE,
+        format!("{}: {}", what, Error::from_raw_os_error(err)),
+    )
+}
+
+/// Current time of clock.
+fn clock_now(clock: libc::clockid_t) -> UResult<Duration> {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    if unsafe { libc::clock_gettime(clock, &mut ts) } != 0 {
+        return Err(clock_error(
+            "clock_gettime failed",
+            Error::last_os_error().raw_os_error().unwrap_or(0),
+        ));
+    }
+    Ok(Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+}
+
+/// Sleep until clock reaches deadline. As the deadline is absolute, a sleep
+/// interrupted by a signal just resumes with the remaining time.
+fn sleep_until(clock: libc::clockid_t, deadline: Duration) -> UResult<()> {
+    let ts = libc::timespec {
+        tv_sec: deadline.as_secs().min(libc::time_t::MAX as u64) as libc::time_t,
+        tv_nsec: deadline.subsec_nanos() as libc::c_long,
+    };
+    loop {
+        let ret =
+            unsafe { libc::clock_nanosleep(clock, libc::TIMER_ABSTIME, &ts, std::ptr::null_mut()) };
+        match ret {
+            0 => return Ok(()),
+            libc::EINTR => continue,
+            err => return Err(clock_error("clock_nanosleep failed", err)),
+        }
+    }
+}
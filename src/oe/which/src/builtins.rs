@@ -0,0 +1,256 @@
+//! This file is part of the easybox package.
+//
+// (c) Jiale Xiao <xiao-xjle@qq.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+//! Reserved words and builtin commands of the shells selectable with --shell
+
+/// Based on the reserved words in the bash manual and `compgen -k`
+const BASH_KEYWORDS: &[&str] = &[
+    "!", "[[", "]]", "case", "coproc", "do", "done", "elif", "else", "esac", "fi", "for",
+    "function", "if", "in", "select", "then", "time", "until", "while", "{", "}",
+];
+
+/// Based on `compgen -b` of bash 5
+const BASH_BUILTINS: &[&str] = &[
+    ".",
+    ":",
+    "[",
+    "alias",
+    "bg",
+    "bind",
+    "break",
+    "builtin",
+    "caller",
+    "cd",
+    "command",
+    "compgen",
+    "complete",
+    "compopt",
+    "continue",
+    "declare",
+    "dirs",
+    "disown",
+    "echo",
+    "enable",
+    "eval",
+    "exec",
+    "exit",
+    "export",
+    "false",
+    "fc",
+    "fg",
+    "getopts",
+    "hash",
+    "help",
+    "history",
+    "jobs",
+    "kill",
+    "let",
+    "local",
+    "logout",
+    "mapfile",
+    "popd",
+    "printf",
+    "pushd",
+    "pwd",
+    "read",
+    "readarray",
+    "readonly",
+    "return",
+    "set",
+    "shift",
+    "shopt",
+    "source",
+    "suspend",
+    "test",
+    "times",
+    "trap",
+    "true",
+    "type",
+    "typeset",
+    "ulimit",
+    "umask",
+    "unalias",
+    "unset",
+    "wait",
+];
+
+/// Based on the reserved words in dash(1)
+const DASH_KEYWORDS: &[&str] = &[
+    "!", "case", "do", "done", "elif", "else", "esac", "fi", "for", "if", "in", "then", "until",
+    "while", "{", "}",
+];
+
+/// Based on the builtins listed in dash(1)
+const DASH_BUILTINS: &[&str] = &[
+    ".", ":", "[", "alias", "bg", "break", "cd", "chdir", "command", "continue", "echo", "eval",
+    "exec", "exit", "export", "false", "fg", "getopts", "hash", "jobs", "kill", "local", "printf",
+    "pwd", "read", "readonly", "return", "set", "shift", "test", "times", "trap", "true", "type",
+    "ulimit", "umask", "unalias", "unset", "wait",
+];
+
+/// Based on the reserved words in zshmisc(1)
+const ZSH_KEYWORDS: &[&str] = &[
+    "!",
+    "[[",
+    "]]",
+    "case",
+    "coproc",
+    "do",
+    "done",
+    "elif",
+    "else",
+    "end",
+    "esac",
+    "fi",
+    "for",
+    "foreach",
+    "function",
+    "if",
+    "in",
+    "nocorrect",
+    "repeat",
+    "select",
+    "then",
+    "time",
+    "until",
+    "while",
+    "{",
+    "}",
+];
+
+/// Based on zshbuiltins(1), without the builtins of optional modules
+const ZSH_BUILTINS: &[&str] = &[
+    "-",
+    ".",
+    ":",
+    "[",
+    "alias",
+    "autoload",
+    "bg",
+    "bindkey",
+    "break",
+    "builtin",
+    "bye",
+    "cd",
+    "chdir",
+    "command",
+    "continue",
+    "declare",
+    "dirs",
+    "disable",
+    "disown",
+    "echo",
+    "emulate",
+    "enable",
+    "eval",
+    "exec",
+    "exit",
+    "export",
+    "false",
+    "fc",
+    "fg",
+    "float",
+    "functions",
+    "getln",
+    "getopts",
+    "hash",
+    "history",
+    "integer",
+    "jobs",
+    "kill",
+    "let",
+    "limit",
+    "local",
+    "log",
+    "logout",
+    "noglob",
+    "popd",
+    "print",
+    "printf",
+    "pushd",
+    "pushln",
+    "pwd",
+    "r",
+    "read",
+    "readonly",
+    "rehash",
+    "return",
+    "sched",
+    "set",
+    "setopt",
+    "shift",
+    "source",
+    "suspend",
+    "test",
+    "times",
+    "trap",
+    "true",
+    "ttyctl",
+    "type",
+    "typeset",
+    "ulimit",
+    "umask",
+    "unalias",
+    "unfunction",
+    "unhash",
+    "unlimit",
+    "unset",
+    "unsetopt",
+    "vared",
+    "wait",
+    "whence",
+    "where",
+    "which",
+    "zcompile",
+    "zle",
+    "zmodload",
+    "zparseopts",
+    "zstyle",
+];
+
+/// Shells whose builtins and keywords which knows
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Shell {
+    /// GNU bash
+    Bash,
+    /// The Debian Almquist shell
+    Dash,
+    /// The Z shell
+    Zsh,
+}
+
+impl Shell {
+    /// Get shell from its name, or the path of its binary
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.rsplit('/').next().unwrap_or(name) {
+            "bash" => Some(Self::Bash),
+            "dash" | "sh" => Some(Self::Dash),
+            "zsh" => Some(Self::Zsh),
+            _ => None,
+        }
+    }
+
+    /// Whether `name` is a reserved word of the shell
+    pub fn is_keyword(self, name: &str) -> bool {
+        match self {
+            Self::Bash => BASH_KEYWORDS,
+            Self::Dash => DASH_KEYWORDS,
+            Self::Zsh => ZSH_KEYWORDS,
+        }
+        .contains(&name)
+    }
+
+    /// Whether `name` is a builtin command of the shell
+    pub fn is_builtin(self, name: &str) -> bool {
+        match self {
+            Self::Bash => BASH_BUILTINS,
+            Self::Dash => DASH_BUILTINS,
+            Self::Zsh => ZSH_BUILTINS,
+        }
+        .contains(&name)
+    }
+}
@@ -0,0 +1,617 @@
+//! This file is part of the easybox package.
+//
+// (c) Jiale Xiao <xiao-xjle@qq.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+//! Tokenizer for the aliases and functions printed by bash, zsh and fish
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+use crate::builtins::Shell;
+
+/// Prefix of the lines printed by bash's `hash -l`
+const HASH_LINE_PREFIX: &str = "builtin hash -p ";
+
+/// Operators of the shell, longest first so that they match greedily
+const OPERATORS: &[&str] = &[
+    "<<<", "<<-", "&>>", ";;&", "&&", "||", ";;", ";&", "|&", "<<", ">>", "<&", ">&", "&>", ">|",
+    ";", "&", "|", "(", ")", "<", ">",
+];
+
+/// Operators which end a command, the others are redirections
+const SEPARATORS: &[&str] = &[";", ";;", ";&", ";;&", "&", "&&", "|", "||", "|&", "(", ")"];
+
+/// Reserved words which are followed by another command in bash and zsh
+const POSIX_PREFIXES: &[&str] = &[
+    "!", "{", "do", "elif", "else", "if", "then", "time", "until", "while",
+];
+
+/// Reserved words which are followed by another command in fish
+const FISH_PREFIXES: &[&str] = &[
+    "!", "and", "begin", "else", "if", "not", "or", "time", "while",
+];
+
+/// Commands which open a block closed by `end` in fish
+const FISH_BLOCKS: &[&str] = &["begin", "for", "function", "if", "switch", "while"];
+
+/// Quoting rules of the shell which printed the input
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Dialect {
+    /// bash and zsh
+    Posix,
+    /// fish, whose single quotes know backslash escapes
+    Fish,
+}
+
+/// A token of shell input
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Token {
+    /// A word with its quotes removed, and whether any of it was quoted
+    Word(String, bool),
+    /// An operator such as `;`, `|`, `&&` or `<<`
+    Op(&'static str),
+}
+
+impl Token {
+    fn text(&self) -> &str {
+        match self {
+            Self::Word(text, _) => text,
+            Self::Op(op) => op,
+        }
+    }
+
+    fn is_bare(&self, word: &str) -> bool {
+        matches!(self, Self::Word(text, false) if text == word)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Quote {
+    Single,
+    Double,
+    /// bash's `$'...'`
+    Ansi,
+    Backtick,
+}
+
+/// Splits lines into tokens, carrying open quotes, line continuations and
+/// here documents over to the following lines
+pub struct Lexer {
+    dialect: Dialect,
+    tokens: Vec<Token>,
+    word: Option<(String, bool)>,
+    quote: Option<Quote>,
+    continued: bool,
+    /// The next word is the delimiter of a here document
+    heredoc_word: Option<bool>,
+    /// Delimiters of the here documents started on the current line
+    heredocs: Vec<(String, bool)>,
+    /// Delimiters of the here documents whose bodies are being read
+    heredoc_bodies: VecDeque<(String, bool)>,
+}
+
+impl Lexer {
+    /// Create a lexer for the input of dialect
+    pub fn new(dialect: Dialect) -> Self {
+        Self {
+            dialect,
+            tokens: Vec::new(),
+            word: None,
+            quote: None,
+            continued: false,
+            heredoc_word: None,
+            heredocs: Vec::new(),
+            heredoc_bodies: VecDeque::new(),
+        }
+    }
+
+    /// The tokens of a single line, ignoring whatever is left open at its end
+    pub fn line_tokens(line: &str, dialect: Dialect) -> Vec<Token> {
+        let mut lexer = Self::new(dialect);
+        lexer.push_line(line);
+        lexer.finish_word();
+        lexer.tokens
+    }
+
+    /// Whether the lines pushed so far make up complete commands
+    pub fn is_complete(&self) -> bool {
+        self.quote.is_none() && !self.continued && self.heredoc_bodies.is_empty()
+    }
+
+    /// What is left open by the lines pushed so far
+    pub fn open_construct(&self) -> &'static str {
+        if self.quote.is_some() {
+            "unterminated quoted string"
+        } else if !self.heredoc_bodies.is_empty() {
+            "unterminated here-document"
+        } else {
+            "unexpected end of input after '\\'"
+        }
+    }
+
+    /// Take the tokens of the complete commands
+    pub fn take_tokens(&mut self) -> Vec<Token> {
+        self.finish_word();
+        std::mem::take(&mut self.tokens)
+    }
+
+    /// Tokenize the next line of input, without its newline
+    pub fn push_line(&mut self, line: &str) {
+        if let Some((delimiter, strip_tabs)) = self.heredoc_bodies.front() {
+            let body_line = if *strip_tabs {
+                line.trim_start_matches('\t')
+            } else {
+                line
+            };
+            if body_line == delimiter {
+                self.heredoc_bodies.pop_front();
+            }
+            return;
+        }
+        let mut joined = self.continued;
+        self.continued = false;
+        if self.quote.is_some() && !joined {
+            self.push_char('\n', true);
+        }
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            joined = false;
+            match self.quote {
+                Some(Quote::Single) => match c {
+                    '\'' => self.quote = None,
+                    '\\' if self.dialect == Dialect::Fish
+                        && matches!(chars.peek(), Some('\'' | '\\')) =>
+                    {
+                        let c = chars.next().unwrap_or(c);
+                        self.push_char(c, true);
+                    }
+                    c => self.push_char(c, true),
+                },
+                Some(Quote::Double) => match c {
+                    '"' => self.quote = None,
+                    '\\' => match chars.next() {
+                        Some(c @ ('"' | '\\' | '$' | '`')) => self.push_char(c, true),
+                        Some(c) => {
+                            self.push_char('\\', true);
+                            self.push_char(c, true);
+                        }
+                        None => joined = true,
+                    },
+                    c => self.push_char(c, true),
+                },
+                Some(Quote::Ansi) => match c {
+                    '\'' => self.quote = None,
+                    '\\' => match chars.next() {
+                        Some('n') => self.push_char('\n', true),
+                        Some('t') => self.push_char('\t', true),
+                        Some(c) => self.push_char(c, true),
+                        None => self.push_char('\\', true),
+                    },
+                    c => self.push_char(c, true),
+                },
+                Some(Quote::Backtick) => match c {
+                    '`' => {
+                        self.quote = None;
+                        self.push_char(c, true);
+                    }
+                    '\\' => {
+                        self.push_char(c, true);
+                        if let Some(c) = chars.next() {
+                            self.push_char(c, true);
+                        }
+                    }
+                    c => self.push_char(c, true),
+                },
+                None => match c {
+                    c if c.is_whitespace() => self.finish_word(),
+                    '#' if self.word.is_none() => break,
+                    '\'' => self.open_quote(Quote::Single),
+                    '"' => self.open_quote(Quote::Double),
+                    '`' if self.dialect == Dialect::Posix => {
+                        self.open_quote(Quote::Backtick);
+                        self.push_char(c, true);
+                    }
+                    '$' if self.dialect == Dialect::Posix && chars.peek() == Some(&'\'') => {
+                        chars.next();
+                        self.open_quote(Quote::Ansi);
+                    }
+                    '\\' => match chars.next() {
+                        Some(c) => self.push_char(c, true),
+                        None => joined = true,
+                    },
+                    ';' | '&' | '|' | '(' | ')' | '<' | '>' => {
+                        self.finish_word();
+                        let rest: String = std::iter::once(c).chain(chars.clone()).collect();
+                        let op = OPERATORS
+                            .iter()
+                            .find(|op| rest.starts_with(**op))
+                            .copied()
+                            .unwrap_or(";");
+                        for _ in 1..op.len() {
+                            chars.next();
+                        }
+                        if self.dialect == Dialect::Posix && (op == "<<" || op == "<<-") {
+                            self.heredoc_word = Some(op == "<<-");
+                        }
+                        self.tokens.push(Token::Op(op));
+                    }
+                    c => self.push_char(c, false),
+                },
+            }
+        }
+        self.continued = joined;
+        if self.quote.is_none() && !self.continued {
+            self.finish_word();
+            self.heredoc_bodies.extend(self.heredocs.drain(..));
+        }
+    }
+
+    fn open_quote(&mut self, quote: Quote) {
+        self.quote = Some(quote);
+        self.word.get_or_insert_with(|| (String::new(), true)).1 = true;
+    }
+
+    fn push_char(&mut self, c: char, quoted: bool) {
+        let word = self.word.get_or_insert_with(|| (String::new(), false));
+        word.0.push(c);
+        word.1 |= quoted;
+    }
+
+    fn finish_word(&mut self) {
+        if let Some((text, quoted)) = self.word.take() {
+            if let Some(strip_tabs) = self.heredoc_word.take() {
+                self.heredocs.push((text.clone(), strip_tabs));
+            }
+            self.tokens.push(Token::Word(text, quoted));
+        }
+    }
+}
+
+/// The words of tokens which are in command position, such as the first word
+/// of each command of a pipeline, and reserved words
+fn command_words(tokens: &[Token], dialect: Dialect) -> Vec<&Token> {
+    let prefixes = match dialect {
+        Dialect::Posix => POSIX_PREFIXES,
+        Dialect::Fish => FISH_PREFIXES,
+    };
+    let mut words = Vec::new();
+    let mut command_position = true;
+    let mut redirection = false;
+    for token in tokens {
+        match token {
+            Token::Op(op) if SEPARATORS.contains(op) => command_position = true,
+            Token::Op(_) => redirection = true,
+            Token::Word(_, _) if redirection => redirection = false,
+            Token::Word(text, quoted) if command_position => {
+                words.push(token);
+                command_position = !quoted
+                    && (prefixes.contains(&text.as_str())
+                        || (dialect == Dialect::Posix && is_assignment(text)));
+            }
+            Token::Word(_, _) => (),
+        }
+    }
+    words
+}
+
+/// Whether word is a `NAME=value` variable assignment
+fn is_assignment(word: &str) -> bool {
+    match word.split_once('=') {
+        Some((name, _)) => {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
+}
+
+/// How many blocks tokens open, negative when they close more than they open
+fn block_depth(tokens: &[Token], dialect: Dialect) -> i32 {
+    let mut depth = 0;
+    let mut previous: Option<&Token> = None;
+    for word in command_words(tokens, dialect) {
+        match dialect {
+            Dialect::Posix if word.is_bare("{") => depth += 1,
+            Dialect::Posix if word.is_bare("}") => depth -= 1,
+            // `else if` continues the block of the first if.
+            Dialect::Fish
+                if word.is_bare("if") && matches!(previous, Some(w) if w.is_bare("else")) => {}
+            Dialect::Fish if FISH_BLOCKS.iter().any(|block| word.is_bare(block)) => depth += 1,
+            Dialect::Fish if word.is_bare("end") => depth -= 1,
+            _ => (),
+        }
+        previous = Some(word);
+    }
+    depth
+}
+
+/// The commands an alias value runs, without reserved words and assignments
+pub fn alias_commands(value: &str) -> Vec<String> {
+    let mut lexer = Lexer::new(Dialect::Posix);
+    let mut tokens = Vec::new();
+    for line in value.lines() {
+        lexer.push_line(line);
+        if lexer.is_complete() {
+            // A newline ends the command like a semicolon.
+            tokens.append(&mut lexer.take_tokens());
+            tokens.push(Token::Op(";"));
+        }
+    }
+    tokens.append(&mut lexer.take_tokens());
+    command_words(&tokens, Dialect::Posix)
+        .into_iter()
+        .map(Token::text)
+        .filter(|word| !Shell::Bash.is_keyword(word) && !is_assignment(word))
+        .map(String::from)
+        .collect()
+}
+
+/// An alias read from stdin
+#[derive(Clone, Debug)]
+pub struct Alias {
+    /// Name
+    pub name: String,
+    /// Value, with its quotes removed
+    pub value: String,
+    /// The definition as it was read, which may span lines
+    pub text: String,
+}
+
+/// A function read from stdin
+#[derive(Clone, Debug)]
+pub struct FunctionSt {
+    /// Name
+    pub name: String,
+    /// The line which starts the definition, without `declare -f`
+    pub header: String,
+    /// The lines of the body, up to the one which closes it
+    pub lines: Vec<String>,
+}
+
+/// Everything which was read from stdin
+#[derive(Default, Debug)]
+pub struct Definitions {
+    /// Aliases, in the order they were read
+    pub aliases: Vec<Alias>,
+    /// Functions, in the order they were read
+    pub functions: Vec<FunctionSt>,
+    /// Commands remembered by the shell, with their paths
+    pub hashed: Vec<(String, PathBuf)>,
+}
+
+type Lines<'a> = std::iter::Enumerate<std::str::Lines<'a>>;
+
+/// Tell fish's `function name` and `alias name value` from the bash and zsh
+/// syntax, everything else is read the same way
+fn dialect_of(line: &str) -> Dialect {
+    let is_fish = if let Some(rest) = line.strip_prefix("function ") {
+        // `function name {` and `function name ()` are ksh style.
+        let after_name = rest
+            .trim_start()
+            .trim_start_matches(|c: char| !c.is_whitespace() && c != '(' && c != '{');
+        !after_name.trim_start().starts_with(['(', '{'])
+    } else if let Some(rest) = line.strip_prefix("alias ") {
+        let rest = rest.trim_start();
+        let rest = rest.strip_prefix("-- ").unwrap_or(rest).trim_start();
+        rest.trim_start_matches(|c: char| !c.is_whitespace() && c != '=')
+            .starts_with(char::is_whitespace)
+    } else {
+        false
+    };
+    if is_fish {
+        Dialect::Fish
+    } else {
+        Dialect::Posix
+    }
+}
+
+/// The name of the function tokens start, and the index of the tokens after
+/// the header which belong to the body
+fn function_header(tokens: &[Token], dialect: Dialect) -> Option<(String, usize)> {
+    if dialect == Dialect::Fish {
+        return match tokens {
+            [first, Token::Word(name, _), ..] if first.is_bare("function") => {
+                Some((name.clone(), 0))
+            }
+            _ => None,
+        };
+    }
+    let mut i = 0;
+    // bash 2.05a and older print `declare -fx NAME ()`.
+    if let [first, Token::Word(options, false), ..] = tokens {
+        if first.is_bare("declare") && options.starts_with('-') && options.contains('f') {
+            i = 2;
+        }
+    }
+    let has_keyword = matches!(tokens.get(i), Some(t) if t.is_bare("function"));
+    if has_keyword {
+        i += 1;
+    }
+    let name = match tokens.get(i) {
+        Some(Token::Word(name, _)) => name.clone(),
+        _ => return None,
+    };
+    i += 1;
+    match tokens.get(i..i + 2) {
+        Some([Token::Op("("), Token::Op(")")]) => Some((name, i + 2)),
+        _ if has_keyword => Some((name, i)),
+        _ => None,
+    }
+}
+
+/// Read the rest of the command which starts with line
+fn logical_line(
+    lines: &mut Lines,
+    lineno: usize,
+    line: &str,
+    dialect: Dialect,
+) -> Result<(Vec<Token>, String), String> {
+    let mut lexer = Lexer::new(dialect);
+    let mut text = line.to_string();
+    lexer.push_line(line);
+    while !lexer.is_complete() {
+        match lines.next() {
+            Some((_, line)) => {
+                text.push('\n');
+                text.push_str(line);
+                lexer.push_line(line);
+            }
+            None => return Err(format!("{} in line {}", lexer.open_construct(), lineno)),
+        }
+    }
+    Ok((lexer.take_tokens(), text))
+}
+
+/// Read the function whose header is line
+fn parse_function(
+    lines: &mut Lines,
+    lineno: usize,
+    line: &str,
+    dialect: Dialect,
+) -> Result<FunctionSt, String> {
+    let (tokens, text) = logical_line(lines, lineno, line, dialect)?;
+    let (name, body_start) = function_header(&tokens, dialect)
+        .ok_or_else(|| format!("malformed function header in line {}", lineno))?;
+    let mut header = text.trim();
+    if matches!(tokens.first(), Some(t) if t.is_bare("declare")) {
+        // Drop `declare` and its options, as bash 2.05b and later print it.
+        header = header["declare".len()..].trim_start();
+        header = header
+            .trim_start_matches(|c: char| !c.is_whitespace())
+            .trim_start();
+    }
+    let unterminated = || format!("unterminated function '{}' in line {}", name, lineno);
+    let mut function = FunctionSt {
+        name: name.clone(),
+        header: header.to_string(),
+        lines: Vec::new(),
+    };
+    let mut depth = block_depth(&tokens[body_start..], dialect);
+    let mut subshell = false;
+    if dialect == Dialect::Posix && body_start == tokens.len() {
+        // bash puts the opening brace of the body on its own line.
+        loop {
+            let (n, line) = lines.next().ok_or_else(unterminated)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (tokens, text) = logical_line(lines, n + 1, line, dialect)?;
+            function.lines.extend(text.split('\n').map(String::from));
+            match tokens.first() {
+                Some(Token::Op("(")) => subshell = true,
+                Some(token) if token.is_bare("{") => depth = block_depth(&tokens, dialect),
+                _ => {
+                    return Err(format!(
+                        "expected '{{' after function '{}' in line {}",
+                        name,
+                        n + 1
+                    ))
+                }
+            }
+            break;
+        }
+    }
+    // A `( ... )` body ends with the line which closes the subshell, as the
+    // case patterns inside it do not balance the parentheses.
+    while depth > 0 || subshell {
+        let (n, line) = lines.next().ok_or_else(unterminated)?;
+        let (tokens, text) = logical_line(lines, n + 1, line, dialect)?;
+        function.lines.extend(text.split('\n').map(String::from));
+        if subshell {
+            subshell = tokens.first() != Some(&Token::Op(")"));
+        } else {
+            depth += block_depth(&tokens, dialect);
+        }
+    }
+    Ok(function)
+}
+
+/// Split the tokens of `alias name=value` (bash), `name=value` (zsh),
+/// `alias name value` (fish) or `name value` (tcsh) into name and value
+fn parse_alias(tokens: &[Token]) -> Option<(String, String)> {
+    let mut words = tokens.iter();
+    let mut first = words.next()?;
+    if first.is_bare("alias") {
+        first = words.next()?;
+    }
+    if first.is_bare("--") {
+        first = words.next()?;
+    }
+    let (name, mut value) = match first.text().split_once('=') {
+        Some((name, value)) => (name, value.to_string()),
+        None => (first.text(), String::new()),
+    };
+    if name.is_empty() {
+        return None;
+    }
+    let mut rest = words.map(Token::text).peekable();
+    if value.is_empty() {
+        // `name = value` and `name =value`
+        if let Some(word) = rest.peek().and_then(|word| word.strip_prefix('=')) {
+            value.push_str(word);
+            rest.next();
+        }
+    }
+    for word in rest {
+        if !value.is_empty() {
+            value.push(' ');
+        }
+        value.push_str(word);
+    }
+    Some((name.to_string(), value))
+}
+
+/// Parse the output of `alias`, `declare -f` or `functions`, and `hash -l`
+/// of bash, zsh and fish. Lines which are none of these are skipped.
+pub fn parse(input: &str) -> Result<Definitions, String> {
+    let mut definitions = Definitions::default();
+    let mut lines = input.lines().enumerate();
+    while let Some((n, line)) = lines.next() {
+        let lineno = n + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(hashed) = trimmed.strip_prefix(HASH_LINE_PREFIX) {
+            if let Some((path, name)) = hashed.rsplit_once(' ') {
+                definitions
+                    .hashed
+                    .push((name.to_string(), PathBuf::from(path)));
+            }
+            continue;
+        }
+        let dialect = dialect_of(trimmed);
+        let probe = Lexer::line_tokens(trimmed, dialect);
+        if function_header(&probe, dialect).is_some() {
+            let function = parse_function(&mut lines, lineno, line, dialect)?;
+            definitions.functions.push(function);
+            continue;
+        }
+        // bash 2.05b prints `declare -fx NAME` for exported functions.
+        if matches!(probe.first(), Some(t) if t.is_bare("declare")) {
+            continue;
+        }
+        // zsh quotes names with special characters, as in 'g c'='git commit'.
+        let is_definition = trimmed.starts_with("alias ")
+            || matches!(probe.first(), Some(Token::Word(word, _)) if word.contains('='));
+        if is_definition {
+            let (tokens, text) = logical_line(&mut lines, lineno, line, dialect)?;
+            let (name, value) = parse_alias(&tokens)
+                .ok_or_else(|| format!("alias without a name in line {}", lineno))?;
+            definitions.aliases.push(Alias { name, value, text });
+        } else if let Some((name, value)) = trimmed.split_once(char::is_whitespace) {
+            // tcsh separates name and value by a tab, and prints the value
+            // as it is.
+            definitions.aliases.push(Alias {
+                name: name.to_string(),
+                value: value.trim().to_string(),
+                text: line.to_string(),
+            });
+        }
+    }
+    Ok(definitions)
+}
@@ -13,6 +13,10 @@ use uucore::{
     msg_log::errmsg_custom,
 };
 
+/// Reserved words and builtins of the shells
+pub mod builtins;
+/// Tokenizer for the aliases and functions read from stdin
+pub mod parser;
 /// which common functions
 pub mod which_common;
 
@@ -20,7 +24,9 @@ use which_common::{
     func_search, get_current_working_directory, get_home_dir, parse_which_cmd_args,
 };
 
-use crate::which_common::{path_search, read_alias_functions};
+use crate::which_common::{
+    path_search, posix_search, read_alias_functions, type_search, why_search,
+};
 
 const ABOUT: &str = help_section!("about", "which.md");
 const USAGE: &str = help_usage!("which.md");
@@ -59,11 +65,42 @@ pub fn oemain(args: impl uucore::Args) -> UResult<()> {
         if argv.is_empty() {
             continue;
         }
-        if config.read_functions && argv.find('/') == None {
-            found_something = func_search(false, argv, &config);
+        if config.show_type || config.posix {
+            let found = if config.posix {
+                posix_search(argv, &config)?
+            } else {
+                type_search(argv, &config)?
+            };
+            if !found {
+                // command -v fails silently.
+                if config.show_type {
+                    errmsg_custom(false, 0, false, &format!("{}: not found", argv));
+                }
+                fail_count += 1;
+            }
+            continue;
+        }
+        let shell = config.shell.filter(|_| !absolute_program(argv));
+        if matches!(shell, Some(shell) if shell.is_keyword(argv)) {
+            println!("{}: shell reserved word", argv);
+            found_something = true;
+        }
+        if config.read_functions && argv.find('/') == None && (config.show_all || !found_something)
+        {
+            found_something |= func_search(false, argv, &config);
+        }
+        if (config.show_all || !found_something)
+            && matches!(shell, Some(shell) if shell.is_builtin(argv))
+        {
+            println!("{}: shell built-in command", argv);
+            found_something = true;
         }
         if (config.show_all || !found_something)
-            && !path_search(false, PathBuf::from(argv), &config)?
+            && !(if config.why {
+                why_search(argv, &config)?
+            } else {
+                path_search(false, PathBuf::from(argv), &config)?
+            })
             && !found_something
         {
             if absolute_program(argv) {
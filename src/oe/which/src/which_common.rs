@@ -7,17 +7,27 @@
 
 use std::env::var_os;
 use std::ffi::{CString, OsStr, OsString};
-use std::io::Write;
+use std::fmt;
+use std::io::{Read, Write};
 use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
 use std::path::{Component, Path, PathBuf};
 
-use nix::unistd::{eaccess, getcwd, geteuid, getgid, getuid, isatty, AccessFlags, Uid, User};
+use nix::sys::statvfs::{statvfs, FsFlags};
+use nix::unistd::{
+    eaccess, getcwd, getegid, geteuid, getgid, getgroups, getuid, isatty, AccessFlags, Gid, Uid,
+    User,
+};
 use nix::NixPath;
-use uucore::error::{UResult, USimpleError};
+use uucore::display::Quotable;
+use uucore::error::{UResult, USimpleError, UUsageError};
 use uucore::{format_usage, show_warning, util_name};
 
 use clap::{crate_version, Arg, ArgMatches, Command};
 
+use crate::builtins::Shell;
+use crate::parser::{alias_commands, parse, Alias, FunctionSt};
+
 const EXIT_FAILURE: i32 = -1;
 const ADDITIONAL_HELP: &str =
     "Recommended use is to write the output of (alias; declare -f) to standard
@@ -27,6 +37,8 @@ examples.
 If the options --read-alias and/or --read-functions are specified then the
 output can be a full alias or function definition, optionally followed by
 the full path of each command used inside of those.";
+/// Symlinks followed before giving up on a loop, like the kernel's MAXSYMLINKS
+const MAX_SYMLINKS: usize = 40;
 
 /// Config
 pub struct Config {
@@ -64,14 +76,18 @@ pub struct Config {
     pub path_list: OsString,
     /// The current user info
     pub current_user: User,
-}
-
-/// Function info
-pub struct FunctionSt {
-    /// Name
-    pub name: String,
-    /// Contents
-    pub lines: Vec<String>,
+    /// Shell whose reserved words and builtins are looked up
+    pub shell: Option<Shell>,
+    /// Report the kind of every match, like `type -a`
+    pub show_type: bool,
+    /// Report the first match the way `command -v` does
+    pub posix: bool,
+    /// Aliases read from stdin, kept for --type and --posix
+    pub aliases: Vec<Alias>,
+    /// Commands remembered by the shell, read from `hash -l` output on stdin
+    pub hashed: Vec<(String, PathBuf)>,
+    /// Explain why each candidate in PATH was taken or rejected
+    pub why: bool,
 }
 
 #[allow(non_camel_case_types)]
@@ -106,6 +122,14 @@ pub mod options {
     ///
     pub static SKIPFUNC: &str = "skip-functions";
     ///
+    pub static SHELL: &str = "shell";
+    ///
+    pub static TYPE: &str = "type";
+    ///
+    pub static POSIX: &str = "posix";
+    ///
+    pub static WHY: &str = "why";
+    ///
     pub static COMMAND: &str = "command";
 }
 
@@ -126,6 +150,13 @@ impl Config {
             },
             None => last_index,
         };
+        let shell = match args_matches.get_one::<String>(options::SHELL) {
+            Some(name) => Some(Shell::from_name(name).ok_or_else(|| {
+                UUsageError::new(EXIT_FAILURE, format!("unsupported shell {}", name.quote()))
+            })?),
+            None => None,
+        };
+        let posix = args_matches.contains_id(options::POSIX);
         Ok(Self {
             skip_dot: match args_matches.index_of(options::SKIPDOT) {
                 Some(val) => val < tty_only_index,
@@ -136,12 +167,13 @@ impl Config {
                 None => false,
             },
             skip_alias: args_matches.contains_id(options::SKIPALIAS),
+            // command -v always prints absolute paths.
             show_dot: match args_matches.index_of(options::SHOWDOT) {
-                Some(val) => val < tty_only_index,
+                Some(val) => val < tty_only_index && !posix,
                 None => false,
             },
             show_tilde: match args_matches.index_of(options::SHOWTILDE) {
-                Some(val) => val < tty_only_index && !geteuid().is_root(),
+                Some(val) => val < tty_only_index && !geteuid().is_root() && !posix,
                 None => false,
             },
             tty_only: tty_only_index != last_index,
@@ -156,6 +188,12 @@ impl Config {
             functions: Vec::new(),
             path_list: var_os("PATH").unwrap_or_default(),
             current_user: uidget_get_current_user_info(),
+            shell,
+            show_type: args_matches.contains_id(options::TYPE),
+            posix,
+            aliases: Vec::new(),
+            hashed: Vec::new(),
+            why: args_matches.contains_id(options::WHY),
         })
     }
 }
@@ -228,6 +266,31 @@ pub fn which_app<'a>(about: &'a str, usage: &'a str) -> Command<'a> {
                 .long(options::SKIPFUNC)
                 .help("Ignore option --read-functions; don't read stdin."),
         )
+        .arg(
+            Arg::new(options::SHELL)
+                .long(options::SHELL)
+                .takes_value(true)
+                .value_name("shell")
+                .help("Look up reserved words and builtins of bash, dash or zsh."),
+        )
+        .arg(
+            Arg::new(options::TYPE)
+                .long(options::TYPE)
+                .conflicts_with(options::POSIX)
+                .help("Report the kind of each match, like 'type'."),
+        )
+        .arg(
+            Arg::new(options::POSIX)
+                .long(options::POSIX)
+                .help("Report the first match like 'command -v'."),
+        )
+        .arg(
+            Arg::new(options::WHY)
+                .long(options::WHY)
+                .visible_alias("explain")
+                .conflicts_with_all(&[options::TYPE, options::POSIX])
+                .help("Explain why each candidate in PATH was taken or skipped."),
+        )
         .after_help(ADDITIONAL_HELP)
         .arg(
             Arg::new(options::COMMAND)
@@ -285,9 +348,6 @@ pub fn get_home_dir(current_user: &User) -> PathBuf {
 
 /// Impl if (read_alias || read_functions) from main()
 pub fn read_alias_functions(config: &mut Config) -> UResult<()> {
-    let mut buf = String::new();
-    let mut processing_aliasess = config.read_alias;
-    let mut aliasess = Vec::new();
     if Ok(true) == isatty(0) {
         eprintln!(
             "{}: {}: Warning: stdin is a tty.",
@@ -301,165 +361,59 @@ pub fn read_alias_functions(config: &mut Config) -> UResult<()> {
             }
         )
     }
-    while std::io::stdin().read_line(&mut buf).is_ok() {
-        let mut looks_like_function_start = false;
-        let mut function_start_has_declare = false;
-        if buf.is_empty() {
-            break;
-        }
-        if config.read_functions {
-            // bash version 2.0.5a and older output a pattern for `str' like
-            // declare -fx FUNCTION_NAME ()
-            // {
-            //   body
-            // }
-            //
-            // bash version 2.0.5b and later output a pattern for `str' like
-            // FUNCTION_NAME ()
-            // {
-            //   body
-            // }
-            let last_non_whitespace = buf.split_ascii_whitespace().last();
-            if buf.find(" ()").is_some() && last_non_whitespace == Some("()") {
-                looks_like_function_start = true;
-                function_start_has_declare = buf.starts_with("declare -");
-            }
-            // Add some zsh support here.
-            // zsh does output a pattern for `str' like
-            // FUNCTION () {
-            //   body
-            // }
-            if buf.find(" () {").is_some() && last_non_whitespace == Some("{") {
-                looks_like_function_start = true;
-                config.function_start_type = 1;
-                function_start_has_declare = false;
-            }
+    let mut input = Vec::new();
+    std::io::stdin().read_to_end(&mut input)?;
+    let definitions = parse(&String::from_utf8_lossy(&input))
+        .map_err(|msg| USimpleError::new(EXIT_FAILURE, msg))?;
+    config.hashed = definitions.hashed;
+    if config.read_functions {
+        // Like GNU which, print every function in the zsh style once one
+        // was read.
+        if definitions
+            .functions
+            .iter()
+            .any(|f| f.header == format!("{} () {{", f.name))
+        {
+            config.function_start_type = 1;
         }
-        if processing_aliasess && !looks_like_function_start {
-            // bash version 2.0.5b can throw in lines like "declare -fx
-            // FUNCTION_NAME", eat them.
-            if buf.starts_with("declare -") {
-                continue;
-            }
-            aliasess.push(buf.clone());
-        } else if config.read_functions && looks_like_function_start {
-            processing_aliasess = false;
-            let mut p = buf.split_ascii_whitespace();
-            if function_start_has_declare {
-                p.next(); // Eat declare
-                p.next(); // Eat -fx
-            }
-            let name = p.next().unwrap();
-            let name_string = match name {
-                "()" => String::new(),
-                _ => String::from(name),
-            };
-            let mut lines = Vec::new();
-            buf.clear();
-            while std::io::stdin().read_line(&mut buf).is_ok() {
-                if buf.is_empty() {
-                    break;
-                }
-                lines.push(buf.clone());
-                if buf == "}\n" {
-                    break;
-                }
-                buf.clear();
-            }
-            config.functions.push(FunctionSt {
-                name: name_string,
-                lines,
-            });
-        }
-        buf.clear();
+        config.functions = definitions.functions;
     }
     if config.read_alias {
-        for aliases in aliasess {
-            process_alias(config, aliases)?;
+        if config.show_type || config.posix {
+            config.aliases = definitions.aliases;
+        } else {
+            for alias in &definitions.aliases {
+                process_alias(config, alias)?;
+            }
         }
     }
     Ok(())
 }
 
-fn process_alias(config: &mut Config, aliases: String) -> UResult<()> {
-    // Remove all whitespace and " '
-    let mut p = aliases.split(|v: char| v.is_ascii_whitespace() || v == '"' || v == '\'');
-    let mut pstr = p.next();
-    if pstr == Some("alias") {
-        pstr = p.next();
-    }
-    if pstr.is_none() {
-        return Ok(());
-    }
-    let mut cmd = pstr.unwrap();
-    let mut pstr_has_equal = false;
-    if let Some(v) = cmd.find('=') {
-        cmd = &cmd[0..v];
-        pstr_has_equal = true;
-    }
+/// Print alias if it is one of the commands, followed by the commands it runs
+fn process_alias(config: &mut Config, alias: &Alias) -> UResult<()> {
     let mut command_list = config.command_list.clone();
     for argv in &mut command_list {
-        if *argv != cmd {
+        if *argv != alias.name {
             continue;
         }
-        if let Err(e) = std::io::stdout().write_all(aliases.as_bytes()) {
+        if let Err(e) = writeln!(std::io::stdout(), "{}", alias.text) {
             show_warning!("Can't write to stdout! {:?}", e);
         }
         if !config.show_all {
             argv.clear();
         }
-        if pstr_has_equal {
-            // 'aa=bb' or 'aa= bb'
-            cmd = pstr.unwrap().split_once('=').unwrap().1;
-            while cmd.is_empty() {
-                match p.next() {
-                    Some(v) => cmd = v,
-                    None => break,
-                };
-            }
-        } else {
-            // 'aa = bb' or 'aa =bb' or 'aa bb'
-            cmd = p.next().unwrap_or_default();
-            if cmd.starts_with('=') {
-                pstr = cmd.strip_prefix('=');
-                if pstr.is_none() {
-                    pstr = p.next();
-                }
-                cmd = pstr.unwrap_or_default();
-            }
-        }
-        if cmd.is_empty() {
-            break;
-        }
-        // now cmd can be 'bb' or 'bb&cc' or 'bb&'
-        let and_or_judge = |c: char| c == '&' || c == '|';
-        loop {
-            let mut found = false;
-            let mut has_andor_in_cmd = false;
-            if let Some(v) = cmd.split_once(and_or_judge) {
-                pstr = Some(v.1);
-                cmd = v.0;
-                has_andor_in_cmd = true;
-            }
-            if !argv.is_empty() && argv == cmd {
+        for cmd in alias_commands(&alias.value) {
+            if !argv.is_empty() && *argv == cmd {
                 argv.clear();
             }
-            if config.read_functions && cmd.find('/').is_none() {
-                found = func_search(true, cmd, config);
+            let mut found = false;
+            if config.read_functions && !cmd.contains('/') {
+                found = func_search(true, &cmd, config);
             }
             if config.show_all || !found {
                 path_search(true, PathBuf::from(cmd), config)?;
             }
-            if has_andor_in_cmd {
-                cmd = pstr.unwrap();
-                if !cmd.is_empty() {
-                    continue;
-                }
-            }
-            match p.next() {
-                Some(v) => cmd = v,
-                None => break,
-            };
         }
         break;
     }
@@ -471,19 +425,19 @@ fn process_alias(config: &mut Config, aliases: String) -> UResult<()> {
 pub fn func_search(indent: bool, cmd: &str, config: &Config) -> bool {
     for i in &config.functions {
         if i.name == cmd {
-            if indent {
-                print!("\t");
-            }
-            if config.function_start_type == 1 {
-                println!("{} () {{", cmd);
-            } else {
-                println!("{} ()", cmd);
+            let mut stdout = std::io::stdout();
+            let mut header = i.header.clone();
+            if header == format!("{} ()", cmd) || header == format!("{} () {{", cmd) {
+                header = match config.function_start_type {
+                    1 => format!("{} () {{", cmd),
+                    _ => format!("{} ()", cmd),
+                };
             }
-            for j in &i.lines {
+            for line in std::iter::once(&header).chain(&i.lines) {
                 if indent {
                     print!("\t");
                 }
-                if let Err(e) = std::io::stdout().write_all(j.as_bytes()) {
+                if let Err(e) = writeln!(stdout, "{}", line) {
                     show_warning!("Can't write to stdout! {:?}", e);
                 }
             }
@@ -495,7 +449,20 @@ pub fn func_search(indent: bool, cmd: &str, config: &Config) -> bool {
 
 /// Search cmd in paths
 pub fn path_search(indent: bool, cmd: PathBuf, config: &Config) -> UResult<bool> {
-    let mut found_something = false;
+    let matches = path_matches(cmd, config)?;
+    for full_path in &matches {
+        if indent {
+            print!("\t");
+        }
+        std::io::stdout().write_all(full_path.as_bytes())?;
+        std::io::stdout().write_all(&[b'\n'])?;
+    }
+    return Ok(!matches.is_empty());
+}
+
+/// The matches of cmd in paths, as they are printed
+fn path_matches(cmd: PathBuf, config: &Config) -> UResult<Vec<OsString>> {
+    let mut matches = Vec::new();
     let mut found_path_starts_with_dot = false;
     if !config.path_list.is_empty() {
         let mut next = true;
@@ -512,16 +479,14 @@ pub fn path_search(indent: bool, cmd: PathBuf, config: &Config) -> UResult<bool>
                 let mut full_path = full_path_buf.as_path();
                 let in_home =
                     (config.show_tilde || config.skip_tilde) && full_path.starts_with(&config.home);
-                if indent {
-                    print!("\t");
-                }
+                let mut shown = OsString::new();
                 if !(config.skip_tilde && in_home)
                     && config.show_dot
                     && found_path_starts_with_dot
                     && full_path.starts_with(&config.cwd)
                 {
                     full_path = full_path.strip_prefix(&config.cwd).unwrap();
-                    print!("./");
+                    shown.push("./");
                 } else if in_home {
                     if config.skip_tilde {
                         next = true;
@@ -529,18 +494,323 @@ pub fn path_search(indent: bool, cmd: PathBuf, config: &Config) -> UResult<bool>
                     }
                     if config.show_tilde {
                         full_path = full_path.strip_prefix(&config.home).unwrap();
-                        print!("~/");
+                        shown.push("~/");
                     }
                 }
-                std::io::stdout().write_all(full_path.as_os_str().as_bytes())?;
-                std::io::stdout().write_all(&[b'\n'])?;
-                found_something = true;
+                shown.push(full_path);
+                matches.push(shown);
             } else {
                 break;
             }
         }
     }
-    return Ok(found_something);
+    Ok(matches)
+}
+
+/// What a command name stands for, in the order `type -a` reports them
+pub enum CommandType<'a> {
+    /// An alias, with its value
+    Alias(&'a str),
+    /// A reserved word of the shell
+    Keyword,
+    /// A shell function
+    Function,
+    /// A builtin command of the shell
+    Builtin,
+    /// A path remembered in the shell's hash table
+    Hashed(&'a Path),
+    /// An executable found in PATH
+    File(OsString),
+}
+
+/// Every meaning of cmd, or only the one the shell would use without -a
+pub fn classify<'a>(cmd: &str, config: &'a Config) -> UResult<Vec<CommandType<'a>>> {
+    let mut types = Vec::new();
+    if !cmd.contains('/') {
+        if let Some(alias) = config.aliases.iter().find(|alias| alias.name == cmd) {
+            types.push(CommandType::Alias(&alias.value));
+        }
+        if matches!(config.shell, Some(shell) if shell.is_keyword(cmd)) {
+            types.push(CommandType::Keyword);
+        }
+        if config.functions.iter().any(|f| f.name == cmd) {
+            types.push(CommandType::Function);
+        }
+        if matches!(config.shell, Some(shell) if shell.is_builtin(cmd)) {
+            types.push(CommandType::Builtin);
+        }
+        // The hash table only speeds up the first lookup, -a searches PATH.
+        if !config.show_all {
+            if let Some((_, path)) = config.hashed.iter().find(|(name, _)| name == cmd) {
+                types.push(CommandType::Hashed(path));
+            }
+        }
+    }
+    if config.show_all || types.is_empty() {
+        types.extend(
+            path_matches(PathBuf::from(cmd), config)?
+                .into_iter()
+                .map(CommandType::File),
+        );
+    }
+    if !config.show_all {
+        types.truncate(1);
+    }
+    Ok(types)
+}
+
+/// Print the meanings of cmd like `type`, returning whether there was any
+pub fn type_search(cmd: &str, config: &Config) -> UResult<bool> {
+    let types = classify(cmd, config)?;
+    for t in &types {
+        match t {
+            CommandType::Alias(value) => println!("{} is aliased to `{}'", cmd, value),
+            CommandType::Keyword => println!("{} is a shell keyword", cmd),
+            CommandType::Function => {
+                println!("{} is a function", cmd);
+                func_search(false, cmd, config);
+            }
+            CommandType::Builtin => println!("{} is a shell builtin", cmd),
+            CommandType::Hashed(path) => println!("{} is hashed ({})", cmd, path.display()),
+            CommandType::File(path) => {
+                let mut stdout = std::io::stdout();
+                write!(stdout, "{} is ", cmd)?;
+                stdout.write_all(path.as_bytes())?;
+                stdout.write_all(b"\n")?;
+            }
+        }
+    }
+    Ok(!types.is_empty())
+}
+
+/// Print the first meaning of cmd like `command -v`, returning whether there
+/// was any
+pub fn posix_search(cmd: &str, config: &Config) -> UResult<bool> {
+    let mut stdout = std::io::stdout();
+    match classify(cmd, config)?.first() {
+        Some(CommandType::Alias(value)) => {
+            writeln!(stdout, "alias {}='{}'", cmd, value.replace('\'', "'\\''"))?
+        }
+        Some(CommandType::Keyword | CommandType::Function | CommandType::Builtin) => {
+            writeln!(stdout, "{}", cmd)?
+        }
+        Some(CommandType::Hashed(path)) => {
+            stdout.write_all(path.as_os_str().as_bytes())?;
+            stdout.write_all(b"\n")?;
+        }
+        Some(CommandType::File(path)) => {
+            stdout.write_all(path.as_bytes())?;
+            stdout.write_all(b"\n")?;
+        }
+        None => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// Why a candidate in PATH was not taken
+enum Rejection {
+    /// The PATH entry starts with a tilde and --skip-tilde was given
+    SkipTilde,
+    /// The PATH entry is relative and --skip-dot was given
+    SkipDot,
+    /// The PATH entry does not exist or is not a directory
+    NoDirectory,
+    /// The PATH entry can not be searched
+    Unsearchable,
+    /// There is no such file in the directory
+    NotFound,
+    /// A symlink whose target does not exist
+    Dangling(PathBuf),
+    /// A directory, which the shell never executes
+    Directory,
+    /// A file on a filesystem mounted noexec
+    NoexecMount,
+    /// The permission bits that apply to us lack execute permission
+    NotExecutable(String),
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::SkipTilde => write!(f, "skipped (--skip-tilde)"),
+            Self::SkipDot => write!(f, "skipped (--skip-dot)"),
+            Self::NoDirectory => write!(f, "no such directory"),
+            Self::Unsearchable => write!(f, "directory is not searchable"),
+            Self::NotFound => write!(f, "not found"),
+            Self::Dangling(target) => write!(f, "dangling symlink to {}", target.quote()),
+            Self::Directory => write!(f, "is a directory"),
+            Self::NoexecMount => write!(f, "on a filesystem mounted noexec"),
+            Self::NotExecutable(reason) => write!(f, "not executable: {}", reason),
+        }
+    }
+}
+
+/// The PATH directories searched for name, with the reason a directory is
+/// skipped, and the file name to look for in them
+fn search_dirs(name_s: &Path, config: &Config) -> (Vec<(PathBuf, Option<Rejection>)>, OsString) {
+    let mut path_list: &OsStr = &config.path_list;
+    let mut name = name_s.as_os_str();
+    let mut abs_path = PathBuf::new();
+    if name_s.file_name().unwrap_or_default().len() < name_s.len() {
+        if !name_s.starts_with(".") && !name_s.has_root() && !name_s.starts_with("~") {
+            abs_path.push("./");
+        }
+        abs_path.push(name);
+        path_list = abs_path.parent().unwrap().as_os_str();
+        name = abs_path.file_name().unwrap();
+    }
+    let mut dirs = Vec::new();
+    for path_bytes in path_list.as_bytes().split(|v| *v == b':') {
+        let mut path = PathBuf::from(OsStr::from_bytes(path_bytes));
+        if path_bytes.is_empty() {
+            path = PathBuf::from(".");
+        }
+        let mut skip = None;
+        if path.starts_with("~") {
+            path = tilde_expand(&path, &config.current_user);
+            if config.skip_tilde {
+                skip = Some(Rejection::SkipTilde);
+            }
+        }
+        if skip.is_none() && config.skip_dot && !path.has_root() {
+            skip = Some(Rejection::SkipDot);
+        }
+        dirs.push((path, skip));
+    }
+    (dirs, name.to_os_string())
+}
+
+/// Which permission bits deny execution of a file to the effective user
+fn exec_denial(metadata: &std::fs::Metadata) -> String {
+    let mode = metadata.mode() & 0o7777;
+    let euid = geteuid();
+    let egid = getegid();
+    if euid.is_root() {
+        return format!("no execute bit set for anyone (mode {:04o})", mode);
+    }
+    if Uid::from_raw(metadata.uid()) == euid {
+        if mode & 0o100 == 0 {
+            return format!(
+                "no execute permission for owner uid {} (mode {:04o})",
+                euid, mode
+            );
+        }
+    } else if Gid::from_raw(metadata.gid()) == egid
+        || matches!(getgroups(), Ok(groups) if groups.contains(&Gid::from_raw(metadata.gid())))
+    {
+        if mode & 0o010 == 0 {
+            return format!(
+                "no execute permission for group gid {} (mode {:04o})",
+                metadata.gid(),
+                mode
+            );
+        }
+    } else if mode & 0o001 == 0 {
+        return format!(
+            "no execute permission for others, owner is uid {} and group is gid {} (mode {:04o})",
+            metadata.uid(),
+            metadata.gid(),
+            mode
+        );
+    }
+    // The mode allows it, so an ACL or a security module said no.
+    "permission denied by an ACL or security policy".to_string()
+}
+
+/// Why full_path in dir can not be executed, None when it can
+fn rejection(dir: &Path, full_path: &Path) -> Option<Rejection> {
+    let status = file_status(full_path);
+    if status.contains(&FileStatus::FS_EXISTS) && status.contains(&FileStatus::FS_EXECABLE) {
+        return None;
+    }
+    if !dir.is_dir() {
+        return Some(Rejection::NoDirectory);
+    }
+    if eaccess(dir, AccessFlags::X_OK).is_err() {
+        return Some(Rejection::Unsearchable);
+    }
+    let link_metadata = match full_path.symlink_metadata() {
+        Ok(v) => v,
+        Err(_) => return Some(Rejection::NotFound),
+    };
+    let metadata = match full_path.metadata() {
+        Ok(v) => v,
+        Err(_) if link_metadata.file_type().is_symlink() => {
+            let target = std::fs::read_link(full_path).unwrap_or_default();
+            return Some(Rejection::Dangling(target));
+        }
+        Err(_) => return Some(Rejection::NotFound),
+    };
+    if status.contains(&FileStatus::FS_DIRECTORY) {
+        return Some(Rejection::Directory);
+    }
+    // A noexec mount fails eaccess() whatever the mode says.
+    if let Ok(fs) = statvfs(full_path) {
+        if fs.flags().contains(FsFlags::ST_NOEXEC) {
+            return Some(Rejection::NoexecMount);
+        }
+    }
+    if metadata.file_type().is_socket() || metadata.file_type().is_fifo() {
+        return Some(Rejection::NotExecutable("not a regular file".to_string()));
+    }
+    Some(Rejection::NotExecutable(exec_denial(&metadata)))
+}
+
+/// The symlinks full_path goes through, ending with the file they resolve to
+fn symlink_chain(full_path: &Path) -> Vec<PathBuf> {
+    let mut chain = vec![full_path.to_path_buf()];
+    let mut current = full_path.to_path_buf();
+    while chain.len() <= MAX_SYMLINKS {
+        match std::fs::read_link(&current) {
+            Ok(target) => {
+                current = match current.parent() {
+                    Some(parent) => parent.join(&target),
+                    None => target,
+                };
+                chain.push(current.clone());
+            }
+            Err(_) => break,
+        }
+    }
+    // Symlinked directories on the way are only resolved by canonicalize.
+    if let Ok(resolved) = current.canonicalize() {
+        if resolved != current {
+            chain.push(resolved);
+        }
+    }
+    chain
+}
+
+/// Print every candidate for cmd in PATH with the reason it was taken or
+/// skipped, returning whether one was taken
+pub fn why_search(cmd: &str, config: &Config) -> UResult<bool> {
+    let (dirs, name) = search_dirs(Path::new(cmd), config);
+    let mut stdout = std::io::stdout();
+    let mut winner: Option<PathBuf> = None;
+    for (dir, skip) in dirs {
+        let full_path = dir.join(&name);
+        stdout.write_all(full_path.as_os_str().as_bytes())?;
+        match skip.or_else(|| rejection(&dir, &full_path)) {
+            Some(reason) => writeln!(stdout, ": {}", reason)?,
+            None if winner.is_some() => writeln!(stdout, ": found, but shadowed")?,
+            None => {
+                writeln!(stdout, ": found")?;
+                winner = Some(full_path);
+            }
+        }
+    }
+    match winner {
+        Some(full_path) => {
+            let chain = symlink_chain(&path_clean_up(full_path)?);
+            for (i, path) in chain.iter().enumerate() {
+                stdout.write_all(if i == 0 { b"\t" } else { b" -> " })?;
+                stdout.write_all(path.as_os_str().as_bytes())?;
+            }
+            stdout.write_all(b"\n")?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
 }
 
 /// Find command in PATH
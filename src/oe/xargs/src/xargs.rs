@@ -19,6 +19,7 @@ const USAGE: &str = help_usage!("xargs.md");
 /// This the main of xargs
 ///
 pub fn oemain(args: impl uucore::Args) -> UResult<()> {
+    xargs_common::init_locale();
     let (config, matches) = xargs_common::parse_xargs_cmd_args(args, ABOUT, USAGE)?;
     let handle_res = xargs_common::xargs_main(config, matches)?;
     set_exit_code(handle_res);
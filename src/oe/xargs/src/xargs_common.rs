@@ -17,15 +17,16 @@ use shell_quote::{Bash, QuoteExt};
 use std::{
     collections::HashMap,
     error::Error,
-    ffi::{OsStr, OsString},
+    ffi::{CStr, CString, OsStr, OsString},
     fmt::Display,
     fs::{self},
     io::{self, BufRead, BufReader, Read, Write},
     os::unix::ffi::{OsStrExt, OsStringExt},
     process::{Command, Stdio},
+    sync::Mutex,
 };
 use uucore::{
-    error::{UResult, USimpleError},
+    error::{strip_errno, UResult, USimpleError},
     format_usage,
 };
 
@@ -57,6 +58,10 @@ pub struct Options {
     pub replace_pat: Option<String>,
     ///
     pub eof: Option<Vec<u8>>,
+    ///
+    pub process_slot_var: Option<String>,
+    ///
+    pub show_limits: bool,
 }
 
 /// Command Options
@@ -76,6 +81,8 @@ pub mod options {
     ///
     pub static MAX_LINES: &str = "max-lines";
     ///
+    pub static MAX_LINES_POSIX: &str = "L";
+    ///
     pub static MAX_PROCS: &str = "max-procs";
     ///
     pub static INTERACTIVE: &str = "interactive";
@@ -91,6 +98,25 @@ pub mod options {
     pub static REPLACE: &str = "replace";
     ///
     pub static EOF: &str = "eof";
+    ///
+    pub static PROCESS_SLOT_VAR: &str = "process-slot-var";
+    ///
+    pub static SHOW_LIMITS: &str = "show-limits";
+}
+
+/// The options whose argument is optional, with the value they take when it
+/// is left out. Like getopt, the argument has to be attached to them.
+const OPTIONAL_ARGUMENTS: [(char, &str, &str); 3] = [
+    ('i', "replace", "{}"),
+    ('l', "max-lines", "1"),
+    ('e', "eof", ""),
+];
+
+/// One of -n, -L, -l or -I, which GNU xargs resolves in command line order.
+enum Batching<'a> {
+    Args(usize),
+    Lines(usize, &'static str),
+    Replace(&'a str),
 }
 
 impl Options {
@@ -103,19 +129,7 @@ impl Options {
             .value_of(options::DELIMITER)
             .map(|value| parse_delimiter(value).unwrap());
         let exit_if_pass_char_limit = args_matches.is_present(options::EXIT);
-        let max_args = args_matches
-            .value_of(options::MAX_ARGS)
-            .map(|value| value.parse().unwrap());
         let open_tty = args_matches.is_present(options::OPEN_TTY);
-        let max_lines = if args_matches.occurrences_of(options::MAX_LINES) > 0
-            && args_matches.value_of(options::MAX_LINES).is_none()
-        {
-            Some(1)
-        } else {
-            args_matches
-                .value_of(options::MAX_LINES)
-                .map(|s| s.parse().expect("Failed to parse value"))
-        };
         // The commented out code below is to obtain the value of max_procs, but the -P option cannot currently be implemented, so it is commented out.
         // let max_procs = args_matches
         //     .value_of(options::MAX_PROCS)
@@ -127,23 +141,18 @@ impl Options {
             .value_of(options::SIZE)
             .map(|value| value.parse().unwrap());
         let verbose = args_matches.get_flag(options::VERBOSE);
-        let replace_pat = if args_matches.is_present(options::REPLACE) {
-            Some(
-                args_matches
-                    .value_of(options::REPLACE)
-                    .map(|value| value.to_owned())
-                    .unwrap_or("{}".to_string()),
-            )
-        } else {
-            None
-        };
-        let eof = if args_matches.is_present(options::EOF) {
-            args_matches
-                .value_of(options::EOF)
-                .map(|eof_str| eof_str.as_bytes().to_vec())
-        } else {
-            None
-        };
+        let (max_args, max_lines, replace_pat) = resolve_batching(args_matches);
+        // -L and -I imply -x.
+        let exit_if_pass_char_limit =
+            exit_if_pass_char_limit || max_lines.is_some() || replace_pat.is_some();
+        let eof = args_matches
+            .value_of(options::EOF)
+            .filter(|eof_str| !eof_str.is_empty())
+            .map(|eof_str| eof_str.as_bytes().to_vec());
+        let process_slot_var = args_matches
+            .value_of(options::PROCESS_SLOT_VAR)
+            .map(|value| value.to_owned());
+        let show_limits = args_matches.is_present(options::SHOW_LIMITS);
         Ok(Self {
             arg_file,
             delimiter,
@@ -158,10 +167,81 @@ impl Options {
             verbose,
             replace_pat,
             eof,
+            process_slot_var,
+            show_limits,
         })
     }
 }
 
+/// Apply -n, -L, -l and -I in the order they were given. They are mutually
+/// exclusive, a later one overrides an earlier one with a warning, except
+/// that -n1 is accepted together with -I.
+fn resolve_batching(args_matches: &ArgMatches) -> (Option<usize>, Option<usize>, Option<String>) {
+    let mut given: Vec<(usize, Batching)> = vec![];
+    let mut collect = |id: &str, batching: &dyn Fn(&str) -> Batching<'_>| {
+        if let (Some(indices), Some(values)) =
+            (args_matches.indices_of(id), args_matches.values_of(id))
+        {
+            given.extend(indices.zip(values.map(batching)));
+        }
+    };
+    collect(options::MAX_ARGS, &|value| {
+        Batching::Args(value.parse().unwrap())
+    });
+    collect(options::MAX_LINES_POSIX, &|value| {
+        Batching::Lines(value.parse().unwrap(), "-L")
+    });
+    collect(options::MAX_LINES, &|value| {
+        Batching::Lines(value.parse().unwrap(), "--max-lines/-l")
+    });
+    collect(options::REPLACE, &|value| Batching::Replace(value));
+    given.sort_by_key(|(index, _)| *index);
+
+    let warn = |previous: &str, option: &str| {
+        eprintln!(
+            "xargs: warning: options {previous} and {option} are mutually exclusive, ignoring previous {previous} value"
+        );
+    };
+    let mut max_args: Option<usize> = None;
+    let mut max_lines: Option<usize> = None;
+    let mut replace_pat: Option<String> = None;
+    for (_, batching) in given {
+        match batching {
+            Batching::Args(args) => {
+                if max_lines.take().is_some() {
+                    warn("--max-lines", "--max-args/-n");
+                }
+                if args != 1 && replace_pat.take().is_some() {
+                    warn("--replace", "--max-args/-n");
+                }
+                max_args = Some(args);
+            }
+            Batching::Lines(lines, option) => {
+                if max_args.take().is_some() {
+                    warn("--max-args", option);
+                }
+                if replace_pat.take().is_some() {
+                    warn("--replace", option);
+                }
+                max_lines = Some(lines);
+            }
+            Batching::Replace(pat) => {
+                if max_lines.take().is_some() {
+                    warn("--max-lines", "--replace/-I/-i");
+                }
+                if max_args.take().is_some() {
+                    warn("--max-args", "--replace/-I/-i");
+                }
+                replace_pat = Some(pat.to_owned());
+            }
+        }
+    }
+    if replace_pat.is_some() {
+        max_args = None;
+    }
+    (max_args, max_lines, replace_pat)
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ArgumentKind {
     Initial,
@@ -266,24 +346,83 @@ impl MaxCharsCommandSizeLimiter {
             max_chars,
         }
     }
+}
 
-    #[cfg(windows)]
-    fn new_system(_env: &HashMap<OsString, OsString>) -> MaxCharsCommandSizeLimiter {
-        const MAX_CMDLINE: usize = 32767;
-        MaxCharsCommandSizeLimiter::new(MAX_CMDLINE)
-    }
+/// The command line size limits, as --show-limits reports them.
+struct SizeLimits {
+    env_size: usize,
+    posix_upper: usize,
+    buffer: usize,
+}
+
+impl SizeLimits {
+    /// POSIX wants at least this much room for arguments on all systems.
+    const POSIX_MIN: usize = 4096;
+    /// The size of the command buffer when -s is not given.
+    const DEFAULT_BUFFER: usize = 128 * 1024;
 
-    #[cfg(unix)]
-    fn new_system(env: &HashMap<OsString, OsString>) -> Self {
+    fn new(env: &HashMap<OsString, OsString>, requested: Option<usize>) -> Self {
         const ARG_HEADROOM: usize = 2048;
-        let arg_max = unsafe { uucore::libc::sysconf(uucore::libc::_SC_ARG_MAX) } as usize;
+        let arg_max = unsafe { uucore::libc::sysconf(uucore::libc::_SC_ARG_MAX) };
+        let arg_max = if arg_max > 0 {
+            arg_max as usize
+        } else {
+            Self::POSIX_MIN
+        };
 
         let env_size: usize = env
             .iter()
             .map(|(var, value)| count_osstr_chars_for_exec(var) + count_osstr_chars_for_exec(value))
             .sum();
+        let posix_upper = arg_max
+            .saturating_sub(ARG_HEADROOM + env_size)
+            .max(Self::POSIX_MIN);
+
+        let buffer = match requested {
+            Some(size) if size > posix_upper => {
+                eprintln!("xargs: value {size} for -s option should be <= {posix_upper}");
+                posix_upper
+            }
+            Some(size) => size,
+            None => Self::DEFAULT_BUFFER.min(posix_upper),
+        };
 
-        Self::new(arg_max - ARG_HEADROOM - env_size)
+        Self {
+            env_size,
+            posix_upper,
+            buffer,
+        }
+    }
+
+    fn show(&self, reading_stdin: bool) {
+        eprintln!("Your environment variables take up {} bytes", self.env_size);
+        eprintln!(
+            "POSIX upper limit on argument length (this system): {}",
+            self.posix_upper
+        );
+        eprintln!(
+            "POSIX smallest allowable upper limit on argument length (all systems): {}",
+            Self::POSIX_MIN
+        );
+        eprintln!(
+            "Maximum length of command we could actually use: {}",
+            self.posix_upper.saturating_sub(self.env_size)
+        );
+        eprintln!(
+            "Size of command buffer we are actually using: {}",
+            self.buffer
+        );
+        eprintln!(
+            "Maximum parallelism (--max-procs must be no greater): {}",
+            i32::MAX
+        );
+        if reading_stdin && unsafe { libc::isatty(libc::STDIN_FILENO) } == 1 {
+            eprintln!(
+                "\nExecution of xargs will continue now, and it will try to read its input \
+                and run commands; if this is not what you wanted to happen, please type the \
+                end-of-file keystroke."
+            );
+        }
     }
 }
 
@@ -426,13 +565,13 @@ pub enum CommandExecutionError {
 impl Display for CommandExecutionError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            CommandExecutionError::UrgentlyFailed => write!(f, "Command exited with code 255"),
+            CommandExecutionError::UrgentlyFailed => write!(f, "exited with status 255; aborting"),
             CommandExecutionError::Killed { signal } => {
-                write!(f, "Command was killed with signal {signal}")
+                write!(f, "terminated by signal {signal}")
             }
-            CommandExecutionError::CannotRun(err) => write!(f, "Command could not be run: {err}"),
-            CommandExecutionError::NotFound => write!(f, "Command not found"),
-            CommandExecutionError::Unknown => write!(f, "Unknown error running command"),
+            CommandExecutionError::CannotRun(err) => write!(f, "{}", strip_errno(err)),
+            CommandExecutionError::NotFound => write!(f, "No such file or directory"),
+            CommandExecutionError::Unknown => write!(f, "unknown error running command"),
         }
     }
 }
@@ -454,6 +593,15 @@ struct CommandBuilderOptions {
     eof: Option<Vec<u8>>,
     no_run_if_empty: bool,
     open_tty: bool,
+    /// The terminal answers to -p are read from. It is opened on the first
+    /// prompt and kept, so that answers typed ahead are not lost, and the
+    /// lock keeps prompts from interleaving when several commands run.
+    tty: Mutex<Option<BufReader<fs::File>>>,
+    /// The size of the command buffer, which a command line built by -I
+    /// has to fit into.
+    max_chars: usize,
+    /// Set to the slot of a child in its environment, for --process-slot-var.
+    process_slot_var: Option<String>,
 }
 
 impl CommandBuilderOptions {
@@ -496,8 +644,92 @@ impl CommandBuilderOptions {
             interactive: false,
             no_run_if_empty,
             open_tty,
+            tty: Mutex::new(None),
+            max_chars: usize::MAX,
+            process_slot_var: None,
         })
     }
+
+    /// Print the command line followed by "?..." and read the answer from
+    /// /dev/tty. Only an answer matching the locale's yes expression runs it.
+    fn confirm(&self, command_line: &str) -> Result<bool, XargsError> {
+        let mut tty = self.tty.lock().unwrap_or_else(|e| e.into_inner());
+        if tty.is_none() {
+            let file = fs::File::open("/dev/tty")
+                .map_err(|e| format!("failed to open /dev/tty for reading: {}", strip_errno(&e)))?;
+            *tty = Some(BufReader::new(file));
+        }
+
+        // One write, so that typed-ahead answers echoed by the terminal
+        // cannot land in the middle of the prompt.
+        io::stderr().write_all(format!("{command_line} ?...").as_bytes())?;
+
+        let mut response = String::new();
+        tty.as_mut().unwrap().read_line(&mut response)?;
+        Ok(is_affirmative(response.trim_end_matches('\n')))
+    }
+
+    /// The stdin of a child: /dev/tty for -o, /dev/null when the arguments
+    /// are read from stdin. Each child opens its own terminal descriptor.
+    fn child_stdin(&self) -> Option<Stdio> {
+        if self.open_tty {
+            match fs::File::open("/dev/tty") {
+                Ok(tty) => return Some(tty.into()),
+                // Like GNU xargs, the command still runs, without a terminal.
+                Err(e) => eprintln!("xargs: /dev/tty: {}", strip_errno(&e)),
+            }
+            return Some(Stdio::null());
+        }
+        if self.close_stdin {
+            Some(Stdio::null())
+        } else {
+            None
+        }
+    }
+}
+
+/// Take LC_CTYPE and LC_MESSAGES from the environment, for the yes
+/// expression [is_affirmative] matches -p answers against. setlocale() is
+/// process-wide and not thread-safe, so this runs once, before any command
+/// is started.
+pub fn init_locale() {
+    let environment = CString::default();
+    unsafe {
+        libc::setlocale(libc::LC_CTYPE, environment.as_ptr());
+        libc::setlocale(libc::LC_MESSAGES, environment.as_ptr());
+    }
+}
+
+/// Match a response against the yes expression of LC_MESSAGES, the way
+/// rpmatch(3) does, falling back to the POSIX locale's "^[yY]".
+fn is_affirmative(response: &str) -> bool {
+    let response = match CString::new(response) {
+        Ok(response) => response,
+        Err(_) => return false,
+    };
+    let yesexpr = unsafe {
+        let expr = libc::nl_langinfo(libc::YESEXPR);
+        if expr.is_null() || *expr == 0 {
+            CString::new("^[yY]").unwrap()
+        } else {
+            CStr::from_ptr(expr).to_owned()
+        }
+    };
+
+    unsafe {
+        let mut regex: libc::regex_t = std::mem::zeroed();
+        if libc::regcomp(
+            &mut regex,
+            yesexpr.as_ptr(),
+            libc::REG_EXTENDED | libc::REG_NOSUB,
+        ) != 0
+        {
+            return matches!(response.as_bytes().first(), Some(b'y' | b'Y'));
+        }
+        let matched = libc::regexec(&regex, response.as_ptr(), 0, std::ptr::null_mut(), 0) == 0;
+        libc::regfree(&mut regex);
+        matched
+    }
 }
 
 struct CommandBuilder<'options> {
@@ -533,7 +765,10 @@ impl CommandBuilder<'_> {
         }
     }
 
-    fn execute_command(self, mut command: Command) -> Result<CommandResult, CommandExecutionError> {
+    fn execute_command(
+        &self,
+        mut command: Command,
+    ) -> Result<CommandResult, CommandExecutionError> {
         match &self.options.action {
             ExecAction::Command(_) => match command.status() {
                 Ok(status) => {
@@ -567,8 +802,8 @@ impl CommandBuilder<'_> {
             ExecAction::Echo => {
                 println!(
                     "{}",
-                    self.extra_args
-                        .iter()
+                    command
+                        .get_args()
                         .map(|arg| arg.to_string_lossy())
                         .collect::<Vec<_>>()
                         .join(" ")
@@ -578,7 +813,7 @@ impl CommandBuilder<'_> {
         }
     }
 
-    fn execute(self) -> Result<CommandResult, CommandExecutionError> {
+    fn execute(self) -> Result<CommandResult, XargsError> {
         let (entry_point, initial_args): (&OsStr, &[OsString]) = match &self.options.action {
             ExecAction::Command(args) => (&args[0], &args[1..]),
             ExecAction::Echo => (OsStr::new("echo"), &[]),
@@ -587,39 +822,49 @@ impl CommandBuilder<'_> {
             return Ok(CommandResult::Success);
         }
 
-        let mut command = Command::new(entry_point);
-
-        if let Some(replace_str) = &self.options.replace_pat {
+        let mut command = if let Some(replace_str) = &self.options.replace_pat {
             let replacement = self
                 .extra_args
                 .iter()
                 .map(|s| s.to_string_lossy())
                 .collect::<Vec<_>>()
                 .join(" ");
-            let initial_args: Vec<OsString> = initial_args
-                .iter()
+            if replacement.is_empty() {
+                return Ok(CommandResult::Success);
+            }
+            // The command name is replaced in as well as its arguments.
+            let mut replaced = std::iter::once(entry_point)
+                .chain(initial_args.iter().map(|arg| arg.as_os_str()))
                 .map(|arg| {
                     let arg_str = arg.to_string_lossy();
                     OsString::from(arg_str.replace(replace_str, &replacement))
-                })
-                .collect();
-            if replacement.len() > 0 {
-                command.args(&initial_args);
-            } else {
-                return Ok(CommandResult::Success);
+                });
+            let entry_point = replaced.next().unwrap();
+            let initial_args: Vec<OsString> = replaced.collect();
+
+            let size: usize = std::iter::once(&entry_point)
+                .chain(&initial_args)
+                .map(|arg| count_osstr_chars_for_exec(arg))
+                .sum();
+            if size > self.options.max_chars {
+                return Err(XargsError::ArgumentTooLarge);
             }
+
+            let mut command = match self.options.action {
+                ExecAction::Command(_) => Command::new(entry_point),
+                ExecAction::Echo => Command::new("echo"),
+            };
+            command.args(&initial_args);
+            command
         } else {
+            let mut command = Command::new(entry_point);
             command.args(initial_args).args(&self.extra_args);
+            command
         };
 
-        if self.options.close_stdin {
-            command.stdin(Stdio::null());
-        }
-
-        if self.options.open_tty {
-            let tty =
-                fs::File::open("/dev/tty").map_err(|e| format!("Failed to open /dev/tty: {}", e));
-            command.stdin(tty.unwrap());
+        if let Some(var) = &self.options.process_slot_var {
+            // Commands run one at a time, so every child gets the first slot.
+            command.env(var, "0");
         }
 
         if self.options.verbose {
@@ -640,24 +885,27 @@ impl CommandBuilder<'_> {
             if args_str.ends_with(' ') {
                 args_str.pop();
             }
-            eprint!("{args_str}");
 
             if self.options.interactive {
-                io::stderr().flush().ok();
-                let response = prompt_user_and_get_response()
-                    .map_err(|e| CommandExecutionError::CannotRun(e))?;
-
-                if !response {
-                    eprint!("\n");
-                    io::stderr().flush().ok();
+                if !self.options.confirm(&args_str)? {
                     return Ok(CommandResult::Success);
                 }
             } else {
-                eprint!("\n");
+                eprintln!("{args_str}");
             }
         }
 
+        if let Some(stdin) = self.options.child_stdin() {
+            command.stdin(stdin);
+        }
+
+        let program = command.get_program().to_string_lossy().into_owned();
         self.execute_command(command)
+            .map_err(|e| XargsError::CommandExecution(program, e))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.extra_args.is_empty()
     }
 }
 
@@ -668,16 +916,20 @@ trait ArgumentReader {
 struct WhitespaceDelimitedArgumentReader<R: Read> {
     rd: R,
     pending: Vec<u8>,
+    /// For -I, where only newlines separate arguments and leading blanks
+    /// of a line are dropped.
+    whole_lines: bool,
 }
 
 impl<R> WhitespaceDelimitedArgumentReader<R>
 where
     R: Read,
 {
-    fn new(rd: R) -> Self {
+    fn new(rd: R, whole_lines: bool) -> Self {
         Self {
             rd,
             pending: vec![],
+            whole_lines,
         }
     }
 }
@@ -713,9 +965,13 @@ where
 
                 if bytes_read == 0 {
                     if let Some(Escape::Quote(q)) = &escape {
+                        let quote = if *q == b'"' { "double" } else { "single" };
                         return Err(io::Error::new(
                             io::ErrorKind::InvalidInput,
-                            format!("unmatched double quote: {q}"),
+                            format!(
+                                "unmatched {quote} quote; by default quotes are special to \
+                                xargs unless you use the -0 option"
+                            ),
                         ));
                     } else if i == 0 {
                         return Ok(None);
@@ -751,6 +1007,11 @@ where
                         result.push(c)
                     }
                 }
+                (None, c) if self.whole_lines && c != b'\n' && c.is_ascii_whitespace() => {
+                    if !result.is_empty() {
+                        result.push(c)
+                    }
+                }
                 (None, c) if c.is_ascii_whitespace() => {
                     if !result.is_empty() {
                         terminated_by_newline = c == b'\n';
@@ -831,7 +1092,9 @@ pub enum XargsError {
     ///
     ArgumentTooLarge,
     ///
-    CommandExecution(CommandExecutionError),
+    ArgumentListTooLong,
+    ///
+    CommandExecution(String, CommandExecutionError),
     ///
     Io(io::Error),
     ///
@@ -841,8 +1104,9 @@ pub enum XargsError {
 impl Display for XargsError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            XargsError::ArgumentTooLarge => write!(f, "Argument too large"),
-            XargsError::CommandExecution(e) => write!(f, "{e}"),
+            XargsError::ArgumentTooLarge => write!(f, "argument line too long"),
+            XargsError::ArgumentListTooLong => write!(f, "argument list too long"),
+            XargsError::CommandExecution(command, e) => write!(f, "{command}: {e}"),
             XargsError::Io(e) => write!(f, "{e}"),
             XargsError::Untyped(s) => write!(f, "{s}"),
         }
@@ -851,6 +1115,22 @@ impl Display for XargsError {
 
 impl Error for XargsError {}
 
+impl XargsError {
+    /// The exit status of xargs, as findutils documents it.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            XargsError::CommandExecution(_, e) => match e {
+                CommandExecutionError::UrgentlyFailed => 124,
+                CommandExecutionError::Killed { .. } => 125,
+                CommandExecutionError::CannotRun(_) => 126,
+                CommandExecutionError::NotFound => 127,
+                CommandExecutionError::Unknown => 1,
+            },
+            _ => 1,
+        }
+    }
+}
+
 impl From<String> for XargsError {
     fn from(s: String) -> Self {
         Self::Untyped(s)
@@ -863,12 +1143,6 @@ impl From<&'_ str> for XargsError {
     }
 }
 
-impl From<CommandExecutionError> for XargsError {
-    fn from(e: CommandExecutionError) -> Self {
-        Self::CommandExecution(e)
-    }
-}
-
 impl From<io::Error> for XargsError {
     fn from(e: io::Error) -> Self {
         Self::Io(e)
@@ -909,16 +1183,17 @@ fn process_input(
                     if arg_str == eof_str {
                         current_builder.drop_arg();
                         result.combine(current_builder.execute()?);
-                        return Ok(CommandResult::Success);
+                        return Ok(result);
                     }
                 }
             }
             Err(ExhaustedCommandSpace { arg, out_of_chars }) => {
                 if out_of_chars
+                    && !current_builder.is_empty()
                     && options.exit_if_pass_char_limit
                     && (options.max_args.is_some() || options.max_lines.is_some())
                 {
-                    return Err(XargsError::ArgumentTooLarge);
+                    return Err(XargsError::ArgumentListTooLong);
                 } else if have_pending_command {
                     result.combine(current_builder.execute()?);
                 }
@@ -938,22 +1213,6 @@ fn process_input(
     Ok(result)
 }
 
-fn prompt_user_and_get_response() -> io::Result<bool> {
-    eprint!("?... ");
-    io::stderr().flush()?;
-
-    let tty = match std::fs::File::open("/dev/tty") {
-        Ok(t) => io::BufReader::new(t),
-        Err(e) => return Err(e),
-    };
-
-    match tty.lines().next() {
-        Some(Ok(line)) => Ok(line.trim().eq_ignore_ascii_case("y")),
-        Some(Err(e)) => Err(e),
-        None => Ok(false),
-    }
-}
-
 fn validate_positive_usize(s: String) -> Result<(), String> {
     match s.parse::<usize>() {
         Ok(v) if v > 0 => Ok(()),
@@ -996,21 +1255,94 @@ pub fn parse_xargs_cmd_args(
     about: &str,
     usage: &str,
 ) -> UResult<(Options, clap::ArgMatches)> {
-    let arg_list = args.collect_lossy();
-    let mut command = xargs_app(about, usage);
-    let mut usage_doc = Vec::new();
-    command.write_help(&mut usage_doc).unwrap();
-
-    let matches = command.get_matches_from(
-        &arg_list
-            .iter()
-            .map(std::convert::AsRef::as_ref)
-            .collect::<Vec<&str>>(),
-    );
+    let command = xargs_app(about, usage);
+    let arg_list = attach_optional_arguments(&command, args.collect_lossy());
+    let matches = command.try_get_matches_from(arg_list)?;
     let options = Options::from(&matches)?;
     Ok((options, matches))
 }
 
+/// getopt only takes the argument of -i, -l and -e when it is attached, and
+/// the one of --replace, --max-lines and --eof after a '='. Spell them all as
+/// `--long=value` so that clap does not take the next word as their argument.
+fn attach_optional_arguments(command: &clap::Command, args: Vec<String>) -> Vec<String> {
+    let takes_value = |short: char| {
+        command
+            .get_arguments()
+            .any(|arg| arg.get_short() == Some(short) && arg.is_takes_value_set())
+    };
+    let long_match = |prefix: &str| {
+        let mut matching = command
+            .get_arguments()
+            .filter(|arg| matches!(arg.get_long(), Some(long) if long.starts_with(prefix)));
+        match (matching.next(), matching.next()) {
+            (Some(arg), None) => Some(arg),
+            _ => None,
+        }
+    };
+
+    let mut normalized = Vec::with_capacity(args.len());
+    let mut args = args.into_iter();
+    normalized.extend(args.next());
+    while let Some(arg) = args.next() {
+        if arg == "--" || arg == "-" || !arg.starts_with('-') {
+            normalized.push(arg);
+            break;
+        }
+
+        if let Some(name) = arg.strip_prefix("--") {
+            if name.contains('=') {
+                normalized.push(arg);
+                continue;
+            }
+            match long_match(name) {
+                Some(found) if found.is_takes_value_set() => {
+                    let long = found.get_long().unwrap();
+                    match OPTIONAL_ARGUMENTS.iter().find(|(_, name, _)| *name == long) {
+                        Some((_, name, default)) => normalized.push(format!("--{name}={default}")),
+                        None => {
+                            normalized.push(arg);
+                            normalized.extend(args.next());
+                        }
+                    }
+                }
+                _ => normalized.push(arg),
+            }
+            continue;
+        }
+
+        let mut flags = String::from("-");
+        for (i, c) in arg.char_indices().skip(1) {
+            if let Some((_, name, default)) =
+                OPTIONAL_ARGUMENTS.iter().find(|(short, ..)| *short == c)
+            {
+                let value = &arg[i + c.len_utf8()..];
+                let value = if value.is_empty() { *default } else { value };
+                if flags.len() > 1 {
+                    normalized.push(std::mem::take(&mut flags));
+                }
+                normalized.push(format!("--{name}={value}"));
+                break;
+            }
+            flags.push(c);
+            if takes_value(c) {
+                let attached = i + c.len_utf8() < arg.len();
+                flags.push_str(&arg[i + c.len_utf8()..]);
+                normalized.push(std::mem::take(&mut flags));
+                if !attached {
+                    normalized.extend(args.next());
+                }
+                break;
+            }
+        }
+        if flags.len() > 1 {
+            normalized.push(flags);
+        }
+    }
+    normalized.extend(args);
+    normalized
+}
+
 /// Create command.
 pub fn xargs_app<'a>(about: &'a str, usage: &'a str) -> clap::Command<'a> {
     clap::Command::new(uucore::util_name())
@@ -1030,6 +1362,7 @@ pub fn xargs_app<'a>(about: &'a str, usage: &'a str) -> clap::Command<'a> {
             Arg::new(options::ARG_FILE)
                 .short('a')
                 .long(options::ARG_FILE)
+                .takes_value(true)
                 .help("Read arguments from the given file instead of stdin"),
         )
         .arg(
@@ -1046,8 +1379,6 @@ pub fn xargs_app<'a>(about: &'a str, usage: &'a str) -> clap::Command<'a> {
                 .long(options::EOF)
                 .takes_value(true)
                 .visible_short_alias('e')
-                .max_values(1)
-                .min_values(0)
                 .help(
                     "set logical EOF string; if END occursas a line of input, \
                     the rest of the input isignored (ignored if -0 or -d was specified)",
@@ -1059,8 +1390,6 @@ pub fn xargs_app<'a>(about: &'a str, usage: &'a str) -> clap::Command<'a> {
                 .long(options::REPLACE)
                 .takes_value(true)
                 .visible_short_alias('i')
-                .max_values(1)
-                .min_values(0)
                 .help(
                     "Replace R in INITIAL-ARGS with names read from standard input; \
                     if R is unspecified, assume {}",
@@ -1093,19 +1422,24 @@ pub fn xargs_app<'a>(about: &'a str, usage: &'a str) -> clap::Command<'a> {
                 ),
         )
         .arg(
-            Arg::new(options::MAX_LINES)
+            Arg::new(options::MAX_LINES_POSIX)
                 .short('L')
                 .takes_value(true)
-                .long(options::MAX_LINES)
-                .visible_short_alias('l')
-                .min_values(0)
-                .max_values(1)
+                .value_name("MAX-LINES")
                 .validator(|s| validate_positive_usize(s.to_string()))
                 .help(
                     "Set the max number of lines from stdin to be passed to each \
                     command invocation (mutually exclusive with -n)",
                 ),
         )
+        .arg(
+            Arg::new(options::MAX_LINES)
+                .short('l')
+                .takes_value(true)
+                .long(options::MAX_LINES)
+                .validator(|s| validate_positive_usize(s.to_string()))
+                .help("Like -L, but MAX-LINES is optional and defaults to 1"),
+        )
         .arg(
             Arg::new(options::MAX_PROCS)
                 .short('P')
@@ -1114,6 +1448,18 @@ pub fn xargs_app<'a>(about: &'a str, usage: &'a str) -> clap::Command<'a> {
                 .validator(|s| validate_positive_usize(s.to_string()))
                 .help("Run up to this many commands in parallel [NOT IMPLEMENTED]"),
         )
+        .arg(
+            Arg::new(options::PROCESS_SLOT_VAR)
+                .long(options::PROCESS_SLOT_VAR)
+                .takes_value(true)
+                .value_name("VAR")
+                .help("Set the environment variable VAR in child processes"),
+        )
+        .arg(
+            Arg::new(options::SHOW_LIMITS)
+                .long(options::SHOW_LIMITS)
+                .help("Show limits on command-line length"),
+        )
         .arg(
             Arg::new(options::INTERACTIVE)
                 .short('p')
@@ -1180,53 +1526,25 @@ pub fn handle_input(
     };
 
     let env = std::env::vars_os().collect();
+    let limits = SizeLimits::new(&env, options.size);
+    if options.show_limits {
+        limits.show(options.arg_file.is_none());
+    }
 
     let mut limiters = LimiterCollection::new();
 
-    if options.max_args.is_some() && options.replace_pat.is_some() {
-        options.max_args = None;
-        eprintln!("xargs: warning: options --max-args and --replace/-I/-i are mutually exclusive, ignoring previous --max-args value");
-    }
-
     if options.eof.is_some() && (options.delimiter.is_some() || options.null) {
         options.eof = None;
         eprintln!("xargs: warning: the -E option has no effect if -0 or -d is used.\n");
     }
 
-    match (
-        matches.index_of(options::EOF),
-        matches.index_of(options::REPLACE),
-    ) {
-        (Some(exit_index), Some(replace_index)) => {
-            if exit_index > replace_index {
-                options.eof = None;
-            }
-        }
-        _ => {}
+    if let Some(max_args) = options.max_args {
+        limiters.add(MaxArgsCommandSizeLimiter::new(max_args));
     }
-
-    match (options.max_args, options.max_lines) {
-        (Some(max_args), Some(max_lines)) => {
-            eprintln!(
-                    "xargs: warning: options --max-lines and --max-args/-n are mutually exclusive, ignoring previous --max-lines value");
-            if matches.indices_of(options::MAX_LINES).unwrap().last()
-                > matches.indices_of(options::MAX_ARGS).unwrap().last()
-            {
-                limiters.add(MaxLinesCommandSizeLimiter::new(max_lines));
-            } else {
-                limiters.add(MaxArgsCommandSizeLimiter::new(max_args));
-            }
-        }
-        (Some(max_args), None) => limiters.add(MaxArgsCommandSizeLimiter::new(max_args)),
-        (None, Some(max_lines)) => limiters.add(MaxLinesCommandSizeLimiter::new(max_lines)),
-        (None, None) => (),
+    if let Some(max_lines) = options.max_lines {
+        limiters.add(MaxLinesCommandSizeLimiter::new(max_lines));
     }
-
-    if let Some(max_chars) = options.size {
-        limiters.add(MaxCharsCommandSizeLimiter::new(max_chars));
-    }
-
-    limiters.add(MaxCharsCommandSizeLimiter::new_system(&env));
+    limiters.add(MaxCharsCommandSizeLimiter::new(limits.buffer));
 
     let mut builder_options = CommandBuilderOptions::new(
         action,
@@ -1236,9 +1554,11 @@ pub fn handle_input(
         options.no_run_if_empty,
         options.open_tty,
     )
-    .map_err(|_| "Base command and environment are too large to fit into one command execution")?;
+    .map_err(|_| "cannot fit single argument within argument list size limit")?;
 
     builder_options.verbose = options.verbose;
+    builder_options.max_chars = limits.buffer;
+    builder_options.process_slot_var = options.process_slot_var.clone();
 
     if options.interactive {
         builder_options.verbose = true;
@@ -1247,7 +1567,10 @@ pub fn handle_input(
     builder_options.close_stdin = options.arg_file.is_none();
 
     let args_file: Box<dyn Read> = if let Some(path) = &options.arg_file {
-        Box::new(fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?)
+        Box::new(
+            fs::File::open(path)
+                .map_err(|e| format!("Cannot open input file '{}': {}", path, strip_errno(&e)))?,
+        )
     } else {
         Box::new(io::stdin())
     };
@@ -1255,7 +1578,10 @@ pub fn handle_input(
     let args: Box<dyn ArgumentReader> = if let Some(delimiter) = delimiter {
         Box::new(ByteDelimitedArgumentReader::new(args_file, delimiter))
     } else {
-        Box::new(WhitespaceDelimitedArgumentReader::new(args_file))
+        Box::new(WhitespaceDelimitedArgumentReader::new(
+            args_file,
+            options.replace_pat.is_some(),
+        ))
     };
 
     let result = process_input(builder_options, args, &options)?;
@@ -1267,23 +1593,6 @@ pub fn xargs_main(options: Options, matches: ArgMatches) -> UResult<i32> {
     match handle_input(options, matches) {
         Ok(CommandResult::Success) => Ok(0),
         Ok(CommandResult::Failure) => Ok(123),
-        Err(e) => {
-            eprintln!("Error: {e}");
-            if let XargsError::CommandExecution(cx) = e {
-                match cx {
-                    CommandExecutionError::UrgentlyFailed => {
-                        Err(USimpleError::new(124, "Urgently failed"))
-                    }
-                    CommandExecutionError::Killed { .. } => Err(USimpleError::new(125, "Killed")),
-                    CommandExecutionError::CannotRun(_) => {
-                        Err(USimpleError::new(126, "Cannot run"))
-                    }
-                    CommandExecutionError::NotFound => Err(USimpleError::new(127, "Not found")),
-                    CommandExecutionError::Unknown => Err(USimpleError::new(1, "Unknown error")),
-                }
-            } else {
-                Err(USimpleError::new(1, "Unknown error"))
-            }
-        }
+        Err(e) => Err(USimpleError::new(e.exit_code(), e.to_string())),
     }
 }
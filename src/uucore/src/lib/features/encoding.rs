@@ -25,10 +25,19 @@ pub enum DecodeError {
     DecodeZ85(#[from] z85::DecodeError),
     #[error("{}", _0)]
     Io(#[from] io::Error),
+    #[error("invalid input at byte {}", _0)]
+    InvalidInput(u64),
 }
 
 pub enum EncodeError {
     Z85InputLenNotMultipleOf4,
+    Io(io::Error),
+}
+
+impl From<io::Error> for EncodeError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
 }
 
 pub type DecodeResult = Result<Vec<u8>, DecodeError>;
@@ -46,6 +55,31 @@ pub enum Format {
 }
 use self::Format::*;
 
+impl Format {
+    /// Number of input bytes encoded without padding as one unit.
+    fn encode_block(self) -> usize {
+        match self {
+            Base64 | Base64Url => 3,
+            Base32 | Base32Hex => 5,
+            Base16 | Base2Lsbf | Base2Msbf => 1,
+            Z85 => 4,
+        }
+    }
+
+    /// Number of encoded characters decoded as one unit.
+    fn decode_block(self) -> usize {
+        match self {
+            Base64 | Base64Url => 4,
+            Base32 | Base32Hex | Base2Lsbf | Base2Msbf => 8,
+            Base16 => 2,
+            Z85 => 5,
+        }
+    }
+}
+
+/// Size of the buffers read by [`Data`]; a multiple of every encoding's input block.
+const CHUNK_SIZE: usize = 64 * 1024 - 64 * 1024 % 60;
+
 const BASE2LSBF: Encoding = new_encoding! {
     symbols: "01",
     bit_order: LeastSignificantFirst,
@@ -137,28 +171,134 @@ impl<R: Read> Data<R> {
         self
     }
 
-    pub fn decode(&mut self) -> DecodeResult {
-        let mut buf = vec![];
-        self.input.read_to_end(&mut buf)?;
-        if self.ignore_garbage {
-            buf.retain(|c| self.alphabet.contains(c));
-        } else {
-            buf.retain(|&c| c != b'\r' && c != b'\n');
-        };
-        decode(self.format, &buf)
+    /// Decode the whole input into `output`, one chunk at a time.
+    ///
+    /// Characters are only decoded in whole blocks, so the result is the same as
+    /// decoding the input at once. On invalid input, the error carries the byte
+    /// offset of the offending character in the original input.
+    pub fn decode_to<W: Write>(&mut self, mut output: W) -> Result<(), DecodeError> {
+        let block = self.format.decode_block();
+        let mut buf = vec![0; CHUNK_SIZE];
+        // Kept characters waiting to be decoded, with their offsets in the input.
+        let mut pending: Vec<u8> = Vec::with_capacity(CHUNK_SIZE + 2 * block);
+        let mut offsets: Vec<u64> = Vec::with_capacity(CHUNK_SIZE + 2 * block);
+        let mut consumed = 0u64;
+        let mut first = true;
+
+        loop {
+            let n = read_full(&mut self.input, &mut buf)?;
+            for (i, &c) in buf[..n].iter().enumerate() {
+                let keep = if self.ignore_garbage {
+                    self.alphabet.contains(&c)
+                } else {
+                    c != b'\r' && c != b'\n'
+                };
+                if keep {
+                    pending.push(c);
+                    offsets.push(consumed + i as u64);
+                }
+            }
+            consumed += n as u64;
+            let eof = n < buf.len();
+
+            // Hold back the last whole block until the end: Z85 treats a final
+            // block starting with '#' as padding.
+            let len = if eof {
+                pending.len()
+            } else {
+                (pending.len() / block).saturating_sub(1) * block
+            };
+            if len > 0 || eof {
+                if first && matches!(self.format, Z85) && pending.starts_with(b"#") {
+                    return Err(DecodeError::InvalidInput(offsets[0]));
+                }
+                first = false;
+                // Report stray characters before any length error they cause.
+                let alphabet = self.alphabet;
+                if let Some(pos) = pending[..len].iter().position(|c| !alphabet.contains(c)) {
+                    return Err(DecodeError::InvalidInput(offsets[pos]));
+                }
+                let res = decode(self.format, &pending[..len]).map_err(|err| {
+                    let pos = match err {
+                        DecodeError::Decode(ref e) => e.position,
+                        DecodeError::DecodeZ85(z85::DecodeError::InvalidByte(pos, _))
+                        | DecodeError::DecodeZ85(z85::DecodeError::InvalidChunk(pos)) => pos,
+                        DecodeError::DecodeZ85(_) => len - len % block,
+                        _ => return err,
+                    };
+                    DecodeError::InvalidInput(offsets.get(pos).copied().unwrap_or(consumed))
+                })?;
+                output.write_all(&res)?;
+                pending.drain(..len);
+                offsets.drain(..len);
+            }
+            if eof {
+                break;
+            }
+        }
+        output.flush()?;
+        Ok(())
+    }
+
+    /// Encode the whole input into `output`, one chunk at a time.
+    ///
+    /// Chunks are a multiple of the encoding's block size, so no padding is
+    /// emitted before the end, and lines are wrapped across chunk boundaries.
+    pub fn encode_to<W: Write>(&mut self, mut output: W) -> Result<(), EncodeError> {
+        debug_assert_eq!(CHUNK_SIZE % self.format.encode_block(), 0);
+        let mut buf = vec![0; CHUNK_SIZE];
+        let mut column = 0;
+
+        loop {
+            let n = read_full(&mut self.input, &mut buf)?;
+            let res = encode(self.format, &buf[..n])?;
+            self.write_wrapped(&mut output, &mut column, res.as_bytes())?;
+            if n < buf.len() {
+                break;
+            }
+        }
+        if self.line_wrap > 0 && column > 0 {
+            output.write_all(b"\n")?;
+        }
+        output.flush()?;
+        Ok(())
     }
 
-    pub fn encode(&mut self) -> Result<String, EncodeError> {
-        let mut buf: Vec<u8> = vec![];
-        self.input.read_to_end(&mut buf).unwrap();
-        encode(self.format, buf.as_slice())
+    fn write_wrapped<W: Write>(
+        &self,
+        output: &mut W,
+        column: &mut usize,
+        mut res: &[u8],
+    ) -> io::Result<()> {
+        if self.line_wrap == 0 {
+            return output.write_all(res);
+        }
+        while !res.is_empty() {
+            let len = res.len().min(self.line_wrap - *column);
+            output.write_all(&res[..len])?;
+            res = &res[len..];
+            *column += len;
+            if *column == self.line_wrap {
+                output.write_all(b"\n")?;
+                *column = 0;
+            }
+        }
+        Ok(())
     }
 }
 
-// NOTE: this will likely be phased out at some point
-pub fn wrap_print<R: Read>(data: &Data<R>, res: &str) {
-    let stdout = io::stdout();
-    wrap_write(stdout.lock(), data.line_wrap, res).unwrap();
+/// Fill `buf` from `input`, returning less than its length only at end of input.
+fn read_full<R: Read>(input: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match input.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
 }
 
 pub fn wrap_write<W: Write>(mut writer: W, line_wrap: usize, res: &str) -> io::Result<()> {
@@ -284,3 +284,93 @@ fn test_wrong_option_ls() {
         .no_stdout()
         .stderr_contains(expect_contains);
 }
+
+#[test]
+fn test_list_values_sorted() {
+    let ts = TestScenario::new(util_name!());
+    for (name, value) in [("zeta", "last"), ("alpha", "two words"), ("mid", "")] {
+        ts.ucmd()
+            .args(&["-q", "-s", name, "-V", value, TEST_FILE])
+            .succeeds();
+    }
+    ts.ucmd()
+        .args(&["-q", "-s", "bin", TEST_FILE])
+        .pipe_in(&b"\x00\xff\n"[..])
+        .succeeds();
+
+    ts.ucmd()
+        .args(&["-ql", TEST_FILE])
+        .succeeds()
+        .stdout_only("alpha\nbin\nmid\nzeta\n");
+    ts.ucmd()
+        .args(&["-lv", TEST_FILE])
+        .succeeds()
+        .stdout_only(
+            "user alpha \"two words\"\nuser bin \"\\000\\377\\n\"\nuser mid \"\"\nuser zeta \"last\"\n",
+        );
+}
+
+#[test]
+fn test_list_default_namespaces() {
+    let ts = TestScenario::new(util_name!());
+    ts.ucmd()
+        .args(&["-q", "-s", "u1", "-V", "1", TEST_FILE])
+        .succeeds();
+    // The trusted namespace needs CAP_SYS_ADMIN.
+    if !ts
+        .ucmd()
+        .args(&["-R", "-q", "-s", "t1", "-V", "1", TEST_FILE])
+        .run()
+        .succeeded()
+    {
+        return;
+    }
+
+    // Without a filter, names of all but the system namespace are listed
+    // without prefix.
+    ts.ucmd()
+        .args(&["-ql", TEST_FILE])
+        .succeeds()
+        .stdout_only("u1\nt1\n");
+    ts.ucmd()
+        .args(&["-ql", "--namespace=user", TEST_FILE])
+        .succeeds()
+        .stdout_only("u1\n");
+    ts.ucmd()
+        .args(&["-ql", "-R", TEST_FILE])
+        .succeeds()
+        .stdout_only("t1\n");
+    ts.ucmd()
+        .args(&["-ql", "--namespace=all", TEST_FILE])
+        .succeeds()
+        .stdout_contains("user.u1\ntrusted.t1\n");
+}
+
+#[test]
+fn test_list_namespace() {
+    let ts = TestScenario::new(util_name!());
+    ts.ucmd()
+        .args(&["-q", "-s", "x", "-V", "1", TEST_FILE])
+        .succeeds();
+
+    ts.ucmd()
+        .args(&["-ql", "--namespace=all", TEST_FILE])
+        .succeeds()
+        .stdout_contains("user.x\n");
+    ts.ucmd()
+        .args(&["-ql", "--namespace=security", TEST_FILE])
+        .succeeds()
+        .stdout_does_not_contain("x\n");
+    ts.ucmd()
+        .args(&["-l", "--namespace=other", TEST_FILE])
+        .fails()
+        .usage_error("invalid namespace 'other'");
+    ts.ucmd()
+        .args(&["-S", "-l", "--namespace=user", TEST_FILE])
+        .fails()
+        .usage_error("Only one of -R, -S or --namespace allowed");
+    ts.ucmd()
+        .args(&["-g", "x", "-v", TEST_FILE])
+        .fails()
+        .usage_error("-v and --namespace only allowed with -l");
+}
@@ -51,7 +51,49 @@ fn test_garbage() {
         .arg("-d")
         .pipe_in(input)
         .fails()
-        .stderr_only("base32: error: invalid input\n");
+        .stderr_only("base32: error: invalid input at byte 0\n");
+}
+
+#[test]
+fn test_invalid_input_offset() {
+    // The offset counts every input byte, including the skipped newline.
+    new_ucmd!()
+        .arg("-d")
+        .pipe_in("JBSWY3DP\nFQQFO!33SNRSCC===\n") // spell-checker:disable-line
+        .fails()
+        .stderr_only("base32: error: invalid input at byte 14\n");
+
+    // An error past the first read buffer still reports its absolute offset.
+    let (at, mut ucmd) = at_and_ucmd!();
+    let mut input = "A".repeat(200_000);
+    input.push('!');
+    at.write("bad.txt", &input);
+    ucmd.args(&["-d", "bad.txt"])
+        .fails()
+        .stderr_is("base32: error: invalid input at byte 200000\n");
+}
+
+#[test]
+fn test_large_input_roundtrip() {
+    // Larger than the internal buffer, so wrapping and decoding cross chunk boundaries.
+    let ts = TestScenario::new(util_name!());
+    let input: Vec<u8> = (0..300_007u32).map(|i| (i * 7 % 251) as u8).collect();
+    ts.fixtures.write_bytes("input.bin", &input);
+    let encoded = ts
+        .ucmd()
+        .args(&["-w", "7", "input.bin"])
+        .succeeds()
+        .stdout_move_str();
+    let lines: Vec<&str> = encoded.lines().collect();
+    assert_eq!(lines.len(), (300_010 / 5 * 8 + 6) / 7);
+    assert!(lines[..lines.len() - 1].iter().all(|l| l.len() == 7));
+    assert!(encoded.ends_with("===\n"));
+
+    ts.fixtures.write("input.b32", &encoded);
+    ts.ucmd()
+        .args(&["-d", "input.b32"])
+        .succeeds()
+        .stdout_is_bytes(input);
 }
 
 #[test]
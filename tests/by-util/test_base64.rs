@@ -0,0 +1,75 @@
+// This file is part of the uutils coreutils package.
+//
+// (c) Jian Zeng <anonymousknight96@gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+//
+
+use crate::common::util::*;
+
+#[test]
+fn test_encode() {
+    let input = "Hello, World!";
+    new_ucmd!()
+        .pipe_in(input)
+        .succeeds()
+        .stdout_only("SGVsbG8sIFdvcmxkIQ==\n"); // spell-checker:disable-line
+
+    // Using '-' as our file
+    new_ucmd!()
+        .arg("-")
+        .pipe_in(input)
+        .succeeds()
+        .stdout_only("SGVsbG8sIFdvcmxkIQ==\n"); // spell-checker:disable-line
+}
+
+#[test]
+fn test_decode() {
+    for decode_param in ["-d", "--decode", "--dec"] {
+        new_ucmd!()
+            .arg(decode_param)
+            .pipe_in("SGVsbG8sIFdvcmxkIQ==\n") // spell-checker:disable-line
+            .succeeds()
+            .stdout_only("Hello, World!");
+    }
+}
+
+#[test]
+fn test_garbage() {
+    new_ucmd!()
+        .arg("-d")
+        .pipe_in("Pj4+P\x01z8/")
+        .fails()
+        .stderr_only("base64: error: invalid input at byte 5\n");
+}
+
+#[test]
+fn test_ignore_garbage() {
+    for ignore_garbage_param in ["-i", "--ignore-garbage", "--ig"] {
+        new_ucmd!()
+            .arg("-d")
+            .arg(ignore_garbage_param)
+            .pipe_in("Pj4+\x01Pz8/")
+            .succeeds()
+            .stdout_only(">>>???");
+    }
+}
+
+#[test]
+fn test_wrap() {
+    new_ucmd!()
+        .args(&["-w", "12"])
+        .pipe_in("The quick brown fox")
+        .succeeds()
+        .stdout_only("VGhlIHF1aWNr\nIGJyb3duIGZv\neA==\n"); // spell-checker:disable-line
+}
+
+#[test]
+fn test_base64_extra_operand() {
+    new_ucmd!()
+        .arg("a.txt")
+        .arg("b.txt")
+        .fails()
+        .usage_error("extra operand 'b.txt'");
+}
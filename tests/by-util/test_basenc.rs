@@ -0,0 +1,168 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+//
+// spell-checker:ignore (encodings) lsbf msbf
+
+use crate::common::util::*;
+
+const INPUT: &[u8] = b"Hello, World!\xff\xfe";
+
+// Reference outputs produced by GNU basenc with `-w 0`.
+const ENCODED: &[(&str, &str)] = &[
+    ("--base64", "SGVsbG8sIFdvcmxkIf/+"),
+    ("--base64url", "SGVsbG8sIFdvcmxkIf_-"),
+    ("--base32", "JBSWY3DPFQQFO33SNRSCD776"),
+    ("--base32hex", "91IMOR3F5GG5ERRIDHI23VVU"),
+    ("--base16", "48656C6C6F2C20576F726C6421FFFE"),
+    (
+        "--base2msbf",
+        "010010000110010101101100011011000110111100101100001000000101011101101111\
+         011100100110110001100100001000011111111111111110",
+    ),
+    (
+        "--base2lsbf",
+        "000100101010011000110110001101101111011000110100000001001110101011110110\
+         010011100011011000100110100001001111111101111111",
+    ),
+];
+
+#[test]
+fn test_encode_formats() {
+    for (format, encoded) in ENCODED {
+        new_ucmd!()
+            .args(&[format, "-w", "0"])
+            .pipe_in(INPUT)
+            .succeeds()
+            .stdout_only(*encoded);
+    }
+}
+
+#[test]
+fn test_decode_formats() {
+    for (format, encoded) in ENCODED {
+        new_ucmd!()
+            .args(&[format, "-d"])
+            .pipe_in(*encoded)
+            .succeeds()
+            .stdout_is_bytes(INPUT);
+    }
+}
+
+#[test]
+fn test_roundtrip_all_formats() {
+    // Every byte value, a multiple of 4 bytes long so Z85 accepts it too.
+    let input: Vec<u8> = (0..=255u8).cycle().take(1024).collect();
+    let formats = ENCODED.iter().map(|(format, _)| *format).chain(["--z85"]);
+    for format in formats {
+        let encoded = new_ucmd!()
+            .arg(format)
+            .pipe_in(input.clone())
+            .succeeds()
+            .stdout_move_str();
+        new_ucmd!()
+            .args(&[format, "-d"])
+            .pipe_in(encoded)
+            .succeeds()
+            .stdout_is_bytes(&input);
+    }
+}
+
+#[test]
+fn test_cross_format_roundtrip() {
+    // Re-encode the output of one format with another, then unwind the chain.
+    let chain = [
+        "--base64",
+        "--base32hex",
+        "--z85",
+        "--base2lsbf",
+        "--base16",
+    ];
+    let mut data = b"round and round!".to_vec();
+    for format in chain {
+        data = new_ucmd!()
+            .args(&[format, "-w", "0"])
+            .pipe_in(data)
+            .succeeds()
+            .stdout_move_bytes();
+    }
+    for format in chain.iter().rev() {
+        data = new_ucmd!()
+            .args(&[format, "-d"])
+            .pipe_in(data)
+            .succeeds()
+            .stdout_move_bytes();
+    }
+    assert_eq!(data, b"round and round!");
+}
+
+#[test]
+fn test_z85() {
+    new_ucmd!()
+        .arg("--z85")
+        .pipe_in("Hello, World!...")
+        .succeeds()
+        .stdout_only("nm=QNz.92jz/PV8aUCx1\n"); // spell-checker:disable-line
+    new_ucmd!()
+        .args(&["--z85", "-d"])
+        .pipe_in("nm=QNz.92jz/PV8aUCx1\n") // spell-checker:disable-line
+        .succeeds()
+        .stdout_only("Hello, World!...");
+}
+
+#[test]
+fn test_z85_not_aligned() {
+    new_ucmd!()
+        .arg("--z85")
+        .pipe_in("abc")
+        .fails()
+        .stderr_only("basenc: error: invalid input (length must be multiple of 4 characters)\n");
+    new_ucmd!()
+        .args(&["--z85", "-d"])
+        .pipe_in("nm=QNz")
+        .fails()
+        .stderr_only("basenc: error: invalid input at byte 5\n");
+}
+
+#[test]
+fn test_ignore_garbage() {
+    // Only alphabet characters survive `-i`; base16 is upper case only.
+    new_ucmd!()
+        .args(&["--base16", "-d", "-i"])
+        .pipe_in("48 65-6C\n6C:6F")
+        .succeeds()
+        .stdout_only("Hello");
+    new_ucmd!()
+        .args(&["--base64url", "-d", "-i"])
+        .pipe_in("Pj4-\x01Pz8_")
+        .succeeds()
+        .stdout_only(">>>???");
+    new_ucmd!()
+        .args(&["--base16", "-d"])
+        .pipe_in("48 65")
+        .fails()
+        .stderr_only("basenc: error: invalid input at byte 2\n");
+}
+
+#[test]
+fn test_last_encoding_wins() {
+    new_ucmd!()
+        .args(&["--base64", "--base16"])
+        .pipe_in("Hi")
+        .succeeds()
+        .stdout_only("4869\n");
+    new_ucmd!()
+        .args(&["--base16", "--base64"])
+        .pipe_in("Hi")
+        .succeeds()
+        .stdout_only("SGk=\n");
+}
+
+#[test]
+fn test_missing_encoding() {
+    new_ucmd!()
+        .pipe_in("Hi")
+        .fails()
+        .usage_error("missing encoding type");
+}
@@ -0,0 +1,142 @@
+// This file is part of the easybox package.
+//
+// (c) openeuler developers
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+//
+
+use std::process::{Child, Command};
+
+use crate::common::util::*;
+
+const C_CHRT_PATH: &str = "/usr/bin/chrt";
+
+// Test helper function
+fn helper_spawn_sleep() -> Child {
+    Command::new("sleep").arg("10").spawn().unwrap()
+}
+
+#[test]
+fn test_show_min_max() {
+    let task = TestScenario::new(util_name!());
+    // Run original C chrt from system path
+    let c_res = task.cmd(C_CHRT_PATH).arg("-m").succeeds();
+    // Run Rust chrt and compare the output with origin one
+    task.ucmd()
+        .arg("-m")
+        .succeeds()
+        .stdout_is_bytes(c_res.stdout());
+}
+
+#[test]
+fn test_retrieve_policy_from_pid() {
+    let test_args = &["-p", "1"];
+    let task = TestScenario::new(util_name!());
+    let c_res = task.cmd(C_CHRT_PATH).args(test_args).succeeds();
+    task.ucmd()
+        .args(test_args)
+        .succeeds()
+        .stdout_is_bytes(c_res.stdout());
+}
+
+#[test]
+fn test_launch_with_policy() {
+    new_ucmd!()
+        .args(&["-b", "0", C_CHRT_PATH, "-p", "0"])
+        .succeeds()
+        .stdout_contains("scheduling policy: SCHED_BATCH\n");
+    new_ucmd!()
+        .args(&["-o", "0", "no-such-command"])
+        .fails()
+        .code_is(127)
+        .stderr_contains("failed to execute no-such-command");
+}
+
+#[test]
+fn test_set_policy_for_pid_verbose() {
+    let mut child = helper_spawn_sleep();
+    let pid = child.id().to_string();
+    new_ucmd!()
+        .args(&["-v", "-i", "-p", "0", &pid])
+        .succeeds()
+        .stdout_is(format!(
+            "pid {pid}'s current scheduling policy: SCHED_OTHER\n\
+             pid {pid}'s current scheduling priority: 0\n\
+             pid {pid}'s new scheduling policy: SCHED_IDLE\n\
+             pid {pid}'s new scheduling priority: 0\n",
+            pid = pid
+        ));
+    new_ucmd!()
+        .args(&["-a", "-p", &pid])
+        .succeeds()
+        .stdout_contains("scheduling policy: SCHED_IDLE\n");
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn test_set_realtime_policy_for_pid() {
+    let mut child = helper_spawn_sleep();
+    let pid = child.id().to_string();
+    let task = TestScenario::new(util_name!());
+    let res = task.ucmd().args(&["-R", "-f", "-p", "7", &pid]).run();
+    if res.succeeded() {
+        task.ucmd()
+            .args(&["-p", &pid])
+            .succeeds()
+            .stdout_is(format!(
+                "pid {pid}'s current scheduling policy: SCHED_FIFO|SCHED_RESET_ON_FORK\n\
+                 pid {pid}'s current scheduling priority: 7\n",
+                pid = pid
+            ));
+    } else {
+        // Without CAP_SYS_NICE real-time policies are refused
+        res.code_is(1)
+            .stderr_contains(format!("failed to set pid {}'s policy", pid));
+    }
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn test_set_deadline_for_pid() {
+    let mut child = helper_spawn_sleep();
+    let pid = child.id().to_string();
+    let task = TestScenario::new(util_name!());
+    let res = task
+        .ucmd()
+        .args(&["-d", "-T", "1000000", "-P", "10000000", "-p", "0", &pid])
+        .run();
+    if res.succeeded() {
+        task.ucmd()
+            .args(&["-p", &pid])
+            .succeeds()
+            .stdout_contains(format!(
+                "pid {}'s current runtime/deadline/period parameters: 1000000/10000000/10000000\n",
+                pid
+            ));
+    } else {
+        res.code_is(1);
+    }
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn test_invalid_arguments() {
+    let task = TestScenario::new(util_name!());
+    for test_args in [
+        &["-o", "5", "true"][..],
+        &["-T", "1000", "-o", "0", "true"][..],
+        &["-p", "999999999"][..],
+    ] {
+        let c_res = task.cmd(C_CHRT_PATH).args(test_args).fails();
+        task.ucmd()
+            .args(test_args)
+            .fails()
+            .code_is(c_res.code())
+            .stdout_is_bytes(c_res.stdout())
+            .stderr_is_bytes(c_res.stderr());
+    }
+}
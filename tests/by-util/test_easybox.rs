@@ -0,0 +1,104 @@
+//! This file is part of the easybox package.
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::common::util::*;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+
+#[test]
+fn test_list() {
+    let ts = TestScenario::new(util_name!());
+    let result = ts.cmd(&ts.bin_path).arg("--list").succeeds();
+    let utils: Vec<&str> = result.stdout_str().lines().collect();
+    let mut sorted = utils.clone();
+    sorted.sort_unstable();
+    assert_eq!(utils, sorted);
+    assert!(utils.contains(&"mount"));
+    assert!(utils.contains(&"xargs"));
+
+    let result = ts.cmd(&ts.bin_path).arg("--list-full").succeeds();
+    let paths: Vec<&str> = result.stdout_str().lines().collect();
+    assert_eq!(paths.len(), utils.len());
+    assert!(paths.contains(&"/usr/bin/flock"));
+    assert!(paths.contains(&"/sbin/hwclock"));
+    assert!(paths.contains(&"/bin/mount"));
+
+    ts.cmd(&ts.bin_path)
+        .args(&["--list", "extra"])
+        .fails()
+        .code_is(1)
+        .stderr_only("easybox: extra operand 'extra'\n");
+}
+
+#[test]
+fn test_install_symlinks() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.mkdir("bin");
+    ts.cmd(&ts.bin_path)
+        .args(&["--install", "-s", "bin"])
+        .succeeds()
+        .no_stdout()
+        .no_stderr();
+
+    let utils = ts.cmd(&ts.bin_path).arg("--list").succeeds();
+    for util in utils.stdout_str().lines() {
+        let link = format!("bin/{}", util);
+        assert!(at.is_symlink(&link), "{} is not a symlink", link);
+        assert_eq!(
+            fs::canonicalize(at.plus(&link)).unwrap(),
+            fs::canonicalize(&ts.bin_path).unwrap()
+        );
+    }
+    ts.cmd(at.plus("bin/base64"))
+        .pipe_in("a")
+        .succeeds()
+        .stdout_only("YQ==\n");
+
+    // applets that are already there are skipped
+    ts.cmd(&ts.bin_path)
+        .args(&["--install", "-s", "bin"])
+        .succeeds()
+        .no_stderr();
+}
+
+#[test]
+fn test_install_hardlinks() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.mkdir("bin");
+    let binary = fs::metadata(&ts.bin_path).unwrap();
+    if binary.dev() != at.metadata("bin").dev() {
+        // hard links cannot cross file systems
+        return;
+    }
+    ts.cmd(&ts.bin_path)
+        .args(&["--install", "bin"])
+        .succeeds()
+        .no_stdout()
+        .no_stderr();
+
+    assert!(!at.is_symlink("bin/xargs"));
+    assert_eq!(at.metadata("bin/xargs").ino(), binary.ino());
+    ts.cmd(at.plus("bin/base64"))
+        .pipe_in("a")
+        .succeeds()
+        .stdout_only("YQ==\n");
+}
+
+#[test]
+fn test_install_errors() {
+    let ts = TestScenario::new(util_name!());
+    ts.cmd(&ts.bin_path)
+        .args(&["--install", "-s", "missing"])
+        .fails()
+        .code_is(1)
+        .stderr_only("easybox: missing: No such file or directory\n");
+    ts.cmd(&ts.bin_path)
+        .args(&["--install", "-s", ".", "extra"])
+        .fails()
+        .code_is(1)
+        .stderr_only("easybox: extra operand 'extra'\n");
+}
@@ -99,3 +99,341 @@ fn test_lock() {
         }
     }
 }
+
+#[test]
+fn test_holders() {
+    let ts = TestScenario::new(util_name!());
+    let mut child = ts
+        .ucmd()
+        .args(&["--exclusive", "holders.lock", "sleep", "3"])
+        .run_no_wait();
+
+    std::thread::sleep(Duration::from_millis(500));
+
+    ts.ucmd()
+        .args(&["--holders", "holders.lock"])
+        .succeeds()
+        .stdout_contains("PID")
+        .stdout_contains(format!("{}", child.id()))
+        .stdout_contains("FLOCK")
+        .stdout_contains("WRITE");
+
+    ts.ucmd()
+        .args(&["--nonblock", "--verbose", "holders.lock", "true"])
+        .fails()
+        .code_is(1)
+        .stderr_contains(format!("lock held by pid {} (FLOCK WRITE)", child.id()));
+
+    child.wait().unwrap();
+    ts.ucmd()
+        .args(&["--holders", "holders.lock"])
+        .succeeds()
+        .stdout_does_not_contain("FLOCK");
+}
+
+#[test]
+fn test_holders_ofd_lock() {
+    use std::os::unix::io::AsRawFd;
+
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.touch("ofd.lock");
+    // /proc/locks reports OFD locks with pid -1, so --holders has to find
+    // this process through its /proc/<pid>/fdinfo entries.
+    let file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(ts.fixtures.plus("ofd.lock"))
+        .unwrap();
+    let mut fl: libc::flock = unsafe { std::mem::zeroed() };
+    fl.l_type = libc::F_WRLCK as libc::c_short;
+    fl.l_whence = libc::SEEK_SET as libc::c_short;
+    assert_eq!(
+        unsafe { libc::fcntl(file.as_raw_fd(), libc::F_OFD_SETLK, &fl) },
+        0
+    );
+
+    let result = ts.ucmd().args(&["--holders", "ofd.lock"]).succeeds();
+    let line = result
+        .stdout_str()
+        .lines()
+        .find(|line| line.contains("OFDLCK"))
+        .expect("no OFD lock listed");
+    assert_eq!(
+        line.split_whitespace().next(),
+        Some(std::process::id().to_string().as_str()),
+        "{}",
+        line
+    );
+    assert!(!line.ends_with(" -"), "{}", line);
+}
+
+fn fcntl_lock(file: &std::fs::File, cmd: i32, start: i64, len: i64) -> i32 {
+    use std::os::unix::io::AsRawFd;
+    let fl = libc::flock {
+        l_type: libc::F_WRLCK as libc::c_short,
+        l_whence: libc::SEEK_SET as libc::c_short,
+        l_start: start,
+        l_len: len,
+        l_pid: 0,
+    };
+    unsafe { libc::fcntl(file.as_raw_fd(), cmd, &fl) }
+}
+
+#[test]
+fn test_fcntl_range() {
+    let ts = TestScenario::new(util_name!());
+    let mut child = ts
+        .ucmd()
+        .args(&[
+            "--fcntl",
+            "--start",
+            "0",
+            "--length",
+            "10",
+            "range.lock",
+            "sleep",
+            "3",
+        ])
+        .run_no_wait();
+
+    std::thread::sleep(Duration::from_millis(500));
+
+    // overlapping range conflicts
+    ts.ucmd()
+        .args(&[
+            "--fcntl",
+            "--nonblock",
+            "--conflict-exit-code",
+            "7",
+            "--start",
+            "5",
+            "--length",
+            "10",
+            "range.lock",
+            "true",
+        ])
+        .fails()
+        .code_is(7);
+
+    // disjoint range does not
+    ts.ucmd()
+        .args(&[
+            "--fcntl",
+            "--nonblock",
+            "--start",
+            "10",
+            "--length",
+            "10",
+            "range.lock",
+            "true",
+        ])
+        .succeeds();
+
+    // timeout applies to fcntl locks as well
+    ts.ucmd()
+        .args(&[
+            "--fcntl",
+            "--timeout",
+            "1",
+            "--conflict-exit-code",
+            "9",
+            "range.lock",
+            "true",
+        ])
+        .fails()
+        .code_is(9);
+
+    // flock(2) and OFD locks do not interact on local filesystems
+    ts.ucmd()
+        .args(&["--nonblock", "range.lock", "true"])
+        .succeeds();
+
+    // a POSIX locker in another process sees the OFD lock
+    let file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(ts.fixtures.plus("range.lock"))
+        .unwrap();
+    assert_eq!(fcntl_lock(&file, libc::F_SETLK, 0, 1), -1);
+    assert_eq!(fcntl_lock(&file, libc::F_SETLK, 10, 1), 0);
+
+    ts.ucmd()
+        .args(&["--holders", "range.lock"])
+        .succeeds()
+        .stdout_contains("OFDLCK")
+        .stdout_contains(format!("{}", child.id()));
+
+    child.wait().unwrap();
+}
+
+#[test]
+fn test_fcntl_foreign_holder() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.touch("foreign.lock");
+    let file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(ts.fixtures.plus("foreign.lock"))
+        .unwrap();
+    assert_eq!(fcntl_lock(&file, libc::F_OFD_SETLK, 100, 0), 0);
+
+    ts.ucmd()
+        .args(&[
+            "--fcntl",
+            "--nonblock",
+            "--start",
+            "200",
+            "foreign.lock",
+            "true",
+        ])
+        .fails()
+        .code_is(1);
+    ts.ucmd()
+        .args(&[
+            "--fcntl",
+            "--nonblock",
+            "--length",
+            "100",
+            "foreign.lock",
+            "true",
+        ])
+        .succeeds();
+
+    drop(file);
+    ts.ucmd()
+        .args(&["--fcntl", "--nonblock", "foreign.lock", "true"])
+        .succeeds();
+}
+
+#[test]
+fn test_multiple_locks() {
+    let ts = TestScenario::new(util_name!());
+    let mut holder = ts
+        .ucmd()
+        .args(&["repo.lock", "--lock", "host.lock", "sleep", "2"])
+        .run_no_wait();
+
+    std::thread::sleep(Duration::from_millis(500));
+
+    for file in ["repo.lock", "host.lock"] {
+        ts.ucmd()
+            .args(&["--holders", file])
+            .succeeds()
+            .stdout_contains(format!("{}", holder.id()));
+    }
+
+    // reversed order waits for both instead of deadlocking
+    ts.ucmd()
+        .args(&[
+            "--timeout",
+            "10",
+            "host.lock",
+            "--lock",
+            "repo.lock",
+            "true",
+        ])
+        .succeeds();
+    holder.wait().unwrap();
+
+    // the same file given twice is locked once
+    ts.ucmd()
+        .args(&[
+            "--nonblock",
+            "repo.lock",
+            "--lock",
+            "repo.lock",
+            "--lock",
+            "host.lock",
+            "true",
+        ])
+        .succeeds();
+}
+
+#[test]
+fn test_multiple_locks_release_on_failure() {
+    let ts = TestScenario::new(util_name!());
+    let mut holder = ts.ucmd().args(&["b.lock", "sleep", "3"]).run_no_wait();
+
+    std::thread::sleep(Duration::from_millis(500));
+
+    let start = SystemTime::now();
+    ts.ucmd()
+        .args(&[
+            "--timeout",
+            "1",
+            "--conflict-exit-code",
+            "42",
+            "a.lock",
+            "--lock",
+            "b.lock",
+            "--lock",
+            "c.lock",
+            "true",
+        ])
+        .fails()
+        .code_is(42);
+    // the timeout covers the whole acquisition
+    assert!(SystemTime::now().duration_since(start).unwrap().as_secs() < 3);
+
+    ts.ucmd().args(&["--nonblock", "a.lock", "true"]).succeeds();
+    ts.ucmd().args(&["--nonblock", "c.lock", "true"]).succeeds();
+    holder.wait().unwrap();
+}
+
+#[test]
+fn test_cleanup() {
+    let ts = TestScenario::new(util_name!());
+    ts.ucmd()
+        .args(&["--cleanup", "cleanup.lock", "true"])
+        .succeeds();
+    assert!(!ts.fixtures.file_exists("cleanup.lock"));
+
+    // a waiter that got the lock on the removed file retries on the new one
+    let mut holder = ts
+        .ucmd()
+        .args(&["--cleanup", "race.lock", "sleep", "1"])
+        .run_no_wait();
+    std::thread::sleep(Duration::from_millis(300));
+    let mut waiter = ts.ucmd().args(&["race.lock", "sleep", "2"]).run_no_wait();
+    std::thread::sleep(Duration::from_millis(1500));
+
+    assert!(ts.fixtures.file_exists("race.lock"));
+    ts.ucmd()
+        .args(&["--nonblock", "race.lock", "true"])
+        .fails()
+        .code_is(1);
+
+    holder.wait().unwrap();
+    waiter.wait().unwrap();
+
+    ts.ucmd()
+        .args(&["--no-fork", "--cleanup", "race.lock", "true"])
+        .fails()
+        .code_is(64);
+}
+
+#[test]
+fn test_wait_for_release() {
+    let ts = TestScenario::new(util_name!());
+    let start = SystemTime::now();
+    let mut holder = ts
+        .ucmd()
+        .args(&["--shared", "release.lock", "sleep", "2"])
+        .run_no_wait();
+    std::thread::sleep(Duration::from_millis(500));
+
+    ts.ucmd()
+        .args(&["--timeout", "1", "--wait-for-release", "release.lock"])
+        .fails()
+        .code_is(1);
+    ts.ucmd()
+        .args(&["--wait-for-release", "release.lock"])
+        .succeeds()
+        .no_stdout();
+    assert!(SystemTime::now().duration_since(start).unwrap().as_millis() >= 2000);
+    holder.wait().unwrap();
+
+    ts.ucmd()
+        .args(&["--wait-for-release", "release.lock", "true"])
+        .fails();
+}
@@ -133,3 +133,170 @@ fn test_free_w() {
     let res = task.ucmd().args(test_args).succeeds();
     both(vec![&*TITLE_W, &*RE_MEM_W, &*RE_SWAP], c_res, res);
 }
+
+#[test]
+fn test_free_cgroup_v2() {
+    let res = new_ucmd!().arg("--cgroup=cgroup_v2").succeeds();
+    res.stdout_contains(
+        "Mem:         1048576      360448      524288       10240      215040      688128",
+    );
+    assert!(RE_SWAP.is_match(res.stdout_str()));
+
+    new_ucmd!()
+        .args(&["--cgroup=cgroup_v2", "-w", "-t", "-l"])
+        .succeeds()
+        .stdout_contains(
+            "Mem:         1048576      360448      524288       10240           0      215040      688128",
+        )
+        .stdout_contains("Low:         1048576      524288      524288");
+}
+
+#[test]
+fn test_free_cgroup_v1() {
+    new_ucmd!()
+        .args(&["--cgroup=cgroup_v1", "--mebi"])
+        .succeeds()
+        .stdout_contains(
+            "Mem:            2048         824        1024           0         300        1224",
+        );
+}
+
+#[test]
+fn test_free_cgroup_self() {
+    let res = new_ucmd!().arg("--cgroup").succeeds();
+    assert!(TITLE.is_match(res.stdout_str()));
+    assert!(RE_MEM.is_match(res.stdout_str()));
+
+    new_ucmd!()
+        .arg("--cgroup=no/such/cgroup")
+        .fails()
+        .stderr_contains("cannot find memory cgroup");
+}
+
+#[test]
+fn test_free_detail() {
+    let res = new_ucmd!().arg("--detail").succeeds();
+    assert!(TITLE.is_match(res.stdout_str()));
+    assert!(RE_MEM.is_match(res.stdout_str()));
+    let re_detail =
+        Regex::new(r"(?m)^(Slab|SReclaimable|Committed_AS|CommitLimit):\s+\d+$").unwrap();
+    assert_eq!(re_detail.find_iter(res.stdout_str()).count(), 4);
+    let re_hugepages = Regex::new(r"(?m)^HugePages_(Total|Free|Rsvd):\s+\d+$").unwrap();
+    assert_eq!(re_hugepages.find_iter(res.stdout_str()).count(), 3);
+}
+
+#[test]
+fn test_free_node() {
+    if !std::path::Path::new("/sys/devices/system/node/node0/meminfo").exists() {
+        return;
+    }
+    let res = new_ucmd!().args(&["--node", "--mebi"]).succeeds();
+    let re_node = Regex::new(r"(?m)^Node0:\s+\d+\s+\d+\s+\d+\s+\d+\s+\d+\s+\d+$").unwrap();
+    assert!(re_node.is_match(res.stdout_str()));
+
+    // As on the Mem: line, used is what is not available.
+    let res = new_ucmd!().args(&["--node", "--kibi"]).succeeds();
+    let node: Vec<u64> = res
+        .stdout_str()
+        .lines()
+        .find(|line| line.starts_with("Node0:"))
+        .unwrap()
+        .split_whitespace()
+        .skip(1)
+        .map(|n| n.parse().unwrap())
+        .collect();
+    assert_eq!(node[1] + node[5], node[0]);
+
+    new_ucmd!()
+        .args(&["--node", "--detail"])
+        .succeeds()
+        .stdout_contains("Node0:\nSlab:");
+}
+
+#[test]
+fn test_free_json() {
+    let re_json = Regex::new(
+        r#"(?m)^\{"timestamp":"\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d{3}Z","unit":"MiB","mem":\{"total":\d+,"used":\d+,"free":\d+,"shared":\d+,"buffers":\d+,"cache":\d+,"available":\d+\},"swap":\{"total":\d+,"used":\d+,"free":\d+\}\}$"#,
+    )
+    .unwrap();
+    let res = new_ucmd!()
+        .args(&["--json", "--mebi", "-c", "2", "-s", "0.1"])
+        .succeeds();
+    assert_eq!(re_json.find_iter(res.stdout_str()).count(), 2);
+
+    new_ucmd!().args(&["--json", "-h"]).fails();
+}
+
+#[test]
+fn test_free_csv() {
+    let res = new_ucmd!()
+        .args(&["--csv", "--timestamp", "epoch", "-c", "3", "-s", "0,1"])
+        .succeeds();
+    let lines: Vec<&str> = res.stdout_str().lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(
+        lines[0],
+        "timestamp,mem_total,mem_used,mem_free,mem_shared,mem_buffers,mem_cache,mem_available,swap_total,swap_used,swap_free"
+    );
+    let re_row = Regex::new(r"^\d+\.\d{3}(,\d+){10}$").unwrap();
+    for line in &lines[1..] {
+        assert!(re_row.is_match(line), "{}", line);
+    }
+}
+
+#[test]
+fn test_free_fractional_seconds() {
+    let start = std::time::Instant::now();
+    let res = new_ucmd!().args(&["-c", "4", "-s", "0.25"]).succeeds();
+    let elapsed = start.elapsed();
+    assert_eq!(RE_MEM.find_iter(res.stdout_str()).count(), 4);
+    assert!(elapsed >= std::time::Duration::from_millis(750));
+    assert!(elapsed < std::time::Duration::from_millis(2000));
+
+    new_ucmd!()
+        .args(&["-s", "abc"])
+        .fails()
+        .stderr_contains("seconds argument 'abc' failed");
+    new_ucmd!().args(&["-c", "0"]).fails();
+}
+
+#[test]
+fn test_free_used_is_total_minus_available() {
+    let res = new_ucmd!().succeeds();
+    let mem_line = res
+        .stdout_str()
+        .lines()
+        .find(|l| l.starts_with("Mem:"))
+        .unwrap();
+    let values: Vec<u64> = mem_line
+        .split_whitespace()
+        .skip(1)
+        .map(|v| v.parse().unwrap())
+        .collect();
+    // total, used, free, shared, buff/cache, available
+    assert_eq!(values[0] - values[5], values[1]);
+}
+
+#[test]
+fn test_free_committed() {
+    let re_comm = Regex::new(r"(?m)^Comm:\s+\d+\s+\d+\s+\d+$").unwrap();
+    let test_args = &["-v", "-t"];
+    let task = TestScenario::new(util_name!());
+    let c_res = task.cmd(C_FREE_PATH).args(test_args).succeeds();
+    let res = task.ucmd().args(test_args).succeeds();
+    both(vec![&*TITLE, &*RE_TOTAL, &re_comm], c_res, res);
+}
+
+#[test]
+fn test_free_line() {
+    let re_line =
+        Regex::new(r"^SwapUse\s+\d+ CachUse\s+\d+ MemUse\s+\d+ MemFree\s+\d+\n$").unwrap();
+    let res = new_ucmd!().arg("-L").succeeds();
+    assert!(re_line.is_match(res.stdout_str()));
+
+    new_ucmd!()
+        .args(&["--line", "-h"])
+        .succeeds()
+        .stdout_contains("SwapUse");
+    new_ucmd!().args(&["-L", "-w"]).fails();
+}
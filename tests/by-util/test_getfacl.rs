@@ -0,0 +1,140 @@
+// This file is part of the easybox package.
+//
+// (c) openeuler developers
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+//
+
+use std::os::unix::fs::symlink;
+
+use crate::common::util::*;
+
+fn setfacl(ts: &TestScenario, args: &[&str]) {
+    ts.ccmd("setfacl").args(args).succeeds();
+}
+
+#[test]
+fn test_minimal_acl_from_mode() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.touch("f");
+    at.set_mode("f", 0o640);
+
+    ts.ucmd()
+        .args(&["-c", "f"])
+        .succeeds()
+        .stdout_only("user::rw-\ngroup::r--\nother::---\n\n");
+    ts.ucmd().args(&["-s", "f"]).succeeds().stdout_only("");
+
+    at.set_mode("f", 0o3755);
+    let out = ts.ucmd().arg("f").succeeds().stdout_move_str();
+    assert!(out.starts_with("# file: f\n# owner: "), "{}", out);
+    assert!(
+        out.ends_with("# flags: -st\nuser::rwx\ngroup::r-x\nother::r-x\n\n"),
+        "{}",
+        out
+    );
+}
+
+#[test]
+fn test_effective_rights() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.touch("f");
+    at.set_mode("f", 0o644);
+    setfacl(&ts, &["-m", "u:12345:rwx,g:23456:rw,m::r", "f"]);
+
+    ts.ucmd().args(&["-c", "-n", "f"]).succeeds().stdout_only(
+        "user::rw-\n\
+         user:12345:rwx\t\t\t#effective:r--\n\
+         group::r--\n\
+         group:23456:rw-\t\t\t#effective:r--\n\
+         mask::r--\n\
+         other::r--\n\n",
+    );
+    ts.ucmd()
+        .args(&["-c", "-n", "-e", "f"])
+        .succeeds()
+        .stdout_only(
+            "user::rw-\n\
+         user:12345:rwx\t\t\t#effective:r--\n\
+         group::r--\t\t\t#effective:r--\n\
+         group:23456:rw-\t\t\t#effective:r--\n\
+         mask::r--\n\
+         other::r--\n\n",
+        );
+    ts.ucmd()
+        .args(&["-c", "-n", "-E", "f"])
+        .succeeds()
+        .stdout_only(
+            "user::rw-\nuser:12345:rwx\ngroup::r--\ngroup:23456:rw-\nmask::r--\nother::r--\n\n",
+        );
+}
+
+#[test]
+fn test_access_and_default() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.mkdir("d");
+    at.set_mode("d", 0o750);
+    setfacl(&ts, &["-d", "-m", "u:12345:rx", "d"]);
+
+    ts.ucmd()
+        .args(&["-c", "-n", "-a", "d"])
+        .succeeds()
+        .stdout_only("user::rwx\ngroup::r-x\nother::---\n\n");
+    ts.ucmd()
+        .args(&["-c", "-n", "-d", "d"])
+        .succeeds()
+        .stdout_only(
+            "default:user::rwx\n\
+         default:user:12345:r-x\n\
+         default:group::r-x\n\
+         default:mask::r-x\n\
+         default:other::---\n\n",
+        );
+}
+
+#[test]
+fn test_recursive_and_absolute_names() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.mkdir_all("d/sub");
+    at.touch("d/sub/f");
+    symlink("sub", at.plus("d/link")).unwrap();
+    setfacl(&ts, &["-m", "g:23456:r", "d/sub/f"]);
+
+    let out = ts
+        .ucmd()
+        .args(&["-R", "-s", "d"])
+        .succeeds()
+        .stdout_move_str();
+    assert!(out.starts_with("# file: d/sub/f\n"), "{}", out);
+    assert_eq!(out.matches("# file:").count(), 1, "{}", out);
+
+    let out = ts
+        .ucmd()
+        .args(&["-R", "-L", "-s", "d"])
+        .succeeds()
+        .stdout_move_str();
+    assert!(out.starts_with("# file: d/link/f\n"), "{}", out);
+    assert_eq!(out.matches("# file:").count(), 2, "{}", out);
+
+    let path = at.plus_as_string("d/sub/f");
+    let res = ts.ucmd().arg(&path).succeeds();
+    res.stderr_is("getfacl: Removing leading '/' from absolute path names\n");
+    assert!(res
+        .stdout_str()
+        .starts_with(&format!("# file: {}\n", path.trim_start_matches('/'))));
+    ts.ucmd()
+        .args(&["-p", &path])
+        .succeeds()
+        .no_stderr()
+        .stdout_contains(format!("# file: {}\n", path));
+
+    ts.ucmd()
+        .arg("missing")
+        .fails()
+        .stderr_only("getfacl: missing: No such file or directory\n");
+}
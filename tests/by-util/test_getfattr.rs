@@ -0,0 +1,115 @@
+// This file is part of the easybox package.
+//
+// (c) openeuler developers
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+//
+
+use std::os::unix::fs::symlink;
+
+use crate::common::util::*;
+
+fn set(ts: &TestScenario, name: &str, value: &str, path: &str) {
+    ts.ccmd("setfattr")
+        .args(&["-n", name, "-v", value, path])
+        .succeeds();
+}
+
+#[test]
+fn test_dump_encodings() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.touch("f");
+    set(&ts, "user.text", "hello", "f");
+    set(&ts, "user.bin", "0x0001020304ff", "f");
+    set(&ts, "user.quoted", "\"say \\\"hi\\\"\"", "f");
+
+    ts.ucmd().args(&["-d", "f"]).succeeds().stdout_only(
+        "# file: f\nuser.bin=0sAAECAwT/\nuser.quoted=\"say \\\"hi\\\"\"\nuser.text=\"hello\"\n\n",
+    );
+    ts.ucmd()
+        .args(&["-d", "-e", "hex", "f"])
+        .succeeds()
+        .stdout_only(
+            "# file: f\nuser.bin=0x0001020304ff\nuser.quoted=0x7361792022686922\nuser.text=0x68656c6c6f\n\n",
+        );
+    ts.ucmd()
+        .args(&["-n", "user.bin", "-e", "text", "f"])
+        .succeeds()
+        .stdout_only_bytes(b"# file: f\nuser.bin=\"\\000\x01\x02\x03\x04\xff\"\n\n");
+}
+
+#[test]
+fn test_names_and_values() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.touch("f");
+    set(&ts, "user.b", "2", "f");
+    set(&ts, "user.a", "1", "f");
+    ts.ccmd("setfattr")
+        .args(&["-n", "user.empty", "f"])
+        .succeeds();
+
+    ts.ucmd()
+        .arg("f")
+        .succeeds()
+        .stdout_only("# file: f\nuser.a\nuser.b\nuser.empty\n\n");
+    ts.ucmd()
+        .args(&["-d", "-m", "^user\\.[ab]$", "f"])
+        .succeeds()
+        .stdout_only("# file: f\nuser.a=\"1\"\nuser.b=\"2\"\n\n");
+    ts.ucmd()
+        .args(&["-n", "user.b", "--only-values", "f"])
+        .succeeds()
+        .stdout_only("2");
+    ts.ucmd()
+        .args(&["-n", "user.missing", "f"])
+        .fails()
+        .stderr_only("getfattr: f: user.missing: No such attribute\n");
+}
+
+#[test]
+fn test_recursive_symlink_policy() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.mkdir_all("d/sub");
+    at.touch("d/sub/f");
+    symlink("sub", at.plus("d/link")).unwrap();
+    set(&ts, "user.x", "1", "d/sub/f");
+
+    // -P, the default, does not descend into d/link.
+    ts.ucmd()
+        .args(&["-R", "-d", "d"])
+        .succeeds()
+        .stdout_only("# file: d/sub/f\nuser.x=\"1\"\n\n");
+    ts.ucmd()
+        .args(&["-R", "-L", "-d", "d"])
+        .succeeds()
+        .stdout_only("# file: d/link/f\nuser.x=\"1\"\n\n# file: d/sub/f\nuser.x=\"1\"\n\n");
+}
+
+#[test]
+fn test_absolute_names() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.touch("f");
+    set(&ts, "user.x", "1", "f");
+    let path = ts.fixtures.plus_as_string("f");
+
+    ts.ucmd()
+        .args(&["-d", &path])
+        .succeeds()
+        .stderr_is("getfattr: Removing leading '/' from absolute path names\n")
+        .stdout_is(format!("# file: {}\nuser.x=\"1\"\n\n", &path[1..]));
+    ts.ucmd()
+        .args(&["-d", "--absolute-names", &path])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(format!("# file: {}\nuser.x=\"1\"\n\n", path));
+}
+
+#[test]
+fn test_invalid_encoding() {
+    new_ucmd!()
+        .args(&["-e", "rot13", "f"])
+        .fails()
+        .usage_error("unrecognized encoding 'rot13'");
+}
@@ -0,0 +1,185 @@
+// This file is part of the easybox package.
+//
+// (c) openeuler developers
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+//
+
+use std::os::unix::fs::PermissionsExt;
+
+use crate::common::util::*;
+
+fn acl_of(ts: &TestScenario, args: &[&str]) -> String {
+    ts.ccmd("getfacl")
+        .args(&["-c", "-n", "-E"])
+        .args(args)
+        .succeeds()
+        .stdout_move_str()
+}
+
+#[test]
+fn test_modify_recalculates_mask() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.touch("f");
+    at.set_mode("f", 0o640);
+
+    ts.ucmd()
+        .args(&["-m", "u:12345:rw,g:23456:r", "f"])
+        .succeeds()
+        .no_stdout()
+        .no_stderr();
+    assert_eq!(
+        acl_of(&ts, &["f"]),
+        "user::rw-\nuser:12345:rw-\ngroup::r--\ngroup:23456:r--\nmask::rw-\nother::---\n\n"
+    );
+    // The group permission bits show the mask.
+    assert_eq!(at.metadata("f").permissions().mode() & 0o777, 0o660);
+
+    // An explicit mask is kept, and -n keeps the current one.
+    ts.ucmd().args(&["-m", "u:12345:rwx,m::r", "f"]).succeeds();
+    assert!(
+        acl_of(&ts, &["f"]).contains("user:12345:rwx\ngroup::r--\ngroup:23456:r--\nmask::r--\n")
+    );
+    ts.ucmd().args(&["-n", "-m", "g:23456:rw", "f"]).succeeds();
+    assert!(acl_of(&ts, &["f"]).contains("mask::r--\n"));
+    ts.ucmd().args(&["-m", "g:23456:rw", "f"]).succeeds();
+    assert!(acl_of(&ts, &["f"]).contains("mask::rwx\n"));
+}
+
+#[test]
+fn test_remove_entries() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.touch("f");
+    at.set_mode("f", 0o600);
+    at.mkdir("d");
+    at.set_mode("d", 0o700);
+    ts.ucmd()
+        .args(&["-m", "u:12345:r,g:23456:r", "f"])
+        .succeeds();
+
+    ts.ucmd().args(&["-x", "u:12345", "f"]).succeeds();
+    assert_eq!(
+        acl_of(&ts, &["f"]),
+        "user::rw-\ngroup::---\ngroup:23456:r--\nmask::r--\nother::---\n\n"
+    );
+    ts.ucmd().args(&["-b", "f"]).succeeds();
+    assert_eq!(acl_of(&ts, &["f"]), "user::rw-\ngroup::---\nother::---\n\n");
+    assert_eq!(at.metadata("f").permissions().mode() & 0o777, 0o600);
+
+    ts.ucmd()
+        .args(&["-m", "d:u:12345:rwx,g:23456:x", "d"])
+        .succeeds();
+    assert_eq!(
+        acl_of(&ts, &["-d", "d"]),
+        "default:user::rwx\ndefault:user:12345:rwx\ndefault:group::---\n\
+         default:mask::rwx\ndefault:other::---\n\n"
+    );
+    ts.ucmd().args(&["-k", "d"]).succeeds();
+    assert_eq!(acl_of(&ts, &["-d", "d"]), "\n");
+    assert!(acl_of(&ts, &["-a", "d"]).contains("group:23456:--x\n"));
+}
+
+#[test]
+fn test_remove_all_applies_mask() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.touch("g");
+    at.set_mode("g", 0o644);
+    ts.ucmd()
+        .args(&["-m", "u:12345:r,g::rwx,m::r", "g"])
+        .succeeds();
+    assert_eq!(at.metadata("g").permissions().mode() & 0o777, 0o644);
+
+    // The owning group keeps only what the mask granted it.
+    ts.ucmd().args(&["-b", "g"]).succeeds();
+    assert_eq!(acl_of(&ts, &["g"]), "user::rw-\ngroup::r--\nother::r--\n\n");
+    assert_eq!(at.metadata("g").permissions().mode() & 0o777, 0o644);
+}
+
+#[test]
+fn test_recursive_conditional_execute() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.mkdir_all("d/sub");
+    at.touch("d/plain");
+    at.set_mode("d/plain", 0o600);
+    at.touch("d/script");
+    at.set_mode("d/script", 0o700);
+
+    // Default entries are skipped for files below the top.
+    ts.ucmd()
+        .args(&["-R", "-m", "g:23456:rX,d:g:23456:r", "d"])
+        .succeeds()
+        .no_stderr();
+    assert!(acl_of(&ts, &["-a", "d/sub"]).contains("group:23456:r-x\n"));
+    assert!(acl_of(&ts, &["-d", "d/sub"]).contains("default:group:23456:r--\n"));
+    assert!(acl_of(&ts, &["d/plain"]).contains("group:23456:r--\n"));
+    assert!(acl_of(&ts, &["d/script"]).contains("group:23456:r-x\n"));
+
+    ts.ucmd()
+        .args(&["-d", "-m", "u:12345:r", "d/plain"])
+        .fails()
+        .stderr_only("setfacl: d/plain: Only directories can have default ACLs\n");
+}
+
+#[test]
+fn test_restore_getfacl_output() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.mkdir("d");
+    at.touch("d/f");
+    ts.ucmd()
+        .args(&["-R", "-m", "u:12345:rw,d:g:23456:r", "d"])
+        .succeeds();
+    let dump = ts
+        .ccmd("getfacl")
+        .args(&["-R", "d"])
+        .succeeds()
+        .stdout_move_str();
+
+    ts.ucmd().args(&["-R", "-b", "d"]).succeeds();
+    at.set_mode("d/f", 0o4755);
+    ts.ccmd("getfacl")
+        .args(&["-R", "-s", "d"])
+        .succeeds()
+        .stdout_only("");
+
+    ts.ucmd()
+        .arg("--restore=-")
+        .pipe_in(dump.clone())
+        .succeeds()
+        .no_stderr();
+    ts.ccmd("getfacl")
+        .args(&["-R", "d"])
+        .succeeds()
+        .stdout_only(dump);
+}
+
+#[test]
+fn test_usage_errors() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.touch("f");
+    ts.fixtures.set_mode("f", 0o644);
+    ts.ucmd()
+        .args(&["-m", "u:12345:rw,bogus:r", "f"])
+        .fails()
+        .usage_error("Option -m: Invalid argument near character 12");
+    ts.ucmd()
+        .args(&["-x", "u:12345:rw", "f"])
+        .fails()
+        .usage_error("Option -x: Invalid argument near character 1");
+    ts.ucmd()
+        .arg("f")
+        .fails()
+        .usage_error("One of -m, -x, -b, -k or --restore is required");
+    ts.ucmd()
+        .args(&["--restore=-", "-b"])
+        .fails()
+        .usage_error("--restore cannot be combined with -m, -x, -b, -k or -d");
+    ts.ucmd().args(&["-x", "g::", "f"]).fails().stderr_only(
+        "setfacl: f: Malformed access ACL 'user::rw-,other::r--': Missing or wrong entry\n",
+    );
+}
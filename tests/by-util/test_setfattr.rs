@@ -0,0 +1,149 @@
+// This file is part of the easybox package.
+//
+// (c) openeuler developers
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+//
+
+use std::os::unix::fs::symlink;
+
+use crate::common::util::*;
+
+fn value_of(ts: &TestScenario, name: &str, path: &str) -> Vec<u8> {
+    ts.ccmd("getfattr")
+        .args(&["-n", name, "--only-values", path])
+        .succeeds()
+        .stdout_move_bytes()
+}
+
+#[test]
+fn test_value_encodings() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.touch("f");
+    for (value, expected) in [
+        ("plain", &b"plain"[..]),
+        ("\"quoted \\\" \\\\ \\012\"", b"quoted \" \\ \n"),
+        ("0x00ff41", b"\x00\xffA"),
+        ("0sAP9B", b"\x00\xffA"),
+    ] {
+        ts.ucmd()
+            .args(&["-n", "user.v", "-v", value, "f"])
+            .succeeds();
+        assert_eq!(value_of(&ts, "user.v", "f"), expected, "value {}", value);
+    }
+
+    ts.ucmd()
+        .args(&["-n", "user.v", "-v", "0xabc", "f"])
+        .fails()
+        .stderr_only("setfattr: '0xabc': bad input encoding\n");
+}
+
+#[test]
+fn test_set_and_remove() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.touch("a");
+    ts.fixtures.touch("b");
+    ts.ucmd()
+        .args(&["-n", "user.x", "-v", "1", "a", "b"])
+        .succeeds()
+        .no_stdout()
+        .no_stderr();
+    assert_eq!(value_of(&ts, "user.x", "b"), b"1");
+
+    ts.ucmd()
+        .args(&["-x", "user.x", "a", "missing"])
+        .fails()
+        .stderr_only("setfattr: missing: No such file or directory\n");
+    ts.ccmd("getfattr")
+        .args(&["-n", "user.x", "a"])
+        .fails()
+        .stderr_only("getfattr: a: user.x: No such attribute\n");
+}
+
+#[test]
+fn test_no_dereference() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.touch("f");
+    symlink("f", ts.fixtures.plus("link")).unwrap();
+    ts.ucmd()
+        .args(&["-n", "user.x", "-v", "1", "link"])
+        .succeeds();
+    assert_eq!(value_of(&ts, "user.x", "f"), b"1");
+    // The user namespace is not available on symbolic links themselves.
+    ts.ucmd()
+        .args(&["-h", "-n", "user.y", "-v", "1", "link"])
+        .fails()
+        .stderr_only("setfattr: link: Operation not permitted\n");
+}
+
+#[test]
+fn test_dump_restore_roundtrip() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.mkdir_all("src/sub");
+    at.touch("src/sub/f");
+    ts.ucmd()
+        .args(&["-n", "user.text", "-v", "hello", "src"])
+        .succeeds();
+    ts.ucmd()
+        .args(&["-n", "user.bin", "-v", "0x00010203fe", "src/sub/f"])
+        .succeeds();
+    ts.ucmd()
+        .args(&["-n", "user.empty", "src/sub/f"])
+        .succeeds();
+    let dump = ts
+        .ccmd("getfattr")
+        .args(&["-R", "-d", "src"])
+        .succeeds()
+        .stdout_move_str();
+
+    // Restore onto a copy without attributes by renaming the files in the dump.
+    at.mkdir_all("dst/sub");
+    at.touch("dst/sub/f");
+    let dump = dump.replace("# file: src", "# file: dst");
+    at.write("dump.txt", &dump);
+    ts.ucmd()
+        .arg("--restore=dump.txt")
+        .succeeds()
+        .no_stdout()
+        .no_stderr();
+    ts.ccmd("getfattr")
+        .args(&["-R", "-d", "dst"])
+        .succeeds()
+        .stdout_only(dump);
+}
+
+#[test]
+fn test_restore_malformed() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.touch("f");
+    ts.ucmd()
+        .arg("--restore=-")
+        .pipe_in("user.x=\"1\"\n")
+        .fails()
+        .stderr_only("setfattr: No filename found in line 1, aborting\n");
+    // A bad line anywhere keeps the whole dump from being applied.
+    ts.ucmd()
+        .arg("--restore=-")
+        .pipe_in("# file: f\nuser.ok=\"1\"\nuser.bad=0xZZ\n")
+        .fails()
+        .stderr_only("setfattr: bad input encoding in line 3\n");
+    ts.ccmd("getfattr").args(&["-n", "user.ok", "f"]).fails();
+}
+
+#[test]
+fn test_invalid_usage() {
+    new_ucmd!()
+        .args(&["-n", "user.x", "-x", "user.y", "f"])
+        .fails()
+        .usage_error("Only one of -n, -x or --restore allowed");
+    new_ucmd!()
+        .args(&["-v", "1", "f"])
+        .fails()
+        .usage_error("-v only allowed with -n");
+    new_ucmd!()
+        .args(&["-n", "user.x"])
+        .fails()
+        .usage_error("missing operand");
+}
@@ -242,3 +242,208 @@ fn test_setsid_ctty() {
         println!("TEST SKIPPED");
     }
 }
+
+#[test]
+fn test_setsid_pidfile() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    let result = ucmd
+        .args(&["-f", "-w", "--pidfile", "setsid.pid", "sh", "-c", "echo $$"])
+        .succeeds();
+    assert_eq!(at.read("setsid.pid"), result.stdout_str());
+    // No temporary file is left behind.
+    assert_eq!(std::fs::read_dir(at.as_string()).unwrap().count(), 1);
+
+    new_ucmd!()
+        .args(&["-f", "-w", "--pidfile", "/no/such/dir/setsid.pid", "true"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("cannot write pidfile /no/such/dir/setsid.pid");
+}
+
+#[test]
+fn test_setsid_stdio() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("out.log", "old\n");
+    ucmd.args(&[
+        "-f",
+        "-w",
+        "--stdio",
+        "out.log",
+        "sh",
+        "-c",
+        "cat; echo out; echo err >&2",
+    ])
+    .pipe_in("not read\n")
+    .succeeds()
+    .no_stdout()
+    .no_stderr();
+    assert_eq!(at.read("out.log"), "old\nout\nerr\n");
+}
+
+#[test]
+fn test_setsid_chdir_umask() {
+    new_ucmd!()
+        .args(&[
+            "-f",
+            "-w",
+            "--chdir",
+            "/",
+            "--umask",
+            "027",
+            "sh",
+            "-c",
+            "pwd; umask",
+        ])
+        .succeeds()
+        .stdout_is("/\n0027\n");
+    new_ucmd!()
+        .args(&["--umask", "1000", "true"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("invalid umask: '1000'");
+    new_ucmd!()
+        .args(&["-f", "-w", "--chdir", "/no/such/dir", "true"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("cannot change directory to /no/such/dir");
+}
+
+#[test]
+fn test_setsid_close_fds() {
+    let test_scenario = TestScenario::new(util_name!());
+    // Leak fd 5 into setsid, it must not reach the program.
+    let script = |option: &str| {
+        format!(
+            "exec 5</dev/null; {} {} -f -w {} sh -c 'ls /proc/$$/fd'",
+            test_scenario.bin_path.display(),
+            test_scenario.util_name,
+            option
+        )
+    };
+    test_scenario
+        .cmd("sh")
+        .args(&["-c", &script("")])
+        .succeeds()
+        .stdout_contains("5\n");
+    test_scenario
+        .cmd("sh")
+        .args(&["-c", &script("--close-fds")])
+        .succeeds()
+        .stdout_is("0\n1\n2\n");
+}
+
+#[test]
+fn test_setsid_signal_exit_code() {
+    new_ucmd!()
+        .args(&["-f", "-w", "sh", "-c", "kill -TERM $$"])
+        .fails()
+        .code_is(128 + 15);
+}
+
+// Namespaces need privileges or unprivileged user namespaces, skip otherwise.
+fn namespaces_unsupported(result: &CmdResult) -> bool {
+    if result.stderr_str().contains("unshare failed") {
+        println!("TEST SKIPPED: {}", result.stderr_str());
+        return true;
+    }
+    false
+}
+
+#[test]
+fn test_setsid_pid_namespace() {
+    let result = new_ucmd!()
+        .args(&[
+            "-w",
+            "--pid",
+            "--mount-proc",
+            "sh",
+            "-c",
+            "echo $$; readlink /proc/self",
+        ])
+        .run();
+    if namespaces_unsupported(&result) {
+        return;
+    }
+    // The program runs as pid 2 below the init, readlink is the third process.
+    result.success().stdout_is("2\n3\n");
+}
+
+#[test]
+fn test_setsid_pid_namespace_exit_code() {
+    let result = new_ucmd!()
+        .args(&["-w", "--pid", "sh", "-c", "exit 3"])
+        .run();
+    if namespaces_unsupported(&result) {
+        return;
+    }
+    result.code_is(3);
+    new_ucmd!()
+        .args(&["-w", "--pid", "sh", "-c", "kill -TERM $$"])
+        .fails()
+        .code_is(128 + 15);
+}
+
+#[test]
+fn test_setsid_pid_namespace_forward_signal() {
+    let test_scenario = TestScenario::new(util_name!());
+    let mut child = test_scenario
+        .ucmd()
+        .args(&["-w", "--pid", "sleep", "10"])
+        .run_no_wait();
+    std::thread::sleep(Duration::from_millis(500));
+    nix::sys::signal::kill(
+        Pid::from_raw(child.id() as i32),
+        nix::sys::signal::Signal::SIGTERM,
+    )
+    .unwrap();
+    let status = child.wait().unwrap();
+    if status.code() == Some(1) {
+        println!("TEST SKIPPED: cannot unshare pid namespace");
+        return;
+    }
+    assert_eq!(status.code(), Some(128 + 15));
+}
+
+#[test]
+fn test_setsid_user_namespace_mapping() {
+    let result = new_ucmd!()
+        .args(&["-w", "--map-root-user", "sh", "-c", "id -u; id -g"])
+        .run();
+    if namespaces_unsupported(&result) {
+        return;
+    }
+    result.success().stdout_is("0\n0\n");
+    new_ucmd!()
+        .args(&[
+            "-w",
+            "--map-user",
+            "1000",
+            "--map-group",
+            "100",
+            "sh",
+            "-c",
+            "id -u; id -g",
+        ])
+        .succeeds()
+        .stdout_is("1000\n100\n");
+    new_ucmd!()
+        .args(&["--map-user", "root", "true"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("invalid user id: 'root'");
+}
+
+#[test]
+fn test_setsid_uts_namespace() {
+    let result = new_ucmd!()
+        .args(&["-w", "--uts", "sh", "-c", "hostname setsid-test; hostname"])
+        .run();
+    if namespaces_unsupported(&result) {
+        return;
+    }
+    result.success().stdout_is("setsid-test\n");
+    assert_ne!(
+        std::fs::read_to_string("/proc/sys/kernel/hostname").unwrap(),
+        "setsid-test\n"
+    );
+}
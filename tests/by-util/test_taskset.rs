@@ -165,3 +165,120 @@ fn test_set_invalid_mask_for_pid() {
         .stdout_is_bytes(c_res.stdout())
         .stderr_is_bytes(c_res.stderr());
 }
+
+#[test]
+fn test_explain_grouped_mask() {
+    new_ucmd!()
+        .args(&["--explain", "0x1,00000003"])
+        .succeeds()
+        .stdout_is("list: 0,1,32\nmask: 100000003\n");
+    new_ucmd!()
+        .args(&["--explain", "3,00000001"])
+        .succeeds()
+        .stdout_is("list: 0,32,33\nmask: 300000001\n");
+    new_ucmd!()
+        .args(&["--explain", "1,100000000"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("failed to parse CPU mask: 1,100000000");
+    // a short group is not padded to 32 bits
+    for mask in ["f,ff", "3,1", "1,00000003,f"] {
+        new_ucmd!()
+            .args(&["--explain", mask])
+            .fails()
+            .code_is(1)
+            .stderr_contains(format!("failed to parse CPU mask: {}", mask));
+    }
+}
+
+#[test]
+fn test_explain_stride_list() {
+    new_ucmd!()
+        .args(&["--explain", "-c", "0-15:4,17"])
+        .succeeds()
+        .stdout_is("0-15:4: 0,4,8,12\n17: 17\nlist: 0,4,8,12,17\nmask: 21111\n");
+    new_ucmd!()
+        .args(&["--explain", "-c", "0-3:0"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("failed to parse CPU list: 0-3:0");
+}
+
+#[test]
+fn test_explain_topology_selectors() {
+    let node_cpus = std::fs::read_to_string("/sys/devices/system/node/node0/cpulist");
+    if let Ok(node_cpus) = node_cpus {
+        new_ucmd!()
+            .args(&["--explain", "-c", "node:0"])
+            .succeeds()
+            .stdout_contains(format!("node:0: {}\n", node_cpus.trim()));
+    }
+    new_ucmd!()
+        .args(&["--explain", "-c", "node:4096"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("failed to parse CPU list: node:4096");
+    // CPU 0 is always the lowest thread of core 0
+    new_ucmd!()
+        .args(&["--explain", "-c", "core:0,smt-siblings-off"])
+        .succeeds()
+        .stdout_contains("smt-siblings-off: keep one thread per core\nlist: 0\n");
+}
+
+#[test]
+fn test_topology_selector_launch_new_process() {
+    new_ucmd!()
+        .args(&["-c", "smt-siblings-off", "sleep", "0"])
+        .succeeds()
+        .no_stdout();
+}
+
+#[test]
+fn test_tree_dry_run() {
+    let mut child = std::process::Command::new("sh")
+        .args(["-c", "sleep 10 & wait"])
+        .spawn()
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    let pid = child.id().to_string();
+    let res = new_ucmd!()
+        .args(&["--tree", "--dry-run", "-pc", "0", &pid])
+        .succeeds();
+    let stdout = res.stdout_str();
+    child.kill().unwrap();
+    child.wait().unwrap();
+    // The shell and its sleep child, each with current and planned lines
+    assert_eq!(stdout.lines().count(), 4, "{}", stdout);
+    assert!(stdout.starts_with(&format!("pid {}'s current affinity list: ", pid)));
+    assert!(stdout.contains(&format!("pid {}'s planned affinity list: 0\n", pid)));
+}
+
+#[test]
+fn test_cgroup_dry_run() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    let mypid = std::process::id();
+    at.mkdir_all("cg/child");
+    at.write("cg/cgroup.procs", &format!("{}\n", mypid));
+    at.write("cg/child/cgroup.procs", "");
+    ucmd.args(&["--cgroup", &at.plus_as_string("cg"), "--dry-run", "-c", "0"])
+        .succeeds()
+        .stdout_contains(format!("pid {}'s planned affinity list: 0\n", mypid));
+}
+
+#[test]
+fn test_cgroup_invalid_usage() {
+    new_ucmd!()
+        .args(&["--cgroup", "/no/such/cgroup"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("cannot read cgroup /no/such/cgroup");
+    new_ucmd!()
+        .args(&["--cgroup", "/no/such/cgroup", "-p", "1"])
+        .fails()
+        .code_is(1);
+    new_ucmd!()
+        .args(&["--dry-run", "1", "true"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("--dry-run cannot be used to launch a command");
+}
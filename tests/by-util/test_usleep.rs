@@ -144,3 +144,116 @@ fn test_usleep_extra_operand() {
         .code_is(expected_code)
         .usage_error(expected_usage_error);
 }
+
+// Check the run took about expected_micros, returning the measured time.
+fn assert_sleep_time(start_time: SystemTime, expected_micros: u128) -> u128 {
+    let time_diff_micros = SystemTime::now()
+        .duration_since(start_time)
+        .unwrap()
+        .as_micros();
+    if time_diff_micros.abs_diff(expected_micros) > SLEEP_OVERHEAD_MICROSECONDS {
+        panic!(
+            "expect sleep for {} microseconds, actually sleep for {} microseconds",
+            expected_micros, time_diff_micros
+        );
+    }
+    time_diff_micros
+}
+
+#[test]
+fn test_usleep_unit_suffixes() {
+    for (interval, micros, seconds) in [
+        ("300ms", 300000, "0.3"),
+        ("0.25s", 250000, "0.25"),
+        ("200000us", 200000, "0.2"),
+        ("150000000ns", 150000, "0.15"),
+        ("0.005m", 300000, "0.3"),
+        ("1.5e5", 150000, "0.15"),
+    ] {
+        let start_time = SystemTime::now();
+        new_ucmd!().arg(interval).succeeds().stderr_is(format!(
+            "warning: usleep is deprecated, and will be removed in near future!\n\
+             warning: use \"sleep {}\" instead...\n",
+            seconds
+        ));
+        assert_sleep_time(start_time, micros);
+    }
+}
+
+#[test]
+fn test_usleep_usage() {
+    new_ucmd!().arg("--usage").succeeds().stdout_only(
+        "Usage: usleep [-vo?] [-v|--version] [-o|--oot] [-?|--help] [--usage] \
+         [--clock=clock] [--report] [microseconds | interval]\n       \
+         usleep [--clock=clock] [--report] --until=time\n",
+    );
+}
+
+#[test]
+fn test_usleep_report() {
+    let result = new_ucmd!()
+        .args(&["--report", "--clock=boottime", "200ms"])
+        .succeeds();
+    let re = regex::Regex::new(r"^slept 0\.2\d{8} s, overshoot \d+\.\d{3} us\n$").unwrap();
+    assert!(re.is_match(result.stdout_str()), "{}", result.stdout_str());
+}
+
+#[test]
+fn test_usleep_until() {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs_f64();
+    let start_time = SystemTime::now();
+    new_ucmd!()
+        .args(&["--until", &format!("@{:.6}", now + 0.3)])
+        .succeeds()
+        .no_stdout()
+        .no_stderr();
+    assert_sleep_time(start_time, 300000);
+
+    // A deadline in the past returns at once.
+    let start_time = SystemTime::now();
+    new_ucmd!()
+        .args(&["--until", "2000-01-01T00:00:00Z"])
+        .succeeds();
+    assert_sleep_time(start_time, 0);
+
+    let uptime = std::fs::read_to_string("/proc/uptime").unwrap();
+    let uptime: f64 = uptime.split(' ').next().unwrap().parse().unwrap();
+    let start_time = SystemTime::now();
+    new_ucmd!()
+        .args(&[
+            "--clock",
+            "boottime",
+            "--until",
+            &format!("{:.3}s", uptime + 0.3),
+        ])
+        .succeeds();
+    assert_sleep_time(start_time, 300000);
+
+    new_ucmd!()
+        .args(&["--until", "tomorrow"])
+        .fails()
+        .code_is(2)
+        .usage_error("invalid deadline 'tomorrow'");
+    new_ucmd!()
+        .args(&["--until", "1", "1000"])
+        .fails()
+        .code_is(1);
+}
+
+#[test]
+fn test_usleep_resume_after_stop() {
+    let test_scenario = TestScenario::new(util_name!());
+    let start_time = SystemTime::now();
+    let mut child = test_scenario.ucmd().arg("600ms").run_no_wait();
+    let pid = nix::unistd::Pid::from_raw(child.id() as i32);
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    nix::sys::signal::kill(pid, nix::sys::signal::Signal::SIGSTOP).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    nix::sys::signal::kill(pid, nix::sys::signal::Signal::SIGCONT).unwrap();
+    assert!(child.wait().unwrap().success());
+    // The deadline stays where it was, the stop does not add to the sleep.
+    assert_sleep_time(start_time, 600000);
+}
@@ -286,3 +286,257 @@ fn test_read_functions_skip_functions() {
         None,
     );
 }
+
+#[test]
+fn test_shell_builtins_and_keywords() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.mkdir("types");
+    at.touch("types/cd");
+    at.set_mode("types/cd", 0o755);
+    let path_env = at.plus_as_string("types");
+    ts.ucmd()
+        .env("PATH", &path_env)
+        .args(&["--shell=bash", "if", "cd", "[["])
+        .succeeds()
+        .stdout_only(
+            "if: shell reserved word\ncd: shell built-in command\n[[: shell reserved word\n",
+        );
+    ts.ucmd()
+        .env("PATH", &path_env)
+        .args(&["--shell=/bin/sh", "-a", "cd", "[["])
+        .fails()
+        .code_is(1)
+        .stdout_is(format!("cd: shell built-in command\n{}/cd\n", path_env));
+    ts.ucmd()
+        .args(&["--shell=fish", "cd"])
+        .fails()
+        .usage_error("unsupported shell 'fish'");
+}
+
+#[test]
+fn test_type() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.mkdir("types");
+    at.touch("types/ls");
+    at.set_mode("types/ls", 0o755);
+    let path_env = at.plus_as_string("types");
+    let stdin = "alias ll='ls -l'\nbuiltin hash -p /opt/ls ls\n";
+    ts.ucmd()
+        .env("PATH", &path_env)
+        .args(&["--type", "--read-alias", "--shell=bash", "ll", "ls", "cd"])
+        .pipe_in(stdin)
+        .succeeds()
+        .stdout_only("ll is aliased to `ls -l'\nls is hashed (/opt/ls)\ncd is a shell builtin\n");
+    ts.ucmd()
+        .env("PATH", &path_env)
+        .args(&["--type", "-a", "--read-alias", "ll", "ls", "nosuch"])
+        .pipe_in(stdin)
+        .fails()
+        .code_is(1)
+        .stdout_is(format!("ll is aliased to `ls -l'\nls is {}/ls\n", path_env))
+        .stderr_is("which: nosuch: not found\n");
+}
+
+#[test]
+fn test_posix() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.mkdir("types");
+    at.touch("types/ls");
+    at.set_mode("types/ls", 0o755);
+    let path_env = at.plus_as_string("types");
+    ts.ucmd()
+        .env("PATH", &path_env)
+        .args(&[
+            "--posix",
+            "--read-alias",
+            "--shell=dash",
+            "hi",
+            "cd",
+            "ls",
+            "nosuch",
+        ])
+        .pipe_in("alias hi='echo '\\''x'\\'''\n")
+        .fails()
+        .code_is(1)
+        .stdout_only(format!(
+            "alias hi='echo '\\''x'\\'''\ncd\n{}/ls\n",
+            path_env
+        ));
+    // The quotes in `it's` are shell quoting, so like the shell the lexer
+    // reads this as an alias named `its`; there is no alias `it's`.
+    let stdin = "alias it's='echo '\\''x'\\'''\n";
+    ts.ucmd()
+        .env("PATH", &path_env)
+        .args(&["--posix", "--read-alias", "--shell=dash", "it's"])
+        .pipe_in(stdin)
+        .fails()
+        .code_is(1)
+        .no_stdout();
+    ts.ucmd()
+        .env("PATH", &path_env)
+        .args(&["--posix", "--read-alias", "--shell=dash", "its"])
+        .pipe_in(stdin)
+        .succeeds()
+        .stdout_contains("alias its=");
+    ts.ucmd()
+        .args(&["--posix", "--type", "ls"])
+        .fails()
+        .code_is(1);
+}
+
+#[test]
+fn test_why() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.mkdir_all("why/plain");
+    at.mkdir_all("why/dir/foo");
+    at.mkdir_all("why/dangling");
+    at.mkdir_all("why/link");
+    at.mkdir_all("why/real");
+    at.touch("why/plain/foo");
+    at.set_mode("why/plain/foo", 0o644);
+    at.relative_symlink_file("/nonexistent", "why/dangling/foo");
+    at.touch("why/real/foo");
+    at.set_mode("why/real/foo", 0o755);
+    at.relative_symlink_file("../real/foo", "why/link/foo");
+    let dir = at.plus_as_string("why");
+    let path_env = ["missing", "plain", "dir", "dangling", "link", "real"]
+        .iter()
+        .map(|d| format!("{}/{}", dir, d))
+        .collect::<Vec<_>>()
+        .join(":");
+    ts.ucmd()
+        .env("PATH", &path_env)
+        .args(&["--why", "foo"])
+        .succeeds()
+        .stdout_contains(format!("{}/missing/foo: no such directory\n", dir))
+        .stdout_contains(format!("{}/plain/foo: not executable: ", dir))
+        .stdout_contains(format!("{}/dir/foo: is a directory\n", dir))
+        .stdout_contains(format!(
+            "{}/dangling/foo: dangling symlink to '/nonexistent'\n",
+            dir
+        ))
+        .stdout_contains(format!("{}/link/foo: found\n", dir))
+        .stdout_contains(format!("{}/real/foo: found, but shadowed\n", dir))
+        .stdout_contains(format!(
+            "\t{0}/link/foo -> {0}/link/../real/foo -> {0}/real/foo\n",
+            dir
+        ));
+    ts.ucmd()
+        .env("PATH", format!("{}/plain", dir))
+        .args(&["--explain", "foo"])
+        .fails()
+        .code_is(1)
+        .stderr_is(format!("which: no foo in ({}/plain)\n", dir));
+}
+
+/// Make a PATH directory holding the commands of the definitions fixtures
+fn definitions_path(ts: &TestScenario) -> String {
+    let at = &ts.fixtures;
+    at.mkdir("defs");
+    for cmd in ["ls", "git", "cat"] {
+        let path = format!("defs/{}", cmd);
+        at.touch(&path);
+        at.set_mode(&path, 0o755);
+    }
+    at.plus_as_string("defs")
+}
+
+#[test]
+fn test_bash_definitions() {
+    let ts = TestScenario::new(util_name!());
+    let path_env = definitions_path(&ts);
+    ts.ucmd()
+        .env("PATH", &path_env)
+        .args(&["--read-alias", "--read-functions", "multi", "quoted", "sub"])
+        .pipe_in_fixture("bash_definitions.in")
+        .succeeds()
+        .stdout_only(format!(
+            "alias multi='ls one\ngit two'\n\t{0}/ls\n\t{0}/git\n\
+             alias quoted='echo '\\''}}'\\'' | git log'\n\t{0}/git\n\
+             sub ()\n{{ \n    ( cd /tmp;\n    case $1 in \n        a)\n            ls\n        ;;\n    esac )\n}}\n",
+            path_env
+        ));
+    let result = ts
+        .ucmd()
+        .env("PATH", &path_env)
+        .args(&["--type", "--read-alias", "--read-functions", "greet", "git"])
+        .pipe_in_fixture("bash_definitions.in")
+        .succeeds();
+    let stdout = result.stdout_str();
+    assert!(stdout.starts_with("greet is a function\ngreet ()\n{ \n"));
+    assert!(stdout.contains("unbalanced {\nEOF\n\n}\ngit is hashed (/opt/bin/git)\n"));
+}
+
+#[test]
+fn test_zsh_definitions() {
+    let ts = TestScenario::new(util_name!());
+    let path_env = definitions_path(&ts);
+    ts.ucmd()
+        .env("PATH", &path_env)
+        .args(&["--read-alias", "--read-functions", "g c", "greet", "inline"])
+        .pipe_in_fixture("zsh_definitions.in")
+        .succeeds()
+        .stdout_only(format!(
+            "'g c'='git commit'\n\t{}/git\n\
+             greet () {{\n\tlocal brace='}}'\n\tprint -r -- \"${{1:-world}} {{\"\n\
+             \tif [[ -n $1 ]]\n\tthen\n\t\techo yes\n\tfi\n}}\n\
+             inline () {{ echo inline; }}\n",
+            path_env
+        ));
+    ts.ucmd()
+        .env("PATH", &path_env)
+        .args(&["--posix", "--read-alias", "g c", "ll"])
+        .pipe_in_fixture("zsh_definitions.in")
+        .succeeds()
+        .stdout_only("alias g c='git commit'\nalias ll='ls -l'\n");
+}
+
+#[test]
+fn test_fish_definitions() {
+    let ts = TestScenario::new(util_name!());
+    let path_env = definitions_path(&ts);
+    ts.ucmd()
+        .env("PATH", &path_env)
+        .args(&[
+            "--type",
+            "--read-alias",
+            "--read-functions",
+            "say",
+            "oneline",
+        ])
+        .pipe_in_fixture("fish_definitions.in")
+        .succeeds()
+        .stdout_only(
+            "say is aliased to `echo 'hi''\n\
+             oneline is a function\nfunction oneline; echo x; end\n",
+        );
+    let result = ts
+        .ucmd()
+        .env("PATH", &path_env)
+        .args(&["--read-alias", "--read-functions", "ll", "greet"])
+        .pipe_in_fixture("fish_definitions.in")
+        .succeeds();
+    let stdout = result.stdout_str();
+    assert!(stdout.starts_with(&format!("alias ll 'ls -l'\n\t{}/ls\n", path_env)));
+    assert!(stdout.contains("function greet --description 'say \\'hi\\' {'\n"));
+    assert!(stdout.ends_with("    for i in 1 2; echo $i; end\nend\n"));
+}
+
+#[test]
+fn test_malformed_definitions() {
+    let ts = TestScenario::new(util_name!());
+    ts.ucmd()
+        .args(&["--read-functions", "broken"])
+        .pipe_in_fixture("malformed_definitions.in")
+        .fails()
+        .stderr_only("which: unterminated quoted string in line 4\n");
+    ts.ucmd()
+        .args(&["--read-functions", "broken"])
+        .pipe_in("broken () \n{ \n    echo\n")
+        .fails()
+        .stderr_only("which: unterminated function 'broken' in line 1\n");
+}
@@ -1832,3 +1832,385 @@ fn test_xargs_trace() {
 
     assert_eq!(expect_result.stdout_str(), actual_result.stdout_str());
 }
+
+// Run xargs under script(1) so that it has a controlling terminal fed from
+// `input`. Returns None where script(1) is not available.
+fn run_on_tty(ts: &TestScenario, args: &str, input: &str) -> Option<CmdResult> {
+    if !std::path::Path::new("/usr/bin/script").exists() {
+        return None;
+    }
+    let command = format!("{} xargs {}", ts.bin_path.display(), args);
+    Some(
+        ts.cmd_keepenv("/usr/bin/script")
+            .args(&["-qec", &command, "/dev/null"])
+            .pipe_in(input)
+            .run(),
+    )
+}
+
+// Run xargs in a new session, without a controlling terminal.
+fn run_without_tty(ts: &TestScenario, args: &[&str]) -> CmdResult {
+    let bin_path = ts.bin_path.display().to_string();
+    ts.cmd_keepenv(&bin_path)
+        .args(&["setsid", "-w", &bin_path, "xargs"])
+        .args(args)
+        .run()
+}
+
+#[test]
+fn test_xargs_interactive() {
+    let ts = TestScenario::new("xargs");
+    ts.fixtures.write("args", "a\nb\nc\nd\n");
+    // All answers are typed ahead, none may get lost between prompts. The
+    // terminal echoes them at any time, so look at what ran in a file.
+    if let Some(result) = run_on_tty(
+        &ts,
+        "-n1 -a args -p sh -c 'echo $0 >>ran'",
+        "y\nn\nYes\nnope\n",
+    ) {
+        result.success().stdout_contains(" a ?...");
+        assert_eq!(ts.fixtures.read("ran"), "a\nc\n");
+    }
+}
+
+#[test]
+fn test_xargs_interactive_without_tty() {
+    let ts = TestScenario::new("xargs");
+    ts.fixtures.write("args", "a\n");
+    run_without_tty(&ts, &["-a", "args", "-p", "echo"])
+        .failure()
+        .code_is(1)
+        .stderr_contains("failed to open /dev/tty for reading")
+        .no_stdout();
+}
+
+#[test]
+fn test_xargs_open_tty() {
+    let ts = TestScenario::new("xargs");
+    ts.fixtures.write("args", "a\n");
+    if let Some(result) = run_on_tty(&ts, "-o -a args bash ask_user.sh", "World\n") {
+        result.success().stdout_contains("Welcome, World!");
+    }
+}
+
+#[test]
+fn test_xargs_open_tty_without_tty() {
+    let ts = TestScenario::new("xargs");
+    ts.fixtures.write("args", "a\n");
+    run_without_tty(
+        &ts,
+        &["-o", "-a", "args", "sh", "-c", "test -t 0 || echo no tty"],
+    )
+    .success()
+    .stdout_is("no tty\n")
+    .stderr_contains("xargs: /dev/tty: ");
+}
+
+#[test]
+fn test_xargs_optional_arguments_attached() {
+    new_ucmd!()
+        .args(&["-i", "echo", "x{}y"])
+        .pipe_in("a b\nc d\n")
+        .succeeds()
+        .stdout_only("xa by\nxc dy\n");
+    new_ucmd!()
+        .args(&["-tiX", "echo", "xXy"])
+        .pipe_in("a\n")
+        .succeeds()
+        .stdout_is("xay\n")
+        .stderr_is("echo xay\n");
+    new_ucmd!()
+        .args(&["--replace", "echo", "x{}y"])
+        .pipe_in("a\n")
+        .succeeds()
+        .stdout_only("xay\n");
+    new_ucmd!()
+        .args(&["-l", "echo"])
+        .pipe_in("a b\nc d\ne\n")
+        .succeeds()
+        .stdout_only("a b\nc d\ne\n");
+    new_ucmd!()
+        .args(&["-rl2", "echo"])
+        .pipe_in("a b\nc d\ne\n")
+        .succeeds()
+        .stdout_only("a b c d\ne\n");
+    new_ucmd!()
+        .args(&["--max-lines", "echo"])
+        .pipe_in("a b\nc d\n")
+        .succeeds()
+        .stdout_only("a b\nc d\n");
+    new_ucmd!()
+        .args(&["-eSTOP", "echo"])
+        .pipe_in("a\nSTOP\nb\n")
+        .succeeds()
+        .stdout_only("a\n");
+    new_ucmd!()
+        .args(&["--eof", "echo"])
+        .pipe_in("a\n_\nb\n")
+        .succeeds()
+        .stdout_only("a _ b\n");
+}
+
+#[test]
+fn test_xargs_optional_arguments_detached() {
+    // A detached word is the command, not the argument of the option.
+    for args in [
+        ["-i", "X", "echo"],
+        ["-l", "2", "echo"],
+        ["-e", "STOP", "echo"],
+    ] {
+        new_ucmd!()
+            .args(&args)
+            .pipe_in("a\n")
+            .fails()
+            .code_is(127)
+            .stderr_only(format!("xargs: {}: No such file or directory\n", args[1]));
+    }
+    new_ucmd!()
+        .args(&["-I"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("requires a value");
+}
+
+#[test]
+fn test_xargs_max_lines_trailing_blanks() {
+    // A line ending in a blank continues on the next one.
+    new_ucmd!()
+        .args(&["-L1", "echo"])
+        .pipe_in("a b \nc d\ne\t\nf\n\ng\n")
+        .succeeds()
+        .stdout_only("a b c d\ne f\ng\n");
+}
+
+#[test]
+fn test_xargs_replace_whole_lines() {
+    new_ucmd!()
+        .args(&["-I{}", "echo", "[{}]"])
+        .pipe_in("  a   b  \n\n \"c  d\" 'e' \n")
+        .succeeds()
+        .stdout_only("[a   b  ]\n[c  d e ]\n");
+    // Without {} in the initial arguments the input is not passed on.
+    new_ucmd!()
+        .args(&["-I{}", "echo", "x"])
+        .pipe_in("a\nb\n")
+        .succeeds()
+        .stdout_only("x\nx\n");
+    new_ucmd!()
+        .args(&["-I{}"])
+        .pipe_in("a\n")
+        .succeeds()
+        .stdout_only("\n");
+}
+
+#[test]
+fn test_xargs_replace_command_name() {
+    new_ucmd!()
+        .args(&["-I", "CMD", "CMD", "[CMD]\\n"])
+        .pipe_in("echo\nprintf\n")
+        .succeeds()
+        .stdout_only("[echo]\\n\n[printf]\n");
+}
+
+#[test]
+fn test_xargs_replace_limit() {
+    // Like findutils, and unlike the 255 bytes POSIX permits, replacements
+    // are bounded only by the -s buffer.
+    let line = "x".repeat(100_000);
+    new_ucmd!()
+        .args(&["-I{}", "echo", "{}"])
+        .pipe_in(format!("{line}\n"))
+        .succeeds()
+        .stdout_only(format!("{line}\n"));
+    // Both replacements together do not fit into the command buffer.
+    new_ucmd!()
+        .args(&["-I{}", "echo", "{}", "{}"])
+        .pipe_in(format!("{line}\n"))
+        .fails()
+        .code_is(1)
+        .stderr_only("xargs: argument line too long\n");
+    new_ucmd!()
+        .args(&["-s", "20", "-I{}", "echo", "{}{}"])
+        .pipe_in("abcde\nabcdefgh\n")
+        .fails()
+        .code_is(1)
+        .stdout_is("abcdeabcde\n")
+        .stderr_is("xargs: argument line too long\n");
+}
+
+#[test]
+fn test_xargs_mutually_exclusive_options() {
+    new_ucmd!()
+        .args(&["-I{}", "-L1", "echo", "{}"])
+        .pipe_in("a\nb\n")
+        .succeeds()
+        .stdout_is("{} a\n{} b\n")
+        .stderr_is(
+            "xargs: warning: options --replace and -L are mutually exclusive, \
+            ignoring previous --replace value\n",
+        );
+    new_ucmd!()
+        .args(&["-I{}", "-l", "echo", "{}"])
+        .pipe_in("a\n")
+        .succeeds()
+        .stdout_is("{} a\n")
+        .stderr_is(
+            "xargs: warning: options --replace and --max-lines/-l are mutually exclusive, \
+            ignoring previous --replace value\n",
+        );
+    new_ucmd!()
+        .args(&["-L2", "-I{}", "echo", "{}"])
+        .pipe_in("a\nb\n")
+        .succeeds()
+        .stdout_is("a\nb\n")
+        .stderr_is(
+            "xargs: warning: options --max-lines and --replace/-I/-i are mutually exclusive, \
+            ignoring previous --max-lines value\n",
+        );
+    new_ucmd!()
+        .args(&["-n1", "-L2", "echo"])
+        .pipe_in("a b\nc d\ne\n")
+        .succeeds()
+        .stdout_is("a b c d\ne\n")
+        .stderr_is(
+            "xargs: warning: options --max-args and -L are mutually exclusive, \
+            ignoring previous --max-args value\n",
+        );
+    new_ucmd!()
+        .args(&["-i", "-n2", "echo", "{}"])
+        .pipe_in("a b\n")
+        .succeeds()
+        .stdout_is("{} a b\n")
+        .stderr_is(
+            "xargs: warning: options --replace and --max-args/-n are mutually exclusive, \
+            ignoring previous --replace value\n",
+        );
+    // -n1 is accepted together with -I.
+    new_ucmd!()
+        .args(&["-I{}", "-n1", "echo", "[{}]"])
+        .pipe_in("a b\nc\n")
+        .succeeds()
+        .stdout_only("[a b]\n[c]\n");
+}
+
+#[test]
+fn test_xargs_size_exceeded() {
+    // -L implies -x.
+    new_ucmd!()
+        .args(&["-L1", "-s", "12", "echo"])
+        .pipe_in("a b\nccc ddd\n")
+        .fails()
+        .code_is(1)
+        .stdout_is("a b\n")
+        .stderr_is("xargs: argument list too long\n");
+    new_ucmd!()
+        .args(&["-s", "10", "echo"])
+        .pipe_in("a\nbbbbbbbbbbbbbbb\n")
+        .fails()
+        .code_is(1)
+        .stdout_is("a\n")
+        .stderr_is("xargs: argument line too long\n");
+    new_ucmd!()
+        .args(&["-s", "4", "echo"])
+        .fails()
+        .code_is(1)
+        .stderr_only("xargs: cannot fit single argument within argument list size limit\n");
+}
+
+#[test]
+fn test_xargs_process_slot_var() {
+    new_ucmd!()
+        .args(&[
+            "-n1",
+            "--process-slot-var=SLOT",
+            "sh",
+            "-c",
+            "echo $SLOT $0",
+        ])
+        .pipe_in("a\nb\n")
+        .succeeds()
+        .stdout_only("0 a\n0 b\n");
+}
+
+#[test]
+fn test_xargs_show_limits() {
+    let result = new_ucmd!()
+        .args(&["--show-limits", "-s", "5000", "echo"])
+        .env("SHOW", "limits")
+        .pipe_in("a\n")
+        .succeeds();
+    result.stdout_is("a\n");
+    let stderr = result.stderr_str();
+    // The test environment holds just SHOW=limits.
+    assert!(stderr.starts_with("Your environment variables take up 12 bytes\n"));
+    assert!(stderr
+        .contains("POSIX smallest allowable upper limit on argument length (all systems): 4096\n"));
+    assert!(stderr.contains("Size of command buffer we are actually using: 5000\n"));
+    assert!(stderr.ends_with("Maximum parallelism (--max-procs must be no greater): 2147483647\n"));
+
+    new_ucmd!()
+        .args(&["--show-limits", "-r"])
+        .succeeds()
+        .stderr_contains("Size of command buffer we are actually using: 131072\n");
+}
+
+#[test]
+fn test_xargs_exit_status() {
+    new_ucmd!()
+        .args(&["-n1", "sh", "-c", "echo $0; exit 3"])
+        .pipe_in("a\nb\n")
+        .fails()
+        .code_is(123)
+        .stdout_only("a\nb\n");
+    new_ucmd!()
+        .args(&["-n1", "sh", "-c", "echo $0; exit 255"])
+        .pipe_in("a\nb\n")
+        .fails()
+        .code_is(124)
+        .stdout_is("a\n")
+        .stderr_is("xargs: sh: exited with status 255; aborting\n");
+    new_ucmd!()
+        .args(&["-n1", "sh", "-c", "kill -9 $$"])
+        .pipe_in("a\nb\n")
+        .fails()
+        .code_is(125)
+        .stderr_only("xargs: sh: terminated by signal 9\n");
+    new_ucmd!()
+        .args(&["/"])
+        .pipe_in("a\n")
+        .fails()
+        .code_is(126)
+        .stderr_only("xargs: /: Permission denied\n");
+    new_ucmd!()
+        .args(&["./missing"])
+        .pipe_in("a\n")
+        .fails()
+        .code_is(127)
+        .stderr_only("xargs: ./missing: No such file or directory\n");
+    // An end of file string does not hide earlier failures.
+    new_ucmd!()
+        .args(&["-n1", "-E", "STOP", "sh", "-c", "exit 3"])
+        .pipe_in("a\nSTOP\nb\n")
+        .fails()
+        .code_is(123);
+    new_ucmd!()
+        .args(&["-I{}", "sh", "-c", "exit {}"])
+        .pipe_in("0\n2\n0\n")
+        .fails()
+        .code_is(123);
+    new_ucmd!()
+        .args(&["echo"])
+        .pipe_in("\"a\n")
+        .fails()
+        .code_is(1)
+        .stderr_only(
+            "xargs: unmatched double quote; by default quotes are special to xargs \
+            unless you use the -0 option\n",
+        );
+    new_ucmd!()
+        .args(&["-a", "missing", "echo"])
+        .fails()
+        .code_is(1)
+        .stderr_only("xargs: Cannot open input file 'missing': No such file or directory\n");
+    new_ucmd!().args(&["--bogus"]).fails().code_is(1);
+}
@@ -9,10 +9,26 @@ mod test_attr;
 #[path = "by-util/test_base32.rs"]
 mod test_base32;
 
+#[cfg(feature = "base64")]
+#[path = "by-util/test_base64.rs"]
+mod test_base64;
+
+#[cfg(feature = "basenc")]
+#[path = "by-util/test_basenc.rs"]
+mod test_basenc;
+
+#[cfg(feature = "chrt")]
+#[path = "by-util/test_chrt.rs"]
+mod test_chrt;
+
 #[cfg(feature = "flock")]
 #[path = "by-util/test_flock.rs"]
 mod test_flock;
 
+#[cfg(feature = "getfattr")]
+#[path = "by-util/test_getfattr.rs"]
+mod test_getfattr;
+
 #[cfg(feature = "hwclock")]
 #[path = "by-util/test_hwclock.rs"]
 mod test_hwclock;
@@ -37,6 +53,18 @@ mod test_taskset;
 #[path = "by-util/test_setsid.rs"]
 mod test_setsid;
 
+#[cfg(feature = "setfattr")]
+#[path = "by-util/test_setfattr.rs"]
+mod test_setfattr;
+
+#[cfg(feature = "getfacl")]
+#[path = "by-util/test_getfacl.rs"]
+mod test_getfacl;
+
+#[cfg(feature = "setfacl")]
+#[path = "by-util/test_setfacl.rs"]
+mod test_setfacl;
+
 #[cfg(feature = "usleep")]
 #[path = "by-util/test_usleep.rs"]
 mod test_usleep;
@@ -52,3 +80,6 @@ mod test_xargs;
 #[cfg(feature = "free")]
 #[path = "by-util/test_free.rs"]
 mod test_free;
+
+#[path = "by-util/test_easybox.rs"]
+mod test_easybox;